@@ -0,0 +1,46 @@
+//! Step throughput of the headless simulation. Each iteration plays ten
+//! seconds at 60 steps a second from a fresh seeded start.
+
+use bevy::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pong_rs::{
+    arena::Arena,
+    sim::{simulate_steps, SimConfig},
+};
+
+const STEPS: u64 = 600;
+
+fn single_ball(c: &mut Criterion) {
+    let config = SimConfig::default();
+    c.bench_function("single ball", |b| {
+        b.iter(|| simulate_steps(black_box(config.clone()), 0, STEPS))
+    });
+}
+
+fn hundred_balls(c: &mut Criterion) {
+    let config = SimConfig {
+        balls: 100,
+        max_balls: 100,
+        ..default()
+    };
+    c.bench_function("100 balls", |b| {
+        b.iter(|| simulate_steps(black_box(config.clone()), 0, STEPS))
+    });
+}
+
+// every wall is checked against every ball each step, so a near-round arena
+// with ~100 walls stands in for a field full of obstacles
+fn obstacle_heavy(c: &mut Criterion) {
+    let config = SimConfig {
+        arena: Arena::regular(96, 320.),
+        balls: 10,
+        max_balls: 10,
+        ..default()
+    };
+    c.bench_function("obstacle heavy", |b| {
+        b.iter(|| simulate_steps(black_box(config.clone()), 0, STEPS))
+    });
+}
+
+criterion_group!(benches, single_ball, hundred_balls, obstacle_heavy);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! Dumps a headless simulation run as JSON lines for training paddle agents.
+//!
+//! `cargo run --release --example export_steps -- [--steps N] [--seed S]
+//! [--arena NAME] [--policy FILE] > steps.jsonl`
+//!
+//! Without `--policy` the paddle wanders at random; with one it plays the
+//! linear policy in the RON file (see `examples/tracker.policy.ron`).
+
+use std::io::{self, BufWriter};
+
+use pong_rs::{
+    agent::{export_steps, LinearPolicy, PaddleController, Wander},
+    arena::Arena,
+    sim::{SimConfig, Simulation},
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn main() -> io::Result<()> {
+    let steps = arg_value("--steps")
+        .and_then(|steps| steps.parse().ok())
+        .unwrap_or(10_000);
+    let seed = arg_value("--seed")
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or(0);
+    let arena = arg_value("--arena")
+        .and_then(|name| Arena::from_name(&name))
+        .unwrap_or_default();
+
+    let mut controller: Box<dyn PaddleController> = match arg_value("--policy") {
+        Some(path) => {
+            let text = std::fs::read_to_string(&path)?;
+            let policy = LinearPolicy::from_ron(&text).map_err(|err| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {err}"))
+            })?;
+            Box::new(policy)
+        }
+        None => Box::new(Wander(StdRng::seed_from_u64(seed))),
+    };
+
+    let mut sim = Simulation::new(
+        SimConfig {
+            arena,
+            ..Default::default()
+        },
+        seed,
+    );
+    let mut out = BufWriter::new(io::stdout().lock());
+    export_steps(&mut sim, controller.as_mut(), steps, &mut out)?;
+    eprintln!("{steps} steps, {} goals conceded", sim.score());
+    Ok(())
+}
+
+fn arg_value(flag: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != flag).nth(1)
+}
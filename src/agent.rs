@@ -0,0 +1,149 @@
+//! Paddle agents for the headless simulation. [`export_steps`] runs a
+//! simulation and writes what the paddle saw and did each step as JSON lines,
+//! for training agents with outside tools; [`LinearPolicy`] plays back the
+//! simplest thing such training produces, a linear policy over the same
+//! features, loaded from RON.
+
+use std::io::{self, Write};
+
+use rand::{rngs::StdRng, Rng};
+use serde::Deserialize;
+
+use crate::sim::{SimBall, Simulation};
+
+/// Length of an [`observe`] feature vector.
+pub const FEATURES: usize = 5;
+
+/// What the paddle sees: its own x, then the position and direction of the
+/// ball nearest its goal.
+pub fn observe(sim: &Simulation) -> [f32; FEATURES] {
+    let arena = &sim.config().arena;
+    let goal = arena.edge(arena.goals[0]);
+    let distance = |ball: &SimBall| goal.signed_distance(ball.translation.truncate());
+    let nearest = sim
+        .balls()
+        .iter()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)));
+
+    let (position, dir) = nearest.map_or(Default::default(), |ball| {
+        (ball.translation.truncate(), ball.speed.dir.truncate())
+    });
+    [sim.paddle().x, position.x, position.y, dir.x, dir.y]
+}
+
+/// Picks the paddle's move each step, from -1 (full left) to 1 (full right).
+pub trait PaddleController {
+    fn act(&mut self, features: &[f32; FEATURES]) -> f32;
+}
+
+/// Moves at random, like [`Simulation::step`]'s own paddle.
+pub struct Wander(pub StdRng);
+
+impl PaddleController for Wander {
+    fn act(&mut self, _: &[f32; FEATURES]) -> f32 {
+        self.0.gen_range(-1.0..=1.0)
+    }
+}
+
+/// `tanh(weights · features + bias)`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct LinearPolicy {
+    pub weights: [f32; FEATURES],
+    pub bias: f32,
+}
+
+impl LinearPolicy {
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+}
+
+impl PaddleController for LinearPolicy {
+    fn act(&mut self, features: &[f32; FEATURES]) -> f32 {
+        let sum: f32 = self
+            .weights
+            .iter()
+            .zip(features)
+            .map(|(weight, feature)| weight * feature)
+            .sum();
+        (sum + self.bias).tanh()
+    }
+}
+
+/// Runs `steps` steps of `sim` under `controller`, writing one JSON object
+/// per step: `{"step", "observation", "action", "score"}`, where the score
+/// is the goals conceded once the step has run.
+pub fn export_steps(
+    sim: &mut Simulation,
+    controller: &mut dyn PaddleController,
+    steps: u64,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    for _ in 0..steps {
+        let observation = observe(sim);
+        let action = controller.act(&observation);
+        let step = sim.steps();
+        sim.step_with(action);
+
+        let observation: Vec<_> = observation.iter().map(f32::to_string).collect();
+        writeln!(
+            out,
+            r#"{{"step":{step},"observation":[{}],"action":{action},"score":{}}}"#,
+            observation.join(","),
+            sim.score(),
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::sim::SimConfig;
+
+    #[test]
+    fn tracking_policy_heads_for_the_ball() {
+        let mut policy =
+            LinearPolicy::from_ron("(weights: (-0.05, 0.05, 0., 0., 0.), bias: 0.)").unwrap();
+        assert!(policy.act(&[0., 100., 0., 0., 0.]) > 0.9);
+        assert!(policy.act(&[100., 0., 0., 0., 0.]) < -0.9);
+        assert_eq!(policy.act(&[20., 20., 50., 3., 4.]), 0.);
+    }
+
+    #[test]
+    fn tracking_beats_wandering() {
+        let conceded = |controller: &mut dyn PaddleController| {
+            let mut sim = Simulation::new(SimConfig::default(), 0);
+            export_steps(&mut sim, controller, 3_000, &mut io::sink()).unwrap();
+            sim.score()
+        };
+        let mut tracker = LinearPolicy {
+            weights: [-0.05, 0.05, 0., 0., 0.],
+            bias: 0.,
+        };
+        let mut wander = Wander(StdRng::seed_from_u64(0));
+        assert!(conceded(&mut tracker) < conceded(&mut wander));
+    }
+
+    #[test]
+    fn export_writes_a_line_per_step() {
+        let mut sim = Simulation::new(SimConfig::default(), 3);
+        let mut out = Vec::new();
+        export_steps(
+            &mut sim,
+            &mut Wander(StdRng::seed_from_u64(3)),
+            20,
+            &mut out,
+        )
+        .unwrap();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines.len(), 20);
+        assert!(lines[0].starts_with(r#"{"step":0,"observation":["#));
+        assert!(lines[19].starts_with(r#"{"step":19,"#));
+        assert_eq!(sim.steps(), 20);
+    }
+}
@@ -0,0 +1,168 @@
+//! Announcer: short clips on key moments, a call on every goal and another
+//! when a rally runs long. Lines share a cooldown so a burst of goals from a
+//! split ball doesn't stack them up; a line that comes up during the cooldown
+//! is dropped. The clips under `sounds/announcer/` are placeholder stingers
+//! until voice recordings with the same names replace them.
+//!
+//! There's no winning score yet, so no "match point" call.
+
+use bevy::prelude::*;
+
+use crate::event_log::GameplayEvent;
+
+// seconds between any two lines
+const LINE_COOLDOWN: f32 = 2.5;
+// paddle returns without a goal that count as an incredible rally
+const RALLY_HITS: u32 = 10;
+
+pub struct AnnouncerPlugin;
+
+impl Plugin for AnnouncerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AnnouncerSettings>()
+            .init_resource::<Announcer>()
+            .add_system(announce);
+    }
+}
+
+/// Audio options for the announcer.
+#[derive(Resource, Clone, Copy)]
+pub struct AnnouncerSettings {
+    pub enabled: bool,
+    pub volume: f32,
+}
+
+impl Default for AnnouncerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 0.8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Line {
+    IncredibleRally,
+    // goals win over rally calls in the same frame
+    Goal,
+}
+
+impl Line {
+    fn clip(self) -> &'static str {
+        match self {
+            Line::IncredibleRally => "sounds/announcer/incredible_rally.wav",
+            Line::Goal => "sounds/announcer/goal.wav",
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Announcer {
+    cooldown: f32,
+    rally: u32,
+}
+
+impl Announcer {
+    /// Counts the rally along and picks the line for this frame's events, if
+    /// the cooldown allows one.
+    fn hear<'a>(
+        &mut self,
+        events: impl IntoIterator<Item = &'a GameplayEvent>,
+        delta: f32,
+    ) -> Option<Line> {
+        self.cooldown = (self.cooldown - delta).max(0.);
+
+        let mut line = None;
+        for event in events {
+            let heard = match event {
+                GameplayEvent::PaddleHit { .. } => {
+                    self.rally += 1;
+                    (self.rally == RALLY_HITS).then_some(Line::IncredibleRally)
+                }
+                GameplayEvent::Goal { .. } => {
+                    self.rally = 0;
+                    Some(Line::Goal)
+                }
+                _ => None,
+            };
+            line = line.max(heard);
+        }
+
+        if self.cooldown > 0. {
+            return None;
+        }
+        if line.is_some() {
+            self.cooldown = LINE_COOLDOWN;
+        }
+        line
+    }
+}
+
+fn announce(
+    mut announcer: ResMut<Announcer>,
+    mut events: EventReader<GameplayEvent>,
+    settings: Res<AnnouncerSettings>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    timer: Res<Time>,
+) {
+    // keep counting while muted so turning it back on mid-rally stays accurate
+    let Some(line) = announcer.hear(events.iter(), timer.delta_seconds()) else {
+        return;
+    };
+    if settings.enabled {
+        audio.play_with_settings(
+            asset_server.load(line.clip()),
+            PlaybackSettings::ONCE.with_volume(settings.volume),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn goal() -> GameplayEvent {
+        GameplayEvent::Goal {
+            ball: Vec3::ZERO,
+            goal: 0,
+            score: (1, 0),
+        }
+    }
+
+    fn hit() -> GameplayEvent {
+        GameplayEvent::PaddleHit {
+            ball: Vec3::ZERO,
+            paddle: Vec3::ZERO,
+            dir: Vec3::Y,
+        }
+    }
+
+    #[test]
+    fn cooldown_drops_lines_until_it_runs_out() {
+        let mut announcer = Announcer::default();
+        assert_eq!(announcer.hear(&[goal()], 0.), Some(Line::Goal));
+        assert_eq!(announcer.hear(&[goal()], LINE_COOLDOWN / 2.), None);
+        assert_eq!(announcer.hear(&[goal()], LINE_COOLDOWN), Some(Line::Goal));
+    }
+
+    #[test]
+    fn long_rally_is_called_once() {
+        let mut announcer = Announcer::default();
+        let lines: Vec<_> = (0..2 * RALLY_HITS)
+            .filter_map(|_| announcer.hear(&[hit()], LINE_COOLDOWN))
+            .collect();
+        assert_eq!(lines, [Line::IncredibleRally]);
+    }
+
+    #[test]
+    fn goal_resets_the_rally() {
+        let mut announcer = Announcer::default();
+        for _ in 0..RALLY_HITS - 1 {
+            announcer.hear(&[hit()], 0.);
+        }
+        announcer.hear(&[goal()], 0.);
+        assert_eq!(announcer.hear(&[hit()], LINE_COOLDOWN), None);
+    }
+}
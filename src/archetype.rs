@@ -0,0 +1,74 @@
+//! Paddle archetypes: named sets of paddle stats picked before a match,
+//! defined in `assets/paddles.archetypes.ron`.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::special::Special;
+
+pub const ARCHETYPES_PATH: &str = "paddles.archetypes.ron";
+
+pub struct ArchetypePlugin;
+
+impl Plugin for ArchetypePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ArchetypeList>()
+            .init_asset_loader::<ArchetypeLoader>();
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Archetype {
+    pub name: String,
+    pub width: f32,
+    /// Movement speed relative to a standard paddle.
+    pub speed: f32,
+    pub special: Special,
+}
+
+impl Default for Archetype {
+    fn default() -> Self {
+        Self {
+            name: "Standard".to_owned(),
+            width: 100.,
+            speed: 1.,
+            special: Special::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "6c1b4d0e-8f1d-4a57-a0c6-3b5f2e7d9a41"]
+pub struct ArchetypeList {
+    pub archetypes: Vec<Archetype>,
+}
+
+/// The archetype the player settled on for this match.
+#[derive(Resource, Default)]
+pub struct ChosenArchetype(pub Archetype);
+
+#[derive(Default)]
+struct ArchetypeLoader;
+
+impl AssetLoader for ArchetypeLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let list: ArchetypeList = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["archetypes.ron"]
+    }
+}
@@ -0,0 +1,453 @@
+//! Arena geometry. The playfield is a convex polygon whose edges are either
+//! walls the ball bounces off or goal lines it escapes through. Goal lines
+//! are flat or upright, and paddles slide along them on x or y to match.
+//! Each arena is an [`ArenaFile`] under [`ARENAS_DIR`], so a new one is a
+//! new file rather than a new build.
+
+use std::{
+    f32::consts::{FRAC_PI_2, PI, TAU},
+    fs,
+};
+
+use bevy::{asset::FileAssetIo, prelude::*};
+use serde::Deserialize;
+
+pub const WALL_THICKNESS: f32 = 10.;
+/// Where the arena files are under the asset root, each `<name>.ron`.
+pub const ARENAS_DIR: &str = "assets/arenas";
+/// How far up the field from its goal line a paddle starts, unless the
+/// arena file says otherwise.
+pub const PADDLE_INSET: f32 = 10.;
+/// How far up the field from the first goal line the ball is served, unless
+/// the arena file says otherwise.
+pub const BALL_INSET: f32 = 50.;
+
+#[derive(Resource, Clone)]
+pub struct Arena {
+    /// Polygon vertices in counter-clockwise order.
+    pub vertices: Vec<Vec2>,
+    /// Indices of the edges (vertex `i` to vertex `i + 1`) that are goal lines.
+    pub goals: Vec<usize>,
+    pub paddle_inset: f32,
+    pub ball_inset: f32,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Self::square(600.)
+    }
+}
+
+impl Arena {
+    /// Box arena with the goal along the bottom edge.
+    pub fn square(size: f32) -> Self {
+        let half = size / 2.;
+        Self {
+            vertices: vec![
+                Vec2::new(-half, -half),
+                Vec2::new(half, -half),
+                Vec2::new(half, half),
+                Vec2::new(-half, half),
+            ],
+            goals: vec![0],
+            paddle_inset: PADDLE_INSET,
+            ball_inset: BALL_INSET,
+        }
+    }
+
+    /// Box arena with goals on the left and right edges, the classic layout.
+    /// The left one is the first goal.
+    pub fn classic(size: f32) -> Self {
+        Self {
+            goals: vec![3, 1],
+            ..Self::square(size)
+        }
+    }
+
+    /// Regular polygon with a flat bottom edge acting as the goal.
+    pub fn regular(sides: usize, radius: f32) -> Self {
+        let start = -PI / 2. - PI / sides as f32;
+        let vertices = (0..sides)
+            .map(|i| {
+                let angle = start + TAU * i as f32 / sides as f32;
+                Vec2::new(angle.cos(), angle.sin()) * radius
+            })
+            .collect();
+
+        Self {
+            vertices,
+            goals: vec![0],
+            paddle_inset: PADDLE_INSET,
+            ball_inset: BALL_INSET,
+        }
+    }
+
+    /// The arena in `assets/arenas/<name>.ron`, if there's one that reads as
+    /// a playable shape.
+    pub fn from_name(name: &str) -> Option<Self> {
+        ArenaFile::load(name).and_then(|file| file.arena()).ok()
+    }
+
+    pub fn edge(&self, index: usize) -> Edge {
+        Edge {
+            start: self.vertices[index],
+            end: self.vertices[(index + 1) % self.vertices.len()],
+        }
+    }
+
+    pub fn walls(&self) -> impl Iterator<Item = Edge> + '_ {
+        (0..self.vertices.len())
+            .filter(|i| !self.goals.contains(i))
+            .map(|i| self.edge(i))
+    }
+
+    pub fn goal_lines(&self) -> impl Iterator<Item = Edge> + '_ {
+        self.goals.iter().map(|&i| self.edge(i))
+    }
+
+    /// The axis a paddle on `goals[goal]` slides along: x for a flat goal
+    /// line, y for an upright one.
+    pub fn goal_axis(&self, goal: usize) -> Vec2 {
+        let goal = self.edge(self.goals[goal]);
+        let dir = goal.end - goal.start;
+        if dir.x.abs() >= dir.y.abs() {
+            Vec2::X
+        } else {
+            Vec2::Y
+        }
+    }
+
+    /// The rotation turning a paddle's top face toward the field from
+    /// `goals[goal]`.
+    pub fn paddle_rotation(&self, goal: usize) -> Quat {
+        let normal = self.edge(self.goals[goal]).normal();
+        Quat::from_rotation_z(normal.y.atan2(normal.x) - FRAC_PI_2)
+    }
+
+    /// `position` moved `step` along the axis of `goals[goal]`, kept where a
+    /// paddle of `half_width` still fits within the goal line's span.
+    pub fn slide(&self, goal: usize, position: Vec3, step: f32, half_width: f32) -> Vec3 {
+        let axis = self.goal_axis(goal);
+        let line = self.edge(self.goals[goal]);
+        let reach = line.length() / 2. - half_width;
+        let along = (position.truncate() - line.midpoint()).dot(axis);
+        let moved = (along + step).clamp(-reach, reach);
+        position + (axis * (moved - along)).extend(0.)
+    }
+
+    /// Where the paddle defending the first goal starts.
+    pub fn paddle_spawn(&self) -> Vec3 {
+        self.paddle_spawn_at(0)
+    }
+
+    /// Where the paddle defending `goals[goal]` starts.
+    pub fn paddle_spawn_at(&self, goal: usize) -> Vec3 {
+        self.goal_offset(goal, self.paddle_inset)
+    }
+
+    /// Where the ball is (re)placed after a point.
+    pub fn ball_spawn(&self) -> Vec3 {
+        self.goal_offset(0, self.ball_inset)
+    }
+
+    /// This arena with the edge across from the first goal made a second goal,
+    /// for two players. Odd-sided arenas have no such edge and come back as they were.
+    pub fn with_opposite_goal(mut self) -> Self {
+        let sides = self.vertices.len();
+        if sides % 2 == 0 && self.goals.len() == 1 {
+            self.goals.push((self.goals[0] + sides / 2) % sides);
+        }
+        self
+    }
+
+    /// How far the arena reaches from the first goal line.
+    pub fn depth(&self) -> f32 {
+        let goal = self.edge(self.goals[0]);
+        self.vertices
+            .iter()
+            .map(|&vertex| goal.signed_distance(vertex))
+            .fold(0., f32::max)
+    }
+
+    /// The part of the segment from `start` to `end` inside the arena, if any.
+    pub fn clip(&self, start: Vec2, end: Vec2) -> Option<(Vec2, Vec2)> {
+        let (mut enter, mut leave) = (0f32, 1f32);
+        // every edge's normal points inward, so each one cuts off a half-plane
+        for i in 0..self.vertices.len() {
+            let edge = self.edge(i);
+            let (from, to) = (edge.signed_distance(start), edge.signed_distance(end));
+            if from < 0. && to < 0. {
+                return None;
+            }
+            let crossing = from / (from - to);
+            if from < 0. {
+                enter = enter.max(crossing);
+            } else if to < 0. {
+                leave = leave.min(crossing);
+            }
+        }
+        (enter < leave).then(|| (start.lerp(end, enter), start.lerp(end, leave)))
+    }
+
+    /// The line across the middle of the field, halfway up from the first
+    /// goal, from wall to wall.
+    pub fn center_line(&self) -> Option<(Vec2, Vec2)> {
+        let goal = self.edge(self.goals[0]);
+        let center = goal.midpoint() + goal.normal() * self.depth() / 2.;
+        let along = goal.normal().perp();
+        let reach = self.vertices.iter().map(|v| v.length()).fold(0., f32::max) * 2.;
+        self.clip(center - along * reach, center + along * reach)
+    }
+
+    /// Whether `point` is in the half of the arena nearer the first goal.
+    pub fn in_own_half(&self, point: Vec2) -> bool {
+        let goal = self.edge(self.goals[0]);
+        goal.signed_distance(point) < self.depth() / 2.
+    }
+
+    fn goal_offset(&self, goal: usize, distance: f32) -> Vec3 {
+        let goal = self.edge(self.goals[goal]);
+        (goal.midpoint() + goal.normal() * distance).extend(0.)
+    }
+}
+
+/// An arena as its file under [`ARENAS_DIR`] describes it. Only `vertices`
+/// and `goals` are needed; `layout` names the scene under `assets/scenes`
+/// that dresses the field, the arena's own name if left out.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ArenaFile {
+    pub vertices: Vec<Vec2>,
+    pub goals: Vec<usize>,
+    #[serde(default)]
+    pub paddle_inset: Option<f32>,
+    #[serde(default)]
+    pub ball_inset: Option<f32>,
+    #[serde(default)]
+    pub layout: Option<String>,
+}
+
+impl ArenaFile {
+    /// Reads `assets/arenas/<name>.ron`, found where the asset server finds
+    /// its files rather than in whatever directory the game was started
+    /// from; what went wrong, path first, if it can't.
+    pub fn load(name: &str) -> Result<Self, String> {
+        let path = FileAssetIo::get_base_path().join(format!("{ARENAS_DIR}/{name}.ron"));
+        let shown = path.display();
+        let text = fs::read_to_string(&path).map_err(|err| format!("{shown}: {err}"))?;
+        // so the optional settings can be written as plain numbers
+        ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(&text)
+            .map_err(|err| format!("{shown}: {err}"))
+    }
+
+    /// The layout scene for the arena called `name`, relative to `assets/`.
+    pub fn layout(&self, name: &str) -> String {
+        format!("scenes/{}.scn.ron", self.layout.as_deref().unwrap_or(name))
+    }
+
+    /// The arena, unless the shape can't be played: it has to be convex,
+    /// its corners counter-clockwise, with at least one goal and every goal
+    /// one of its edges.
+    pub fn arena(&self) -> Result<Arena, String> {
+        let sides = self.vertices.len();
+        if sides < 3 {
+            return Err(format!("{sides} corners don't make a field"));
+        }
+        let turns: Vec<f32> = (0..sides)
+            .map(|i| {
+                let [a, b, c] = [i, i + 1, i + 2].map(|corner| self.vertices[corner % sides]);
+                let (along, next) = (b - a, c - b);
+                along.perp_dot(next).atan2(along.dot(next))
+            })
+            .collect();
+        // a star turns left at every corner too, but goes round more than once
+        let goes_round_once = (turns.iter().sum::<f32>() - TAU).abs() < 0.01;
+        if !turns.iter().all(|&turn| turn > 0.) || !goes_round_once {
+            return Err("the corners have to go counter-clockwise round a convex field".into());
+        }
+        if self.goals.is_empty() {
+            return Err("there's no goal".into());
+        }
+        if let Some(goal) = self.goals.iter().find(|&&goal| goal >= sides) {
+            return Err(format!("goal {goal} isn't one of the {sides} edges"));
+        }
+        Ok(Arena {
+            vertices: self.vertices.clone(),
+            goals: self.goals.clone(),
+            paddle_inset: self.paddle_inset.unwrap_or(PADDLE_INSET),
+            ball_inset: self.ball_inset.unwrap_or(BALL_INSET),
+        })
+    }
+}
+
+/// A single side of the arena polygon.
+#[derive(Component, Reflect, Default, Clone, Copy)]
+#[reflect(Component)]
+pub struct Edge {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Edge {
+    /// Unit normal pointing into the arena.
+    pub fn normal(&self) -> Vec2 {
+        (self.end - self.start).perp().normalize()
+    }
+
+    pub fn midpoint(&self) -> Vec2 {
+        (self.start + self.end) / 2.
+    }
+
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    /// Distance from the edge's line, positive on the arena side.
+    pub fn signed_distance(&self, point: Vec2) -> f32 {
+        (point - self.start).dot(self.normal())
+    }
+
+    /// The solid wall along this edge, placed with [`Edge::transform`].
+    pub fn collider(&self) -> Collider {
+        Collider {
+            size: Vec2::new(self.length() + WALL_THICKNESS, WALL_THICKNESS),
+            normal: self.normal(),
+        }
+    }
+
+    /// Transform placing a horizontal mesh along this edge.
+    pub fn transform(&self) -> Transform {
+        let dir = self.end - self.start;
+        Transform::from_translation(self.midpoint().extend(0.))
+            .with_rotation(Quat::from_rotation_z(dir.y.atan2(dir.x)))
+    }
+}
+
+/// A wall's solid box, set when it's spawned: `size` along the wall and
+/// through it, centered on its translation, facing into the arena along
+/// `normal`.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Collider {
+    pub size: Vec2,
+    pub normal: Vec2,
+}
+
+impl Collider {
+    /// Distance from the face of a wall centered on `center`, positive on
+    /// the arena side.
+    pub fn gap(&self, center: Vec3, point: Vec3) -> f32 {
+        (point - center).truncate().dot(self.normal) - self.size.y / 2.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opposite_goal_faces_the_first() {
+        let square = Arena::default().with_opposite_goal();
+        assert_eq!(square.goals, [0, 2]);
+        let (bottom, top) = (square.edge(0), square.edge(2));
+        assert!(bottom.normal().abs_diff_eq(-top.normal(), 1e-5));
+        assert_eq!(square.walls().count(), 2);
+
+        let triangle = Arena::from_name("triangle").unwrap().with_opposite_goal();
+        assert_eq!(triangle.goals, [0]);
+    }
+
+    #[test]
+    fn clipping_keeps_the_inside_of_a_segment() {
+        let arena = Arena::square(600.);
+        let (start, end) = arena
+            .clip(Vec2::new(-1000., 0.), Vec2::new(1000., 0.))
+            .unwrap();
+        assert!(start.abs_diff_eq(Vec2::new(-300., 0.), 0.01));
+        assert!(end.abs_diff_eq(Vec2::new(300., 0.), 0.01));
+        assert_eq!(
+            arena.clip(Vec2::new(-100., 50.), Vec2::new(100., 50.)),
+            Some((Vec2::new(-100., 50.), Vec2::new(100., 50.)))
+        );
+        assert_eq!(
+            arena.clip(Vec2::new(-500., 400.), Vec2::new(500., 400.)),
+            None
+        );
+    }
+
+    #[test]
+    fn classic_paddles_slide_up_and_down_their_goals() {
+        let classic = Arena::classic(600.);
+        assert_eq!(classic.goal_axis(0), Vec2::Y);
+        assert!(classic
+            .edge(classic.goals[0])
+            .normal()
+            .abs_diff_eq(Vec2::X, 1e-5));
+
+        let paddle = classic.paddle_spawn();
+        let moved = classic.slide(0, paddle, 1000., 50.);
+        assert_eq!(moved.x, paddle.x);
+        assert!((moved.y.abs() - 250.).abs() < 1e-3);
+
+        // the paddle's top face points out of the goal
+        let face = classic.paddle_rotation(0) * Vec3::Y;
+        assert!(face.truncate().abs_diff_eq(Vec2::X, 1e-5));
+    }
+
+    #[test]
+    fn files_describe_the_built_in_arenas() {
+        for (name, built_in) in [
+            ("square", Arena::square(600.)),
+            ("classic", Arena::classic(600.)),
+            ("hex", Arena::regular(6, 320.)),
+            ("triangle", Arena::regular(3, 340.)),
+        ] {
+            let file = ArenaFile::load(name).unwrap();
+            assert_eq!(file.layout(name), format!("scenes/{name}.scn.ron"));
+            let arena = file.arena().unwrap();
+            assert_eq!(arena.goals, built_in.goals, "{name}");
+            assert_eq!(arena.vertices.len(), built_in.vertices.len(), "{name}");
+            for (corner, expected) in arena.vertices.iter().zip(&built_in.vertices) {
+                assert!(corner.abs_diff_eq(*expected, 0.01), "{name}");
+            }
+            assert!(arena
+                .paddle_spawn()
+                .abs_diff_eq(built_in.paddle_spawn(), 0.01));
+            assert!(arena.ball_spawn().abs_diff_eq(built_in.ball_spawn(), 0.01));
+        }
+        assert!(Arena::from_name("nowhere").is_none());
+    }
+
+    #[test]
+    fn unplayable_shapes_are_turned_down() {
+        let file = |vertices: &[(f32, f32)], goals: Vec<usize>| ArenaFile {
+            vertices: vertices.iter().map(|&(x, y)| Vec2::new(x, y)).collect(),
+            goals,
+            paddle_inset: None,
+            ball_inset: Some(80.),
+            layout: Some("square".into()),
+        };
+        let square = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)];
+        let arena = file(&square, vec![0]).arena().unwrap();
+        assert_eq!(arena.paddle_inset, PADDLE_INSET);
+        assert_eq!(arena.ball_inset, 80.);
+        assert_eq!(
+            file(&square, vec![0]).layout("custom"),
+            "scenes/square.scn.ron"
+        );
+
+        let mut clockwise = square;
+        clockwise.reverse();
+        assert!(file(&clockwise, vec![0]).arena().is_err());
+        let dented = [(-1., -1.), (1., -1.), (0., -0.5), (1., 1.), (-1., 1.)];
+        assert!(file(&dented, vec![0]).arena().is_err());
+        let pentagram: Vec<(f32, f32)> = (0..5)
+            .map(|i| Vec2::from_angle(FRAC_PI_2 + i as f32 * 0.8 * PI))
+            .map(|corner| (corner.x, corner.y))
+            .collect();
+        assert!(file(&pentagram, vec![0]).arena().is_err());
+        assert!(file(&square, vec![]).arena().is_err());
+        assert!(file(&square, vec![4]).arena().is_err());
+        assert!(file(&square[..2], vec![0]).arena().is_err());
+    }
+}
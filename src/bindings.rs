@@ -0,0 +1,397 @@
+//! Key bindings, one set per player, read from a RON file at startup: each
+//! player's keys for moving along a flat goal and an upright one, serving
+//! ahead of the countdown and pausing. A missing file is written out with
+//! the defaults, so there's one to edit; one that doesn't parse is left as
+//! it is and the defaults used. Keys go by their Bevy names (`"Left"`,
+//! `"A"`, `"Return"`), and players past the last set share it. Keys rebound
+//! on the controls screen are written back to the same file, as is each
+//! player's [`Handedness`]. The `dev` build's frame-step keys are kept there
+//! too, shared by everyone.
+
+use std::{fs, io::ErrorKind, path::PathBuf};
+
+use bevy::{
+    prelude::*,
+    reflect::{DynamicEnum, DynamicVariant, Enum, FromReflect, TypeInfo, Typed},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    arena::Arena,
+    engine::{ScheduleSetup, Stage},
+    handedness::Handedness,
+    paddle::Side,
+};
+
+pub struct BindingsPlugin {
+    pub path: Option<PathBuf>,
+}
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .insert_resource(BindingsFile(self.path.clone()));
+        if let Some(path) = self.path.clone() {
+            app.add_systems_at(Stage::Startup, move |mut commands: Commands| {
+                commands.insert_resource(KeyBindings::load(&path));
+            });
+        }
+    }
+}
+
+/// Where the bindings are kept, if anywhere.
+#[derive(Resource)]
+pub struct BindingsFile(pub Option<PathBuf>);
+
+/// Something a player has a key for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    MoveLeft,
+    MoveRight,
+    MoveDown,
+    MoveUp,
+    Serve,
+    Pause,
+}
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 6] = [
+        KeyAction::MoveLeft,
+        KeyAction::MoveRight,
+        KeyAction::MoveDown,
+        KeyAction::MoveUp,
+        KeyAction::Serve,
+        KeyAction::Pause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            KeyAction::MoveLeft => "MOVE LEFT",
+            KeyAction::MoveRight => "MOVE RIGHT",
+            KeyAction::MoveDown => "MOVE DOWN",
+            KeyAction::MoveUp => "MOVE UP",
+            KeyAction::Serve => "SERVE",
+            KeyAction::Pause => "PAUSE",
+        }
+    }
+}
+
+/// One player's keys.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PlayerKeys {
+    #[serde(with = "key_name")]
+    pub move_left: KeyCode,
+    #[serde(with = "key_name")]
+    pub move_right: KeyCode,
+    /// Steering on an upright goal.
+    #[serde(with = "key_name")]
+    pub move_down: KeyCode,
+    #[serde(with = "key_name")]
+    pub move_up: KeyCode,
+    /// Serves at once instead of waiting out the countdown.
+    #[serde(with = "key_name")]
+    pub serve: KeyCode,
+    #[serde(with = "key_name")]
+    pub pause: KeyCode,
+    #[serde(default)]
+    pub handedness: Handedness,
+}
+
+impl PlayerKeys {
+    /// The key pressed for `action`, its twin when the controls are mirrored.
+    pub fn key(&self, action: KeyAction) -> KeyCode {
+        self.handedness.key(*self.slot(action))
+    }
+
+    fn slot(&self, action: KeyAction) -> &KeyCode {
+        match action {
+            KeyAction::MoveLeft => &self.move_left,
+            KeyAction::MoveRight => &self.move_right,
+            KeyAction::MoveDown => &self.move_down,
+            KeyAction::MoveUp => &self.move_up,
+            KeyAction::Serve => &self.serve,
+            KeyAction::Pause => &self.pause,
+        }
+    }
+
+    fn slot_mut(&mut self, action: KeyAction) -> &mut KeyCode {
+        match action {
+            KeyAction::MoveLeft => &mut self.move_left,
+            KeyAction::MoveRight => &mut self.move_right,
+            KeyAction::MoveDown => &mut self.move_down,
+            KeyAction::MoveUp => &mut self.move_up,
+            KeyAction::Serve => &mut self.serve,
+            KeyAction::Pause => &mut self.pause,
+        }
+    }
+
+    /// Steering keys, left then right, or down then up on an `upright` goal.
+    pub fn steering(&self, upright: bool) -> [KeyCode; 2] {
+        if upright {
+            [self.key(KeyAction::MoveDown), self.key(KeyAction::MoveUp)]
+        } else {
+            [
+                self.key(KeyAction::MoveLeft),
+                self.key(KeyAction::MoveRight),
+            ]
+        }
+    }
+}
+
+/// Freezing the game and stepping it a frame at a time.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FrameStepKeys {
+    #[serde(with = "key_name")]
+    pub freeze: KeyCode,
+    #[serde(with = "key_name")]
+    pub step: KeyCode,
+}
+
+impl Default for FrameStepKeys {
+    // F8 and F9 are the spectator view and the inspector
+    fn default() -> Self {
+        Self {
+            freeze: KeyCode::F11,
+            step: KeyCode::F12,
+        }
+    }
+}
+
+/// Every player's keys, by side.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct KeyBindings {
+    pub players: Vec<PlayerKeys>,
+    /// Missing from files written before there were any.
+    #[serde(default)]
+    pub frame_step: FrameStepKeys,
+}
+
+impl Default for KeyBindings {
+    // Up and Down are the first side's flick and block, so upright goals
+    // steer with W/S and I/K
+    fn default() -> Self {
+        Self {
+            players: vec![
+                PlayerKeys {
+                    move_left: KeyCode::Left,
+                    move_right: KeyCode::Right,
+                    move_down: KeyCode::S,
+                    move_up: KeyCode::W,
+                    serve: KeyCode::Return,
+                    pause: KeyCode::Escape,
+                    handedness: Handedness::default(),
+                },
+                PlayerKeys {
+                    move_left: KeyCode::A,
+                    move_right: KeyCode::D,
+                    move_down: KeyCode::K,
+                    move_up: KeyCode::I,
+                    serve: KeyCode::E,
+                    pause: KeyCode::P,
+                    handedness: Handedness::default(),
+                },
+            ],
+            frame_step: FrameStepKeys::default(),
+        }
+    }
+}
+
+impl KeyBindings {
+    /// The keys of the player on `side`.
+    pub fn player(&self, side: usize) -> PlayerKeys {
+        match self.players.get(side).or(self.players.last()) {
+            Some(&keys) => keys,
+            None => KeyBindings::default().players[0],
+        }
+    }
+
+    /// What `player` presses for one of the keys the game reads directly,
+    /// such as charge or smash: `key`, or its twin when their controls are
+    /// mirrored.
+    pub fn reserved(&self, player: usize, key: KeyCode) -> KeyCode {
+        self.player(player).handedness.key(key)
+    }
+
+    /// `side`'s steering keys for its goal in `arena`.
+    pub fn steering(&self, side: Side, arena: &Arena) -> [KeyCode; 2] {
+        self.player(side.0)
+            .steering(arena.goal_axis(side.0) == Vec2::Y)
+    }
+
+    /// The player and action `key` is bound to already, if any.
+    pub fn holder(&self, key: KeyCode) -> Option<(usize, KeyAction)> {
+        self.players.iter().enumerate().find_map(|(player, keys)| {
+            KeyAction::ALL
+                .into_iter()
+                .find(|&action| keys.key(action) == key)
+                .map(|action| (player, action))
+        })
+    }
+
+    /// Binds `key` to `player`'s `action`, unless another action has it;
+    /// that one comes back instead.
+    pub fn rebind(
+        &mut self,
+        player: usize,
+        action: KeyAction,
+        key: KeyCode,
+    ) -> Result<(), (usize, KeyAction)> {
+        match self.holder(key) {
+            Some(holder) if holder != (player, action) => Err(holder),
+            _ => {
+                if let Some(keys) = self.players.get_mut(player) {
+                    // kept unmirrored, so it's `key` again once mirrored
+                    *keys.slot_mut(action) = keys.handedness.key(key);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Flips whether `player`'s controls are mirrored, unless one of the
+    /// twins they'd press is another player's key; that one comes back
+    /// instead.
+    pub fn mirror(&mut self, player: usize) -> Result<(), (usize, KeyAction)> {
+        let Some(&keys) = self.players.get(player) else {
+            return Ok(());
+        };
+        let mut mirrored = keys;
+        mirrored.handedness.mirror_controls = !keys.handedness.mirror_controls;
+        if let Some(holder) = KeyAction::ALL.into_iter().find_map(|action| {
+            self.holder(mirrored.key(action))
+                .filter(|&(other, _)| other != player)
+        }) {
+            return Err(holder);
+        }
+        self.players[player] = mirrored;
+        Ok(())
+    }
+
+    pub fn save(&self, path: &PathBuf) {
+        let text = ron::ser::to_string_pretty(self, default()).expect("bindings serialize");
+        match fs::write(path, text) {
+            Ok(()) => info!("wrote the keys to {}", path.display()),
+            Err(err) => warn!("couldn't write {}: {err}", path.display()),
+        }
+    }
+
+    fn load(path: &PathBuf) -> Self {
+        match fs::read_to_string(path) {
+            Ok(text) => ron::from_str(&text).unwrap_or_else(|err| {
+                warn!(
+                    "couldn't read {}, using the default keys: {err}",
+                    path.display()
+                );
+                KeyBindings::default()
+            }),
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                let defaults = KeyBindings::default();
+                defaults.save(path);
+                defaults
+            }
+            Err(err) => {
+                warn!(
+                    "couldn't read {}, using the default keys: {err}",
+                    path.display()
+                );
+                KeyBindings::default()
+            }
+        }
+    }
+}
+
+// keys by variant name, since Bevy only derives serde for them behind a feature
+mod key_name {
+    use super::*;
+
+    pub fn serialize<S: serde::Serializer>(
+        key: &KeyCode,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(key.variant_name())
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<KeyCode, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        let known =
+            matches!(KeyCode::type_info(), TypeInfo::Enum(info) if info.contains_variant(&name));
+        let key = known.then(|| {
+            KeyCode::from_reflect(&DynamicEnum::new(
+                "KeyCode",
+                name.as_str(),
+                DynamicVariant::Unit,
+            ))
+        });
+        key.flatten()
+            .ok_or_else(|| serde::de::Error::custom(format!("no key called {name:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_keys_by_name() {
+        let defaults = KeyBindings::default();
+        let text = ron::to_string(&defaults).unwrap();
+        assert!(text.contains("\"Return\""));
+        assert_eq!(ron::from_str::<KeyBindings>(&text).unwrap(), defaults);
+
+        let typo = text.replace("\"Return\"", "\"Retrun\"");
+        assert!(ron::from_str::<KeyBindings>(&typo).is_err());
+
+        // files from before the frame-step keys read with the defaults
+        let older = format!("(players: {})", ron::to_string(&defaults.players).unwrap());
+        assert_eq!(ron::from_str::<KeyBindings>(&older).unwrap(), defaults);
+
+        // a third player shares the second's keys
+        assert_eq!(defaults.player(2), defaults.players[1]);
+
+        let mut bindings = defaults.clone();
+        assert_eq!(
+            bindings.rebind(0, KeyAction::Serve, KeyCode::A),
+            Err((1, KeyAction::MoveLeft))
+        );
+        assert_eq!(
+            bindings.rebind(0, KeyAction::Serve, KeyCode::Return),
+            Ok(())
+        );
+        assert_eq!(bindings.rebind(0, KeyAction::Serve, KeyCode::J), Ok(()));
+        assert_eq!(bindings.player(0).serve, KeyCode::J);
+    }
+
+    #[test]
+    fn mirrored_players_press_the_twin_of_each_key() {
+        let mut bindings = KeyBindings::default();
+        bindings.players[0].handedness.mirror_controls = true;
+        assert_eq!(bindings.player(0).key(KeyAction::MoveLeft), KeyCode::A);
+        assert_eq!(bindings.reserved(0, KeyCode::RShift), KeyCode::LShift);
+        assert_eq!(bindings.reserved(1, KeyCode::RShift), KeyCode::RShift);
+
+        // what's pressed is what plays, mirrored or not
+        assert_eq!(bindings.rebind(0, KeyAction::MoveUp, KeyCode::Left), Ok(()));
+        assert_eq!(bindings.player(0).key(KeyAction::MoveUp), KeyCode::Left);
+        bindings.players[0].handedness.mirror_controls = false;
+        assert_eq!(bindings.player(0).key(KeyAction::MoveUp), KeyCode::A);
+    }
+
+    #[test]
+    fn mirroring_onto_another_players_keys_is_refused() {
+        let mut bindings = KeyBindings::default();
+        // the first player's arrows would land on the second player's A and D
+        assert_eq!(bindings.mirror(0), Err((1, KeyAction::MoveLeft)));
+        assert!(!bindings.player(0).handedness.mirror_controls);
+
+        bindings.rebind(1, KeyAction::MoveLeft, KeyCode::J).unwrap();
+        bindings
+            .rebind(1, KeyAction::MoveRight, KeyCode::L)
+            .unwrap();
+        assert_eq!(bindings.mirror(0), Ok(()));
+        assert_eq!(bindings.player(0).key(KeyAction::MoveLeft), KeyCode::A);
+        assert_eq!(bindings.mirror(0), Ok(()));
+        assert_eq!(bindings.player(0).key(KeyAction::MoveLeft), KeyCode::Left);
+    }
+}
@@ -0,0 +1,71 @@
+//! Block stance: holding the block key braces the paddle. It moves at half
+//! speed, but soaks up the ball's pace and drops it back slow and steep,
+//! which takes the sting out of smashes.
+
+use bevy::prelude::*;
+
+use crate::{
+    bindings::KeyBindings, engine::ScheduleSetup, paddle::Side, physics::clamp_angle, AppState,
+};
+
+pub const BLOCK_KEY: KeyCode = KeyCode::Down;
+
+// share of the ball's speed kept after a block
+const BLOCK_ABSORB: f32 = 0.5;
+// widest angle off the paddle normal a blocked return can take
+const BLOCK_MAX_ANGLE: f32 = 10. * std::f32::consts::PI / 180.;
+// slowest a blocked return can go, in `Speed::dir` units
+const BLOCK_MIN_SPEED: f32 = 3.;
+const BRACED_THICKNESS: f32 = 1.6;
+
+pub struct BlockPlugin;
+
+impl Plugin for BlockPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, block_input);
+    }
+}
+
+#[derive(Component, Default, Clone, Copy, PartialEq)]
+pub enum Stance {
+    #[default]
+    Normal,
+    Blocking,
+}
+
+impl Stance {
+    pub fn move_factor(self) -> f32 {
+        match self {
+            Stance::Normal => 1.,
+            Stance::Blocking => 0.5,
+        }
+    }
+}
+
+/// A slow return angled mostly straight off the paddle.
+pub fn blocked_dir(dir: Vec3, normal: Vec3) -> Vec3 {
+    let steep = clamp_angle(dir, normal, BLOCK_MAX_ANGLE);
+    steep.normalize_or_zero() * (dir.length() * BLOCK_ABSORB).max(BLOCK_MIN_SPEED)
+}
+
+fn block_input(
+    mut query: Query<(&mut Stance, &mut Transform, &Side)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    for (mut current, mut transform, side) in &mut query {
+        let stance = if keyboard_input.pressed(bindings.reserved(side.0, BLOCK_KEY)) {
+            Stance::Blocking
+        } else {
+            Stance::Normal
+        };
+        if *current != stance && *side == Side(0) {
+            *current = stance;
+            // a braced paddle looks thicker
+            transform.scale.y = match stance {
+                Stance::Normal => 1.,
+                Stance::Blocking => BRACED_THICKNESS,
+            };
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! The computer opponent. A [`Bot`] paddle aims for where the nearest
+//! incoming ball will cross its line, folding the path off the side walls.
+//! It only looks again every so often, and each look lands a little off;
+//! [`Difficulty`] sets how long it waits, how far off it lands and how fast
+//! it can move.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    arena::Arena,
+    block::Stance,
+    engine::ScheduleSetup,
+    paddle::{PaddleStats, Side, PADDLE_SPEED},
+    AppState, Ball, Speed,
+};
+
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, ai_paddle);
+    }
+}
+
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "easy" => Some(Self::Easy),
+            "normal" => Some(Self::Normal),
+            "hard" => Some(Self::Hard),
+            _ => None,
+        }
+    }
+
+    /// Seconds between looks at the ball.
+    fn reaction(self) -> f32 {
+        match self {
+            Self::Easy => 0.35,
+            Self::Normal => 0.2,
+            Self::Hard => 0.08,
+        }
+    }
+
+    /// Top speed relative to the paddle's own.
+    fn speed(self) -> f32 {
+        match self {
+            Self::Easy => 0.6,
+            Self::Normal => 0.8,
+            Self::Hard => 1.,
+        }
+    }
+
+    /// Furthest a look can land from the true crossing, in pixels.
+    fn error(self) -> f32 {
+        match self {
+            Self::Easy => 60.,
+            Self::Normal => 25.,
+            Self::Hard => 5.,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Bot {
+    difficulty: Difficulty,
+    /// Seconds until the next look.
+    cooldown: f32,
+    /// Where along the goal line it's headed, from the line's middle.
+    target: f32,
+    // its own rng, so bots don't shift the serves
+    rng: StdRng,
+}
+
+impl Bot {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self {
+            difficulty,
+            cooldown: 0.,
+            target: 0.,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// Where a ball at `position` heading along `dir` crosses the horizontal line
+/// at `line_y`, bounced back inside `(min_x, max_x)`; `None` if it's heading away.
+pub fn predict_crossing(
+    position: Vec2,
+    dir: Vec2,
+    line_y: f32,
+    (min_x, max_x): (f32, f32),
+) -> Option<f32> {
+    let rise = line_y - position.y;
+    if dir.y == 0. || rise.signum() != dir.y.signum() {
+        return None;
+    }
+    let x = position.x + dir.x * rise / dir.y;
+
+    // unfold the straight path into the bounces between the side walls
+    let width = max_x - min_x;
+    if width <= 0. {
+        return Some(min_x);
+    }
+    let folded = (x - min_x).rem_euclid(2. * width);
+    let bounced = if folded > width {
+        2. * width - folded
+    } else {
+        folded
+    };
+    Some(min_x + bounced)
+}
+
+// works in the goal's frame, along the line and out into the field, so the
+// same prediction covers flat and upright goals
+fn ai_paddle(
+    mut query: Query<(&mut Transform, &mut Bot, &PaddleStats, &Stance, &Side), Without<Ball>>,
+    query_ball: Query<(&Transform, &Speed), With<Ball>>,
+    arena: Res<Arena>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    for (mut transform, mut bot, stats, stance, side) in &mut query {
+        let line = arena.edge(arena.goals[side.0]);
+        let axis = arena.goal_axis(side.0);
+        let field = line.normal();
+        let to_frame = |dir: Vec2| Vec2::new(dir.dot(axis), dir.dot(field));
+        let half_span = line.length() / 2.;
+
+        let paddle = to_frame(transform.translation.truncate() - line.midpoint());
+        bot.cooldown -= delta;
+        if bot.cooldown <= 0. {
+            bot.cooldown = bot.difficulty.reaction();
+
+            // the ball that arrives first, or back to the middle with none coming
+            let crossing = query_ball
+                .iter()
+                .filter_map(|(ball, speed)| {
+                    let position = to_frame(ball.translation.truncate() - line.midpoint());
+                    let dir = to_frame(speed.dir.truncate());
+                    let x = predict_crossing(position, dir, paddle.y, (-half_span, half_span))?;
+                    Some(((paddle.y - position.y) / dir.y, x))
+                })
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, x)| x);
+            let error = bot.difficulty.error();
+            let miss = bot.rng.gen_range(-error..=error);
+            bot.target = crossing.unwrap_or(0.) + miss;
+        }
+
+        let reach =
+            PADDLE_SPEED * stats.speed * bot.difficulty.speed() * stance.move_factor() * delta;
+        let step = (bot.target - paddle.x).clamp(-reach, reach);
+        transform.translation = arena.slide(side.0, transform.translation, step, stats.size.x / 2.);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn predicts_a_straight_crossing() {
+        let x = predict_crossing(Vec2::ZERO, Vec2::new(1., 2.), 100., (-200., 200.));
+        assert_eq!(x, Some(50.));
+    }
+
+    #[test]
+    fn folds_the_path_off_the_side_walls() {
+        // 900 across: off the right wall, then the left
+        let x = predict_crossing(Vec2::ZERO, Vec2::new(3., 1.), 300., (-200., 200.));
+        assert_eq!(x, Some(100.));
+        let x = predict_crossing(Vec2::ZERO, Vec2::new(-3., 1.), 300., (-200., 200.));
+        assert_eq!(x, Some(-100.));
+    }
+
+    #[test]
+    fn ignores_balls_heading_away() {
+        assert_eq!(
+            predict_crossing(Vec2::ZERO, Vec2::new(0., -1.), 100., (-200., 200.)),
+            None
+        );
+    }
+}
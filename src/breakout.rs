@@ -0,0 +1,177 @@
+//! Breakout bricks, for the mutator that lays them: rows of bricks across
+//! the far half of the field, knocked out by a ball on contact as it bounces
+//! off. Each one is a point for whoever last returned that ball, and once the
+//! last one goes the level's clear and a fresh set is laid.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::{Arena, WALL_THICKNESS},
+    callout::spawn_callout,
+    engine::ScheduleSetup,
+    ownership::Owner,
+    prefab::{spawn_prefab, Brick, PrefabLibrary, PrefabOverrides, Prefabs},
+    AppState, GameState, Tunables,
+};
+
+const BRICK_POINTS: u32 = 1;
+// how far past halfway up the field the first row is
+const FIRST_ROW: f32 = 60.;
+const BRICK_GAP: f32 = 6.;
+
+pub struct BreakoutPlugin;
+
+impl Plugin for BreakoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BrickBroken>()
+            .add_systems_during(AppState::Playing, (lay_bricks, score_bricks));
+    }
+}
+
+/// Sent by the bounce when `ball` knocks out the brick at `brick`.
+pub struct BrickBroken {
+    pub ball: Entity,
+    pub brick: Vec3,
+}
+
+/// Where each of `rows` rows of `size` bricks goes, the nearest row first,
+/// each row as many bricks as fit between the walls.
+fn brick_spots(arena: &Arena, rows: u32, size: Vec2) -> Vec<Vec3> {
+    let goal = arena.edge(arena.goals[0]);
+    let (normal, along) = (goal.normal(), (goal.end - goal.start).normalize());
+    let reach = arena.vertices.iter().map(|v| v.length()).fold(0., f32::max) * 2.;
+    // the stretch of a line `depth` up the field inside the arena, as
+    // distances along the goal
+    let span = |depth: f32| {
+        let point = goal.midpoint() + normal * depth;
+        arena
+            .clip(point - along * reach, point + along * reach)
+            .map(|(start, end)| {
+                let (start, end) = (start.dot(along), end.dot(along));
+                (start.min(end), start.max(end))
+            })
+    };
+
+    let mut spots = Vec::new();
+    for row in 0..rows {
+        let depth = arena.depth() / 2. + FIRST_ROW + row as f32 * (size.y + BRICK_GAP);
+        // both the row's sides have to fit, for walls that close in
+        let (Some(near), Some(far)) = (span(depth - size.y / 2.), span(depth + size.y / 2.)) else {
+            continue;
+        };
+        let margin = WALL_THICKNESS / 2. + BRICK_GAP;
+        let (low, high) = (near.0.max(far.0) + margin, near.1.min(far.1) - margin);
+        let count = ((high - low + BRICK_GAP) / (size.x + BRICK_GAP)).max(0.) as usize;
+        let line = goal.midpoint() + normal * depth;
+        let line = line - along * line.dot(along);
+        for i in 0..count {
+            let offset =
+                (low + high) / 2. + (i as f32 - (count - 1) as f32 / 2.) * (size.x + BRICK_GAP);
+            spots.push((line + along * offset).extend(0.));
+        }
+    }
+    spots
+}
+
+// a level's cleared once its last brick has gone from the field
+fn lay_bricks(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut laid: Local<bool>,
+    query: Query<(), With<Brick>>,
+    (arena, prefabs, libraries, tunables, asset_server): (
+        Res<Arena>,
+        Res<Prefabs>,
+        Res<Assets<PrefabLibrary>>,
+        Res<Tunables>,
+        Res<AssetServer>,
+    ),
+) {
+    if tunables.brick_rows == 0 || !query.is_empty() {
+        return;
+    }
+    let Some(library) = libraries.get(&prefabs.0) else {
+        return;
+    };
+    let Some(prefab) = library.prefabs.get("brick") else {
+        return;
+    };
+
+    if *laid {
+        let center = arena
+            .center_line()
+            .map_or(Vec2::ZERO, |(start, end)| start.lerp(end, 0.5));
+        spawn_callout(
+            &mut commands,
+            &asset_server,
+            "LEVEL CLEAR!",
+            center.extend(0.),
+        );
+    }
+    *laid = true;
+    let rotation = arena.paddle_rotation(0);
+    for translation in brick_spots(&arena, tunables.brick_rows, prefab.shape.size()) {
+        let Some(brick) = spawn_prefab(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            library,
+            "brick",
+            PrefabOverrides {
+                translation,
+                ..default()
+            },
+        ) else {
+            continue;
+        };
+        commands
+            .entity(brick)
+            .insert(Transform::from_translation(translation).with_rotation(rotation));
+    }
+}
+
+// the score counts goals let in, so a side's brick is a goal against the other
+fn score_bricks(
+    mut broken: EventReader<BrickBroken>,
+    mut game_state: ResMut<GameState>,
+    query_ball: Query<&Owner>,
+) {
+    for event in broken.iter() {
+        // a ball nobody has returned yet scores for nobody
+        let Ok(owner) = query_ball.get(event.ball) else {
+            continue;
+        };
+        if owner.0 == 0 {
+            game_state.score.1 += BRICK_POINTS;
+        } else {
+            game_state.score.0 += BRICK_POINTS;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bricks_fill_rows_in_the_far_half() {
+        let arena = Arena::square(600.);
+        let size = Vec2::new(60., 20.);
+        let spots = brick_spots(&arena, 3, size);
+        assert_eq!(spots.len(), 3 * 8);
+        for spot in &spots {
+            assert!(spot.y > 0. && spot.x.abs() + size.x / 2. < 300. - WALL_THICKNESS / 2.);
+        }
+        // side by side along each row, without overlapping
+        assert!(spots[1].x - spots[0].x >= size.x);
+        assert_eq!(spots[0].y, spots[7].y);
+        assert!(spots[8].y - spots[0].y >= size.y);
+
+        // a triangle's rows narrow toward its tip
+        let triangle = Arena::regular(3, 300.);
+        let spots = brick_spots(&triangle, 3, size);
+        let row = |y: f32| spots.iter().filter(|spot| spot.y == y).count();
+        assert!(row(spots[0].y) > row(spots[spots.len() - 1].y));
+    }
+}
@@ -0,0 +1,78 @@
+//! Short-lived text popups announcing notable moments ("SPLIT!" and friends).
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::time_scale::{scaled, TimeScale};
+
+const CALLOUT_DURATION: f32 = 1.;
+const CALLOUT_RISE: f32 = 40.;
+
+pub struct CalloutPlugin;
+
+impl Plugin for CalloutPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(animate_callouts);
+    }
+}
+
+#[derive(Component)]
+pub struct Callout {
+    timer: Timer,
+}
+
+pub fn spawn_callout(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    text: &str,
+    position: Vec3,
+) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 32.,
+                    color: Color::ORANGE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            // keep the text above the playfield meshes
+            transform: Transform::from_translation(position.truncate().extend(10.)),
+            ..default()
+        },
+        Callout {
+            timer: Timer::from_seconds(CALLOUT_DURATION, TimerMode::Once),
+        },
+    ));
+}
+
+// drifts callouts upward while fading them out, then despawns them
+fn animate_callouts(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Callout,
+        &mut Transform,
+        &mut Text,
+        Option<&TimeScale>,
+    )>,
+    timer: Res<Time>,
+) {
+    for (entity, mut callout, mut transform, mut text, time_scale) in &mut query {
+        let delta = scaled(timer.delta_seconds(), time_scale);
+        callout.timer.tick(Duration::from_secs_f32(delta));
+
+        if callout.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += CALLOUT_RISE * delta / CALLOUT_DURATION;
+        for section in &mut text.sections {
+            section.style.color.set_a(callout.timer.percent_left());
+        }
+    }
+}
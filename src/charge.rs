@@ -0,0 +1,165 @@
+//! Charged shots: hold the charge key while the ball approaches and release it
+//! right before contact for a faster, straighter return. Holding on too long
+//! overcharges the paddle, which wastes the charge and locks it out for a while.
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    bindings::KeyBindings,
+    engine,
+    engine::ScheduleSetup,
+    paddle::Side,
+    time_scale::{scaled, TimeScale},
+    AppState,
+};
+
+pub const CHARGE_KEY: KeyCode = KeyCode::Space;
+
+// seconds of holding to reach full charge
+const CHARGE_TIME: f32 = 1.;
+// how long a full charge can be held before it overcharges
+const OVERCHARGE_TIME: f32 = 0.6;
+const OVERCHARGE_LOCKOUT: f32 = 1.5;
+// how long after releasing the ball has to make contact
+const RELEASE_WINDOW: f32 = 0.2;
+// extra speed and sideways damping at full charge
+const MAX_BOOST: f32 = 0.6;
+const MAX_STRAIGHTEN: f32 = 0.5;
+
+const METER_SIZE: Vec2 = Vec2::new(100., 4.);
+
+pub struct ChargePlugin;
+
+impl Plugin for ChargePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, (charge_input, update_meters));
+    }
+}
+
+#[derive(Component, Default, Clone)]
+pub struct Charge {
+    level: f32,
+    held_full: f32,
+    primed: Option<Primed>,
+    lockout: f32,
+}
+
+/// A released charge waiting for the ball to arrive.
+#[derive(Clone)]
+struct Primed {
+    power: f32,
+    remaining: f32,
+}
+
+impl Charge {
+    /// Spends a released charge, returning its power in `0..=1`.
+    pub fn take_shot(&mut self) -> Option<f32> {
+        self.primed.take().map(|primed| primed.power)
+    }
+}
+
+/// Speeds up a return and pulls it toward the paddle's normal.
+pub fn charged_dir(dir: Vec3, normal: Vec3, power: f32) -> Vec3 {
+    let along = normal * dir.dot(normal);
+    let across = (dir - along) * (1. - MAX_STRAIGHTEN * power);
+    let straightened = (along + across).normalize_or_zero() * dir.length();
+    straightened * (1. + MAX_BOOST * power)
+}
+
+#[derive(Component)]
+struct ChargeMeter {
+    material: Handle<ColorMaterial>,
+}
+
+/// Adds the charge meter bar underneath a paddle.
+pub fn spawn_meter(
+    parent: &mut ChildBuilder,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+) {
+    let material = materials.add(ColorMaterial::from(Color::YELLOW));
+    parent.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes.add(engine::rectangle(METER_SIZE)).into(),
+            material: material.clone(),
+            transform: Transform::from_xyz(0., -10., 1.).with_scale(Vec3::new(0., 1., 1.)),
+            ..default()
+        },
+        ChargeMeter { material },
+    ));
+}
+
+fn charge_input(
+    mut query: Query<(&mut Charge, &Side, Option<&TimeScale>)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    timer: Res<Time>,
+    audio: Res<Audio>,
+    asset_server: Res<AssetServer>,
+) {
+    for (mut charge, side, time_scale) in &mut query {
+        let charge_key = bindings.reserved(side.0, CHARGE_KEY);
+        let delta = scaled(timer.delta_seconds(), time_scale);
+        if let Some(primed) = &mut charge.primed {
+            primed.remaining -= delta;
+            if primed.remaining <= 0. {
+                charge.primed = None;
+            }
+        }
+
+        if charge.lockout > 0. {
+            charge.lockout -= delta;
+            continue;
+        }
+
+        if keyboard_input.pressed(charge_key) {
+            charge.level = (charge.level + delta / CHARGE_TIME).min(1.);
+            if charge.level >= 1. {
+                charge.held_full += delta;
+            }
+
+            if charge.held_full > OVERCHARGE_TIME {
+                *charge = Charge {
+                    lockout: OVERCHARGE_LOCKOUT,
+                    ..default()
+                };
+                audio.play(asset_server.load("sounds/overcharge.wav"));
+            }
+        } else if charge.level > 0. {
+            charge.primed = Some(Primed {
+                power: charge.level,
+                remaining: RELEASE_WINDOW,
+            });
+            charge.level = 0.;
+            charge.held_full = 0.;
+        }
+    }
+}
+
+fn update_meters(
+    query_paddles: Query<(&Charge, &Children)>,
+    mut query_meters: Query<(&ChargeMeter, &mut Transform)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (charge, children) in &query_paddles {
+        for &child in children {
+            let Ok((meter, mut transform)) = query_meters.get_mut(child) else {
+                continue;
+            };
+
+            // a locked out meter stays full and red until it recovers
+            let (fill, color) = if charge.lockout > 0. {
+                (charge.lockout / OVERCHARGE_LOCKOUT, Color::RED)
+            } else if charge.level >= 1. {
+                (1., Color::ORANGE)
+            } else {
+                (charge.level, Color::YELLOW)
+            };
+
+            transform.scale.x = fill;
+            if let Some(material) = materials.get_mut(&meter.material) {
+                material.color = color;
+            }
+        }
+    }
+}
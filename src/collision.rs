@@ -0,0 +1,362 @@
+//! What a ball runs into. The physics only moves and turns balls: the
+//! bounce sends [`BallHitWall`] and [`BallHitPaddle`] as it turns one, and
+//! the goal check [`BallOutOfBounds`] once one is past a goal line.
+//! Everything that follows from a hit (the speed ramp, charged shots,
+//! smashes and splits, the score, the next serve, the log entry) is a
+//! system here reading those, so a sound or a particle burst hooks on
+//! without touching the physics.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena,
+    block::{blocked_dir, Stance},
+    callout::spawn_callout,
+    charge::{charged_dir, Charge},
+    event_log::GameplayEvent,
+    flash::spawn_flash,
+    input::{Action, InputBuffer},
+    lives::Lives,
+    ownership::Owner,
+    paddle::{Paddle, PaddleStats, Player, Side},
+    paddle_boxes,
+    physics::split,
+    serve::{serve_spots, start_serve, MatchPhase, ServePattern},
+    server,
+    smash::{smashed_dir, SMASH_WINDOW},
+    spawn_ball,
+    special::Energy,
+    Ball, BallAssets, GameRng, GameState, LastPaddleHit, Speed, Tunables, DEFAULT_SPEED, MAX_BALLS,
+    SPLIT_ANGLE, SPLIT_SLOWDOWN, SPLIT_SPEED,
+};
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<BallHitWall>()
+            .add_event::<BallHitPaddle>()
+            .add_event::<BallOutOfBounds>();
+    }
+}
+
+/// `ball` bounced off a wall facing `normal`, touching it at `at`.
+#[derive(Clone, Copy, Debug)]
+pub struct BallHitWall {
+    pub ball: Entity,
+    pub at: Vec3,
+    pub normal: Vec3,
+}
+
+/// `paddle` returned `ball` off its face with `normal`, touching it at `at`.
+#[derive(Clone, Copy, Debug)]
+pub struct BallHitPaddle {
+    pub ball: Entity,
+    pub paddle: Entity,
+    pub at: Vec3,
+    pub normal: Vec3,
+}
+
+/// `ball` crossed the goal line `goal`, an index into `Arena::goals`, at `at`.
+#[derive(Clone, Copy, Debug)]
+pub struct BallOutOfBounds {
+    pub ball: Entity,
+    pub at: Vec3,
+    pub goal: usize,
+}
+
+/// Every bounce speeds the ball up for the next tick.
+pub fn ramp_speed(
+    mut walls: EventReader<BallHitWall>,
+    mut paddles: EventReader<BallHitPaddle>,
+    mut query: Query<&mut Speed, With<Ball>>,
+    tunables: Res<Tunables>,
+) {
+    let balls = walls
+        .iter()
+        .map(|hit| hit.ball)
+        .chain(paddles.iter().map(|hit| hit.ball));
+    for ball in balls {
+        if let Ok(mut speed) = query.get_mut(ball) {
+            speed.speed_multiplier *= tunables.ramp;
+        }
+    }
+}
+
+/// What the returning paddle puts on the ball: a block, a charged shot, a
+/// smash, and a split off a fast enough return. These land after the
+/// bounce, so they take effect from the next tick.
+pub fn return_ball(
+    mut commands: Commands,
+    mut hits: EventReader<BallHitPaddle>,
+    mut query_ball: Query<&mut Speed, With<Ball>>,
+    mut query_paddle: Query<
+        (
+            &Stance,
+            &Side,
+            Option<&mut Charge>,
+            Option<&mut InputBuffer>,
+            Option<&mut Energy>,
+        ),
+        With<Paddle>,
+    >,
+    (ball_assets, asset_server, audio, tunables): (
+        Res<BallAssets>,
+        Res<AssetServer>,
+        Res<Audio>,
+        Res<Tunables>,
+    ),
+) {
+    let mut ball_count = query_ball.iter().len();
+
+    for &BallHitPaddle {
+        ball,
+        paddle,
+        at,
+        normal,
+    } in hits.iter()
+    {
+        let (Ok(mut speed), Ok((stance, side, mut charge, mut buffer, mut energy))) =
+            (query_ball.get_mut(ball), query_paddle.get_mut(paddle))
+        else {
+            continue;
+        };
+
+        if let Some(energy) = &mut energy {
+            energy.gain_return();
+        }
+
+        // a block soaks up the hit, so it leaves no room for charged shots or smashes
+        if *stance == Stance::Blocking {
+            speed.dir = blocked_dir(speed.dir, normal);
+            speed.speed_multiplier = tunables.speed;
+            continue;
+        }
+
+        if let Some(power) = charge.as_mut().and_then(|charge| charge.take_shot()) {
+            speed.dir = charged_dir(speed.dir, normal, power);
+            spawn_callout(&mut commands, &asset_server, "POWER!", at);
+            audio.play(asset_server.load("sounds/charged_shot.wav"));
+        }
+
+        if buffer
+            .as_mut()
+            .map_or(false, |buffer| buffer.take(Action::Smash, SMASH_WINDOW))
+        {
+            speed.dir = smashed_dir(speed.dir);
+            spawn_flash(&mut commands, Color::rgba(1., 1., 1., 0.6));
+            spawn_callout(&mut commands, &asset_server, "SMASH!", at);
+        }
+
+        if speed.dir.length() * DEFAULT_SPEED > SPLIT_SPEED && ball_count < MAX_BALLS {
+            let [kept, split_off] = split(speed.dir, SPLIT_ANGLE, SPLIT_SLOWDOWN);
+            speed.dir = kept;
+            // the split-off starts against the paddle too
+            let split_ball = spawn_ball(&mut commands, &ball_assets, at, split_off);
+            commands
+                .entity(split_ball)
+                .insert((LastPaddleHit { paddle, age: 0. }, Owner(side.0)));
+            spawn_callout(&mut commands, &asset_server, "SPLIT!", at);
+            ball_count += 1;
+        }
+    }
+}
+
+/// Puts wall and paddle hits in the gameplay log.
+pub fn log_hits(
+    mut walls: EventReader<BallHitWall>,
+    mut paddles: EventReader<BallHitPaddle>,
+    mut events: EventWriter<GameplayEvent>,
+    query_ball: Query<&Speed, With<Ball>>,
+    query_paddle: Query<&Transform, With<Paddle>>,
+) {
+    for hit in paddles.iter() {
+        let (Ok(speed), Ok(paddle)) = (query_ball.get(hit.ball), query_paddle.get(hit.paddle))
+        else {
+            continue;
+        };
+        events.send(GameplayEvent::PaddleHit {
+            ball: hit.at,
+            paddle: paddle.translation,
+            dir: speed.dir,
+        });
+    }
+    events.send_batch(walls.iter().map(|hit| GameplayEvent::WallHit {
+        ball: hit.at,
+        normal: hit.normal,
+    }));
+}
+
+/// A goal scores for the other side, or costs a life where lives are on.
+pub fn score_goals(
+    mut goals: EventReader<BallOutOfBounds>,
+    mut game_state: ResMut<GameState>,
+    mut lives: ResMut<Lives>,
+    mut events: EventWriter<GameplayEvent>,
+) {
+    for &BallOutOfBounds { at, goal, .. } in goals.iter() {
+        if lives.take(goal) {
+            // the score stays as it was
+        } else if goal == 0 {
+            game_state.score.0 += 1;
+        } else {
+            game_state.score.1 += 1;
+        }
+        events.send(GameplayEvent::Goal {
+            ball: at,
+            goal,
+            score: game_state.score,
+        });
+    }
+}
+
+/// Extra balls (from a split or multi-ball) just leave play; the last one
+/// is served again.
+pub fn serve_after_goal(
+    mut commands: Commands,
+    mut goals: EventReader<BallOutOfBounds>,
+    mut query: Query<(&mut Transform, &mut Speed), With<Ball>>,
+    query_player: Query<
+        (Entity, &Transform, &PaddleStats, &Side, Option<&Player>),
+        (With<Paddle>, Without<Ball>),
+    >,
+    mut next_phase: ResMut<NextState<MatchPhase>>,
+    (arena, tunables, game_state, ball_assets, mut rng): (
+        Res<Arena>,
+        Res<Tunables>,
+        Res<GameState>,
+        Res<BallAssets>,
+        ResMut<GameRng>,
+    ),
+) {
+    let mut ball_count = query.iter().len();
+
+    for &BallOutOfBounds { ball, goal, .. } in goals.iter() {
+        let Ok((mut transform, mut speed)) = query.get_mut(ball) else {
+            continue;
+        };
+        if ball_count > 1 {
+            commands.entity(ball).despawn();
+            ball_count -= 1;
+            continue;
+        }
+        let server = match tunables.serve {
+            ServePattern::Paddle => server(&arena, goal, &query_player),
+            _ => None,
+        };
+        if let Some(server) = server {
+            start_serve(&mut commands, &mut next_phase, server);
+            continue;
+        }
+        let mut spots = serve_spots(
+            tunables.serve,
+            &arena,
+            goal,
+            game_state.score.0 + game_state.score.1,
+            &paddle_boxes(
+                query_player
+                    .iter()
+                    .map(|(_, transform, stats, ..)| (transform, stats)),
+            ),
+            tunables.ball_size(),
+            &mut rng.0,
+        )
+        .into_iter();
+        if let Some((translation, dir)) = spots.next() {
+            transform.translation = translation;
+            speed.dir = dir;
+        }
+        for (translation, dir) in spots {
+            spawn_ball(&mut commands, &ball_assets, translation, dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serve::rally_goes_on;
+
+    #[test]
+    fn goals_score_for_the_other_side_unless_a_life_covers_them() {
+        let mut world = World::new();
+        world.init_resource::<GameState>();
+        world.insert_resource(Lives {
+            on: true,
+            remaining: 3,
+        });
+        world.init_resource::<Events<BallOutOfBounds>>();
+        world.init_resource::<Events<GameplayEvent>>();
+        let ball = world.spawn_empty().id();
+        for goal in [0, 1] {
+            world.send_event(BallOutOfBounds {
+                ball,
+                at: Vec3::ZERO,
+                goal,
+            });
+        }
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(score_goals);
+        schedule.run(&mut world);
+        // each goal is only heard once
+        schedule.run(&mut world);
+
+        assert_eq!(world.resource::<GameState>().score, (0, 1));
+        assert_eq!(world.resource::<Lives>().remaining, 2);
+        assert_eq!(world.resource::<Events<GameplayEvent>>().len(), 2);
+    }
+
+    #[test]
+    fn a_goal_scores_once_however_many_ticks_the_frame_runs() {
+        let mut world = World::new();
+        world.insert_resource(Arena::default());
+        world.init_resource::<Tunables>();
+        world.init_resource::<GameState>();
+        world.init_resource::<Lives>();
+        world.init_resource::<GameRng>();
+        world.init_resource::<NextState<MatchPhase>>();
+        world.insert_resource(BallAssets {
+            mesh: default(),
+            material: default(),
+        });
+        world.init_resource::<Events<BallOutOfBounds>>();
+        world.init_resource::<Events<GameplayEvent>>();
+        world.spawn((
+            Paddle,
+            Transform::from_translation(Arena::default().paddle_spawn()),
+            PaddleStats {
+                size: Vec2::new(100., 20.),
+                speed: 1.,
+            },
+            Side(0),
+            Player { name: "P1".into() },
+        ));
+        // well past the first goal line
+        world.spawn((
+            Ball,
+            Transform::from_xyz(0., -400., 0.),
+            Speed {
+                dir: Vec3::NEG_Y,
+                speed_multiplier: DEFAULT_SPEED,
+            },
+        ));
+
+        let mut tick = Schedule::new();
+        tick.add_systems(
+            (crate::out_of_bounds, score_goals, serve_after_goal)
+                .chain()
+                .distributive_run_if(rally_goes_on),
+        );
+        // a slow frame's worth, with the serve not taken up until after it
+        for _ in 0..3 {
+            tick.run(&mut world);
+        }
+
+        assert_eq!(world.resource::<GameState>().score, (1, 0));
+        assert_eq!(
+            world.resource::<NextState<MatchPhase>>().0,
+            Some(MatchPhase::Serve)
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! The key bindings screen, off the options screen: a column a player, a
+//! line for each action with the key it's on. Confirm on a line and press the new key to
+//! rebind it; a key another action has, or one the game keeps for itself
+//! (charge, smash and the rest), is turned down with a note saying what
+//! holds it. Under each column the player can mirror their controls for
+//! the left hand, and the first player the HUD too. Reset puts every key
+//! back to the defaults, and each change is saved to the bindings file.
+//! Back returns to the options.
+
+use bevy::{prelude::*, reflect::Enum};
+
+use crate::{
+    bindings::{BindingsFile, KeyAction, KeyBindings},
+    block::BLOCK_KEY,
+    charge::CHARGE_KEY,
+    despawn_screen,
+    engine::ScheduleSetup,
+    flick::FLICK_KEY,
+    focus::{navigate_focus, Focus, FocusEvent, Focusable},
+    paddle::LANE_KEY,
+    rewind::REWIND_KEY,
+    smash::SMASH_KEY,
+    special::SPECIAL_KEY,
+    AppState,
+};
+
+// keys other systems read directly
+const RESERVED: [(KeyCode, &str); 7] = [
+    (CHARGE_KEY, "CHARGE"),
+    (BLOCK_KEY, "BLOCK"),
+    (FLICK_KEY, "FLICK"),
+    (SMASH_KEY, "SMASH"),
+    (SPECIAL_KEY, "SPECIAL"),
+    (LANE_KEY, "LANE SWITCH"),
+    (REWIND_KEY, "REWIND"),
+];
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Controls, spawn_controls)
+            // after the focus, so the Confirm that binds a key can't start
+            // another rebind
+            .add_systems_during(
+                AppState::Controls,
+                (rebind_keys, show_bindings).chain().after(navigate_focus),
+            )
+            .add_systems_on_exit(AppState::Controls, despawn_screen::<ControlsScreen>);
+    }
+}
+
+#[derive(Component)]
+struct ControlsScreen;
+
+/// The line for one player's key for one action.
+#[derive(Component, Clone, Copy, PartialEq)]
+struct BindingLine {
+    player: usize,
+    action: KeyAction,
+}
+
+/// A player's mirroring toggle, for the HUD or their controls.
+#[derive(Component, Clone, Copy)]
+struct MirrorLine {
+    player: usize,
+    hud: bool,
+}
+
+#[derive(Component)]
+struct ResetLine;
+
+#[derive(Component)]
+struct StatusText;
+
+/// The line waiting for a key, and what the last attempt came to.
+#[derive(Resource, Default)]
+struct Rebinding {
+    listening: Option<(Entity, BindingLine)>,
+    status: String,
+}
+
+fn key_name(key: KeyCode) -> String {
+    key.variant_name().to_uppercase()
+}
+
+fn player_label(player: usize, action: KeyAction) -> String {
+    format!("P{} {}", player + 1, action.label())
+}
+
+/// Why `key` can't go to `line`, if it can't.
+fn refusal(bindings: &mut KeyBindings, line: BindingLine, key: KeyCode) -> Option<String> {
+    if let Some((_, name)) = RESERVED.iter().find(|(reserved, _)| {
+        (0..bindings.players.len()).any(|player| bindings.reserved(player, *reserved) == key)
+    }) {
+        return Some(format!("{} IS {name}", key_name(key)));
+    }
+    bindings
+        .rebind(line.player, line.action, key)
+        .err()
+        .map(|(player, action)| format!("{} IS {}", key_name(key), player_label(player, action)))
+}
+
+fn spawn_controls(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    bindings: Res<KeyBindings>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(2.)),
+            ..default()
+        })
+    };
+    commands.insert_resource(Rebinding::default());
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                ..default()
+            },
+            ControlsScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("KEY BINDINGS".into(), 48.).with_style(Style {
+                margin: UiRect::bottom(Val::Px(16.)),
+                ..default()
+            }));
+            // a column a player
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        margin: UiRect::bottom(Val::Px(12.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for player in 0..bindings.players.len() {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::horizontal(Val::Px(24.)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(text(format!("PLAYER {}", player + 1), 24.));
+                                for action in KeyAction::ALL {
+                                    parent.spawn((
+                                        text(String::new(), 20.),
+                                        Focusable::default(),
+                                        BindingLine { player, action },
+                                    ));
+                                }
+                                // the HUD is the first player's
+                                let huds: &[bool] = if player == 0 {
+                                    &[false, true]
+                                } else {
+                                    &[false]
+                                };
+                                for &hud in huds {
+                                    parent.spawn((
+                                        text(String::new(), 20.),
+                                        Focusable { adjusts: true },
+                                        MirrorLine { player, hud },
+                                    ));
+                                }
+                            });
+                    }
+                });
+            parent.spawn((
+                text("RESET TO DEFAULTS".into(), 24.),
+                Focusable::default(),
+                ResetLine,
+            ));
+            parent.spawn((text(String::new(), 18.), StatusText));
+        });
+}
+
+fn rebind_keys(
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+    mut events: EventReader<FocusEvent>,
+    mut focus: ResMut<Focus>,
+    mut next_state: ResMut<NextState<AppState>>,
+    (query_line, query_mirror, query_reset): (
+        Query<&BindingLine>,
+        Query<&MirrorLine>,
+        Query<(), With<ResetLine>>,
+    ),
+    (keys, file): (Res<Input<KeyCode>>, Res<BindingsFile>),
+) {
+    let mut changed = false;
+    if let Some((entity, line)) = rebinding.listening {
+        // every key binds while one's awaited, Escape and the arrows included
+        events.clear();
+        let Some(&key) = keys.get_just_pressed().next() else {
+            return;
+        };
+        rebinding.status = match refusal(&mut bindings, line, key) {
+            Some(refusal) => refusal,
+            None => {
+                changed = true;
+                format!(
+                    "{} ON {}",
+                    player_label(line.player, line.action),
+                    key_name(key)
+                )
+            }
+        };
+        rebinding.listening = None;
+        focus.0 = Some(entity);
+    }
+
+    for event in events.iter() {
+        match *event {
+            // only the mirroring lines adjust
+            FocusEvent::Activated(entity) | FocusEvent::Adjusted(entity, _) => {
+                if let Ok(&line) = query_line.get(entity) {
+                    rebinding.listening = Some((entity, line));
+                    rebinding.status =
+                        format!("PRESS A KEY FOR {}", player_label(line.player, line.action));
+                } else if query_reset.contains(entity) {
+                    *bindings = KeyBindings::default();
+                    rebinding.status = "EVERY KEY BACK TO ITS DEFAULT".into();
+                    changed = true;
+                } else if let Ok(&MirrorLine { player, hud }) = query_mirror.get(entity) {
+                    if hud {
+                        if let Some(keys) = bindings.players.get_mut(player) {
+                            keys.handedness.mirror_hud = !keys.handedness.mirror_hud;
+                            changed = true;
+                        }
+                    } else {
+                        match bindings.mirror(player) {
+                            Ok(()) => changed = true,
+                            Err((holder, action)) => {
+                                let key = bindings.player(holder).key(action);
+                                rebinding.status = format!(
+                                    "{} IS {}",
+                                    key_name(key),
+                                    player_label(holder, action)
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            FocusEvent::Back => next_state.set(AppState::Options),
+        }
+    }
+
+    if let (true, Some(path)) = (changed, &file.0) {
+        bindings.save(path);
+    }
+}
+
+fn show_bindings(
+    mut query_line: Query<(&BindingLine, &mut Text), Without<StatusText>>,
+    mut query_mirror: Query<(&MirrorLine, &mut Text), (Without<BindingLine>, Without<StatusText>)>,
+    mut query_status: Query<&mut Text, With<StatusText>>,
+    rebinding: Res<Rebinding>,
+    bindings: Res<KeyBindings>,
+) {
+    if !rebinding.is_changed() && !bindings.is_changed() {
+        return;
+    }
+    for (line, mut text) in &mut query_line {
+        let key = match rebinding.listening {
+            Some((_, listening)) if listening == *line => "?".to_owned(),
+            _ => key_name(bindings.player(line.player).key(line.action)),
+        };
+        text.sections[0].value = format!("{}  < {key} >", line.action.label());
+    }
+    for (line, mut text) in &mut query_mirror {
+        let handedness = bindings.player(line.player).handedness;
+        let (label, on) = if line.hud {
+            ("MIRROR HUD", handedness.mirror_hud)
+        } else {
+            ("MIRROR CONTROLS", handedness.mirror_controls)
+        };
+        text.sections[0].value = format!("{label}  < {} >", if on { "on" } else { "off" });
+    }
+    for mut text in &mut query_status {
+        text.sections[0].value.clone_from(&rebinding.status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_down_keys_already_in_use() {
+        let mut bindings = KeyBindings::default();
+        let serve = BindingLine {
+            player: 0,
+            action: KeyAction::Serve,
+        };
+        assert_eq!(
+            refusal(&mut bindings, serve, KeyCode::Space).as_deref(),
+            Some("SPACE IS CHARGE")
+        );
+        assert_eq!(
+            refusal(&mut bindings, serve, KeyCode::D).as_deref(),
+            Some("D IS P2 MOVE RIGHT")
+        );
+        assert_eq!(refusal(&mut bindings, serve, KeyCode::M), None);
+        assert_eq!(bindings.player(0).serve, KeyCode::M);
+    }
+}
@@ -0,0 +1,262 @@
+//! Desync detection for lockstep and rollback play. Both peers run the same
+//! seeded [`Simulation`]; every few frames each sends the other a checksum of
+//! its serialized state. A mismatch means the simulations have diverged, and
+//! the report names the frame and, when the peer sent its state along, which
+//! fields differ.
+
+use std::{collections::VecDeque, fmt};
+
+use bevy::prelude::*;
+
+use crate::sim::{SimBall, Simulation};
+
+// frames of local state kept to compare against late checksums
+const HISTORY_LEN: usize = 240;
+
+/// Everything that has to match between peers after a given frame.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameState {
+    pub frame: u64,
+    pub balls: Vec<SimBall>,
+    pub paddle: Vec3,
+    pub score: u32,
+}
+
+impl FrameState {
+    pub fn capture(sim: &Simulation) -> Self {
+        Self {
+            frame: sim.steps(),
+            balls: sim.balls().to_vec(),
+            paddle: sim.paddle(),
+            score: sim.score(),
+        }
+    }
+
+    /// Little-endian byte encoding; floats go in bit for bit so `-0.` and NaN
+    /// payloads count as differences too.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        fn put_vec3(bytes: &mut Vec<u8>, v: Vec3) {
+            for f in v.to_array() {
+                bytes.extend(f.to_bits().to_le_bytes());
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(28 + self.balls.len() * 28);
+
+        bytes.extend(self.frame.to_le_bytes());
+        bytes.extend(self.score.to_le_bytes());
+        put_vec3(&mut bytes, self.paddle);
+        bytes.extend((self.balls.len() as u32).to_le_bytes());
+        for ball in &self.balls {
+            put_vec3(&mut bytes, ball.translation);
+            put_vec3(&mut bytes, ball.speed.dir);
+            bytes.extend(ball.speed.speed_multiplier.to_bits().to_le_bytes());
+        }
+        bytes
+    }
+
+    /// FNV-1a over [`Self::to_bytes`], stable across platforms and builds.
+    pub fn checksum(&self) -> u64 {
+        self.to_bytes()
+            .iter()
+            .fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+            })
+    }
+
+    /// Human-readable list of the fields that differ from `other`.
+    pub fn diff(&self, other: &FrameState) -> Vec<String> {
+        let mut diff = Vec::new();
+        if self.score != other.score {
+            diff.push(format!("score: {} vs {}", self.score, other.score));
+        }
+        if self.paddle != other.paddle {
+            diff.push(format!("paddle: {} vs {}", self.paddle, other.paddle));
+        }
+        if self.balls.len() != other.balls.len() {
+            diff.push(format!(
+                "ball count: {} vs {}",
+                self.balls.len(),
+                other.balls.len()
+            ));
+        }
+        for (i, (a, b)) in self.balls.iter().zip(&other.balls).enumerate() {
+            if a.translation != b.translation {
+                diff.push(format!(
+                    "ball {i} translation: {} vs {}",
+                    a.translation, b.translation
+                ));
+            }
+            if a.speed.dir != b.speed.dir {
+                diff.push(format!("ball {i} dir: {} vs {}", a.speed.dir, b.speed.dir));
+            }
+            if a.speed.speed_multiplier != b.speed.speed_multiplier {
+                diff.push(format!(
+                    "ball {i} multiplier: {} vs {}",
+                    a.speed.speed_multiplier, b.speed.speed_multiplier
+                ));
+            }
+        }
+        diff
+    }
+}
+
+/// A checksum to send to the peer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameChecksum {
+    pub frame: u64,
+    pub checksum: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DesyncReport {
+    pub frame: u64,
+    pub local_checksum: u64,
+    pub remote_checksum: u64,
+    pub local: FrameState,
+    /// Field differences, empty when the peer only sent a checksum.
+    pub diff: Vec<String>,
+}
+
+impl fmt::Display for DesyncReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "desync at frame {}: local checksum {:016x}, remote {:016x}",
+            self.frame, self.local_checksum, self.remote_checksum
+        )?;
+        for line in &self.diff {
+            write!(f, "\n  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps recent local states and checks the peer's checksums against them.
+pub struct DesyncDetector {
+    /// Frames between checksums sent to the peer.
+    pub interval: u64,
+    history: VecDeque<FrameState>,
+    report: Option<DesyncReport>,
+}
+
+impl DesyncDetector {
+    pub fn new(interval: u64) -> Self {
+        Self {
+            interval: interval.max(1),
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            report: None,
+        }
+    }
+
+    /// Records the state after a step, returning a checksum when one is due.
+    pub fn record(&mut self, sim: &Simulation) -> Option<FrameChecksum> {
+        let state = FrameState::capture(sim);
+        let due = (state.frame % self.interval == 0).then(|| FrameChecksum {
+            frame: state.frame,
+            checksum: state.checksum(),
+        });
+
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(state);
+        due
+    }
+
+    /// Compares a checksum from the peer against the local state for the same
+    /// frame. `remote_state` is optional debug payload used to diff the two.
+    /// Only the first desync is reported; everything after it diverges anyway.
+    pub fn check_remote(
+        &mut self,
+        remote: FrameChecksum,
+        remote_state: Option<&FrameState>,
+    ) -> Result<(), &DesyncReport> {
+        if self.report.is_none() {
+            if let Some(report) = self.compare(remote, remote_state) {
+                error!("{report}");
+                self.report = Some(report);
+            }
+        }
+        self.report.as_ref().map_or(Ok(()), Err)
+    }
+
+    fn compare(
+        &self,
+        remote: FrameChecksum,
+        remote_state: Option<&FrameState>,
+    ) -> Option<DesyncReport> {
+        let Some(local) = self
+            .history
+            .iter()
+            .find(|state| state.frame == remote.frame)
+        else {
+            warn!(
+                "no local state for frame {}, checksum skipped",
+                remote.frame
+            );
+            return None;
+        };
+
+        let local_checksum = local.checksum();
+        (local_checksum != remote.checksum).then(|| DesyncReport {
+            frame: remote.frame,
+            local_checksum,
+            remote_checksum: remote.checksum,
+            local: local.clone(),
+            diff: remote_state.map_or_else(Vec::new, |remote| local.diff(remote)),
+        })
+    }
+
+    pub fn report(&self) -> Option<&DesyncReport> {
+        self.report.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::SimConfig;
+
+    fn run_pair(remote_config: SimConfig) -> Option<DesyncReport> {
+        let mut local = Simulation::new(SimConfig::default(), 7);
+        let mut remote = Simulation::new(remote_config, 7);
+        let mut detector = DesyncDetector::new(10);
+
+        for _ in 0..600 {
+            local.step();
+            remote.step();
+            if detector.record(&local).is_some() {
+                let state = FrameState::capture(&remote);
+                let checksum = FrameChecksum {
+                    frame: state.frame,
+                    checksum: state.checksum(),
+                };
+                if detector.check_remote(checksum, Some(&state)).is_err() {
+                    break;
+                }
+            }
+        }
+        detector.report().cloned()
+    }
+
+    #[test]
+    fn identical_peers_stay_in_sync() {
+        assert!(run_pair(SimConfig::default()).is_none());
+    }
+
+    #[test]
+    fn diverging_peer_is_reported_with_a_diff() {
+        let report = run_pair(SimConfig {
+            dt: 1. / 59.,
+            ..default()
+        })
+        .expect("desync not detected");
+
+        assert_eq!(report.frame, 10);
+        assert!(report
+            .diff
+            .iter()
+            .any(|line| line.starts_with("ball 0 translation")));
+    }
+}
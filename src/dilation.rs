@@ -0,0 +1,45 @@
+//! Time-dilation power-up: collecting it drops every ball in play to 40% speed
+//! for three seconds while the paddles keep full speed, with a ticking clock
+//! to mark the window.
+
+use bevy::prelude::*;
+
+use crate::{
+    engine::ScheduleSetup,
+    pickup::{Pickup, PickupCollected},
+    time_scale::TimeScale,
+    AppState, Ball,
+};
+
+const DILATION_SCALE: f32 = 0.4;
+const DILATION_DURATION: f32 = 3.;
+
+pub struct DilationPlugin;
+
+impl Plugin for DilationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, start_dilation);
+    }
+}
+
+fn start_dilation(
+    mut commands: Commands,
+    mut collected: EventReader<PickupCollected>,
+    query: Query<Entity, With<Ball>>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+) {
+    if !collected
+        .iter()
+        .any(|event| event.pickup == Pickup::TimeDilation)
+    {
+        return;
+    }
+
+    for entity in &query {
+        commands
+            .entity(entity)
+            .insert(TimeScale::new(DILATION_SCALE, DILATION_DURATION));
+    }
+    audio.play(asset_server.load("sounds/clock_tick.wav"));
+}
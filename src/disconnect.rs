@@ -0,0 +1,160 @@
+//! Pausing for a lost controller. The gamepad the player last pressed a
+//! button on is theirs until they touch the keyboard; if it disconnects in
+//! the middle of a match, the game pauses behind a "controller disconnected"
+//! prompt rather than playing on without them. Plugging a pad back in picks
+//! play up again on it, and a press on the keyboard or any other pad hands
+//! the match over to that.
+
+use bevy::{input::gamepad::GamepadConnectionEvent, prelude::*};
+
+use crate::engine::{ScheduleSetup, Stage};
+use crate::AppState;
+
+const DISCONNECTED_PROMPT: &str =
+    "CONTROLLER DISCONNECTED\nreconnect it or press a key to reassign";
+
+pub struct DisconnectPlugin;
+
+impl Plugin for DisconnectPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Controller>()
+            .add_systems_at(Stage::Startup, spawn_disconnect_screen)
+            .add_system(watch_controller);
+    }
+}
+
+/// The player's gamepad, and whether the match is waiting for it.
+#[derive(Resource, Default)]
+struct Controller {
+    pad: Option<Gamepad>,
+    lost: bool,
+}
+
+impl Controller {
+    /// `pad` went away; returns whether to wait for it.
+    fn disconnected(&mut self, pad: Gamepad, playing: bool) -> bool {
+        if self.pad != Some(pad) {
+            return false;
+        }
+        self.pad = None;
+        self.lost = playing;
+        self.lost
+    }
+
+    /// Input from `pad`, or the keyboard for `None`; returns whether that
+    /// ends a wait.
+    fn used(&mut self, pad: Option<Gamepad>) -> bool {
+        self.pad = pad;
+        std::mem::take(&mut self.lost)
+    }
+}
+
+#[derive(Component)]
+struct DisconnectScreen;
+
+fn spawn_disconnect_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                // over the idle screen, which only asks for what this does too
+                z_index: ZIndex::Global(11),
+                ..default()
+            },
+            DisconnectScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    DISCONNECTED_PROMPT,
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 40.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+            );
+        });
+}
+
+fn watch_controller(
+    mut controller: ResMut<Controller>,
+    mut time: ResMut<Time>,
+    mut connections: EventReader<GamepadConnectionEvent>,
+    mut query: Query<&mut Visibility, With<DisconnectScreen>>,
+    // whether the wait paused the game, so it leaves anyone else's pause alone
+    mut paused_it: Local<bool>,
+    (keyboard_input, gamepad_input, state): (
+        Res<Input<KeyCode>>,
+        Res<Input<GamepadButton>>,
+        Res<State<AppState>>,
+    ),
+) {
+    let was_lost = controller.lost;
+    let playing = state.0 == AppState::Playing;
+    for event in connections.iter() {
+        if event.connected() {
+            // pads often come back under a new id, so whichever turns up
+            if controller.lost {
+                controller.used(Some(event.gamepad));
+            }
+        } else {
+            controller.disconnected(event.gamepad, playing);
+        }
+    }
+    if let Some(button) = gamepad_input.get_just_pressed().next() {
+        controller.used(Some(button.gamepad));
+    } else if keyboard_input.get_just_pressed().len() > 0 {
+        controller.used(None);
+    }
+    // a match that ended some other way isn't waiting any more
+    if !playing {
+        controller.lost = false;
+    }
+    if controller.lost == was_lost {
+        return;
+    }
+
+    if controller.lost {
+        *paused_it = !time.is_paused();
+        time.pause();
+    } else if std::mem::take(&mut *paused_it) {
+        time.unpause();
+    }
+    for mut visibility in &mut query {
+        *visibility = if controller.lost {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_only_for_the_players_pad_mid_match() {
+        let (mine, other) = (Gamepad::new(0), Gamepad::new(1));
+        let mut controller = Controller::default();
+        controller.used(Some(mine));
+        assert!(!controller.disconnected(other, true));
+        assert!(controller.disconnected(mine, true));
+        // the keyboard takes over
+        assert!(controller.used(None));
+        assert!(!controller.lost && controller.pad.is_none());
+
+        controller.used(Some(mine));
+        assert!(!controller.disconnected(mine, false));
+    }
+}
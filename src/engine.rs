@@ -0,0 +1,185 @@
+//! Thin wrappers over the Bevy APIs that change shape from one release to
+//! the next: the AABB collision test, the 2D mesh primitives and where in
+//! the frame a plugin's systems go. Systems and plugins go through these
+//! rather than the engine's own, so moving to a newer Bevy means rewriting
+//! this module and not every caller.
+
+use bevy::{ecs::schedule::SystemConfigs, prelude::*, sprite::collide_aabb::collide};
+
+/// Whether boxes of `a_size` and `b_size` centered on `a` and `b` overlap.
+pub fn overlaps(a: Vec3, a_size: Vec2, b: Vec3, b_size: Vec2) -> bool {
+    collide(a, a_size, b, b_size).is_some()
+}
+
+/// A flat `size` rectangle centered on the origin.
+pub fn rectangle(size: Vec2) -> Mesh {
+    shape::Box::new(size.x, size.y, 0.).into()
+}
+
+pub fn circle(radius: f32) -> Mesh {
+    shape::Circle::new(radius).into()
+}
+
+/// A regular polygon of `sides` with its corners `radius` from the center.
+pub fn polygon(radius: f32, sides: usize) -> Mesh {
+    shape::RegularPolygon::new(radius, sides).into()
+}
+
+/// The fixed points of a frame a system can be placed at, besides the
+/// update itself and the state schedules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Once, before the first frame.
+    Startup,
+    First,
+    /// After input is read, before the update.
+    PreUpdate,
+    /// The physics tick, as many times a frame as the fixed timestep needs.
+    FixedUpdate,
+    /// After the update, before transforms are propagated.
+    PostUpdate,
+    Last,
+}
+
+/// One system or a tuple of them, as every [`ScheduleSetup`] method takes.
+pub trait IntoSystems<Marker> {
+    fn into_systems(self) -> SystemConfigs;
+}
+
+/// Marks the [`IntoSystems`] impl for a single system.
+pub struct OneSystem;
+
+/// Marks the [`IntoSystems`] impl for a tuple of systems.
+pub struct ManySystems;
+
+impl<M, T: IntoSystemConfig<M>> IntoSystems<(OneSystem, M)> for T {
+    fn into_systems(self) -> SystemConfigs {
+        (self.into_config(),).into_configs()
+    }
+}
+
+impl<M, T: IntoSystemConfigs<M>> IntoSystems<(ManySystems, M)> for T {
+    fn into_systems(self) -> SystemConfigs {
+        self.into_configs()
+    }
+}
+
+/// Where a plugin's systems run, added the same way whatever the engine
+/// calls its schedules and base sets.
+pub trait ScheduleSetup {
+    /// Runs `systems` at `stage`.
+    fn add_systems_at<M>(&mut self, stage: Stage, systems: impl IntoSystems<M>) -> &mut Self;
+
+    /// Runs `systems` every frame while `state` is current.
+    fn add_systems_during<S: States, M>(
+        &mut self,
+        state: S,
+        systems: impl IntoSystems<M>,
+    ) -> &mut Self;
+
+    /// Runs `systems` once each time `state` is entered.
+    fn add_systems_on_enter<S: States, M>(
+        &mut self,
+        state: S,
+        systems: impl IntoSystems<M>,
+    ) -> &mut Self;
+
+    /// Runs `systems` once each time `state` is left.
+    fn add_systems_on_exit<S: States, M>(
+        &mut self,
+        state: S,
+        systems: impl IntoSystems<M>,
+    ) -> &mut Self;
+}
+
+impl ScheduleSetup for App {
+    fn add_systems_at<M>(&mut self, stage: Stage, systems: impl IntoSystems<M>) -> &mut Self {
+        let systems = systems.into_systems();
+        match stage {
+            Stage::Startup => self.add_systems(systems.in_schedule(CoreSchedule::Startup)),
+            Stage::FixedUpdate => self.add_systems(systems.in_schedule(CoreSchedule::FixedUpdate)),
+            Stage::First => self.add_systems(systems.in_base_set(CoreSet::First)),
+            Stage::PreUpdate => self.add_systems(systems.in_base_set(CoreSet::PreUpdate)),
+            Stage::PostUpdate => self.add_systems(systems.in_base_set(CoreSet::PostUpdate)),
+            Stage::Last => self.add_systems(systems.in_base_set(CoreSet::Last)),
+        }
+    }
+
+    fn add_systems_during<S: States, M>(
+        &mut self,
+        state: S,
+        systems: impl IntoSystems<M>,
+    ) -> &mut Self {
+        self.add_systems(systems.into_systems().in_set(OnUpdate(state)))
+    }
+
+    fn add_systems_on_enter<S: States, M>(
+        &mut self,
+        state: S,
+        systems: impl IntoSystems<M>,
+    ) -> &mut Self {
+        self.add_systems(systems.into_systems().in_schedule(OnEnter(state)))
+    }
+
+    fn add_systems_on_exit<S: States, M>(
+        &mut self,
+        state: S,
+        systems: impl IntoSystems<M>,
+    ) -> &mut Self {
+        self.add_systems(systems.into_systems().in_schedule(OnExit(state)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(States, Clone, Copy, Default, Debug, Hash, PartialEq, Eq)]
+    enum Phase {
+        #[default]
+        Off,
+        On,
+    }
+
+    #[derive(Resource, Default)]
+    struct Ran(Vec<&'static str>);
+
+    fn ran(app: &mut App) -> Vec<&'static str> {
+        std::mem::take(&mut app.world.resource_mut::<Ran>().0)
+    }
+
+    #[test]
+    fn systems_run_where_they_were_put() {
+        let mut app = App::new();
+        app.add_state::<Phase>()
+            .init_resource::<Ran>()
+            .add_systems_at(Stage::Startup, |mut ran: ResMut<Ran>| ran.0.push("startup"))
+            .add_systems_on_enter(Phase::On, |mut ran: ResMut<Ran>| ran.0.push("enter"))
+            .add_systems_during(
+                Phase::On,
+                (
+                    |mut ran: ResMut<Ran>| ran.0.push("first"),
+                    |mut ran: ResMut<Ran>| ran.0.push("second"),
+                )
+                    .chain(),
+            )
+            .add_systems_on_exit(Phase::On, |mut ran: ResMut<Ran>| ran.0.push("exit"));
+
+        app.update();
+        assert_eq!(ran(&mut app), ["startup"]);
+        app.world.resource_mut::<NextState<Phase>>().set(Phase::On);
+        app.update();
+        assert_eq!(ran(&mut app), ["enter", "first", "second"]);
+        app.world.resource_mut::<NextState<Phase>>().set(Phase::Off);
+        app.update();
+        assert_eq!(ran(&mut app), ["exit"]);
+    }
+
+    #[test]
+    fn boxes_overlap_only_when_they_cross() {
+        let size = Vec2::splat(10.);
+        assert!(overlaps(Vec3::ZERO, size, Vec3::new(9., 0., 0.), size));
+        assert!(!overlaps(Vec3::ZERO, size, Vec3::new(11., 0., 0.), size));
+        assert!(!overlaps(Vec3::ZERO, size, Vec3::new(0., -11., 0.), size));
+    }
+}
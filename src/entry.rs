@@ -0,0 +1,206 @@
+//! Name entry for a bracket, off the title menu's Tournament item: type a
+//! name and Confirm to add it, Backspace on an empty line to take the last
+//! one back, and Confirm on an empty line once [`MIN_ENTRANTS`] are in to
+//! seed the bracket in entry order and go on to the pre-match select. Back
+//! returns to the title.
+
+use bevy::prelude::*;
+
+use crate::{
+    despawn_screen,
+    engine::ScheduleSetup,
+    prompt::{MenuAction, MenuInput},
+    tournament::Tournament,
+    AppState,
+};
+
+pub const MIN_ENTRANTS: usize = 3;
+pub const MAX_ENTRANTS: usize = 8;
+const MAX_NAME_LENGTH: usize = 12;
+
+pub struct EntryPlugin;
+
+impl Plugin for EntryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Entry, spawn_entry_screen)
+            .add_systems_during(AppState::Entry, enter_names)
+            .add_systems_on_exit(AppState::Entry, despawn_screen::<EntryScreen>);
+    }
+}
+
+/// The names entered so far and the one being typed.
+#[derive(Resource, Default)]
+struct Entrants {
+    names: Vec<String>,
+    typing: String,
+}
+
+impl Entrants {
+    fn push(&mut self, c: char) {
+        if self.names.len() < MAX_ENTRANTS
+            && self.typing.chars().count() < MAX_NAME_LENGTH
+            && !c.is_control()
+        {
+            self.typing.push(c);
+        }
+    }
+
+    fn backspace(&mut self) {
+        if self.typing.pop().is_none() {
+            self.names.pop();
+        }
+    }
+
+    /// Ends the line being typed, adding it unless it's blank or taken, and
+    /// whether that was an empty line with enough names in to start.
+    fn submit(&mut self) -> bool {
+        let name = self.typing.trim();
+        if name.is_empty() {
+            self.typing.clear();
+            return self.names.len() >= MIN_ENTRANTS;
+        }
+        let taken = self
+            .names
+            .iter()
+            .any(|entered| entered.eq_ignore_ascii_case(name));
+        if !taken {
+            self.names.push(name.to_owned());
+            self.typing.clear();
+        }
+        false
+    }
+
+    fn lines(&self, input: &MenuInput) -> String {
+        let mut lines: Vec<String> = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(seed, name)| format!("{}. {name}", seed + 1))
+            .collect();
+        if self.names.len() < MAX_ENTRANTS {
+            lines.push(format!("{}. {}_", self.names.len() + 1, self.typing));
+        }
+        lines.push(String::new());
+        let confirm = input.glyph(MenuAction::Confirm).to_uppercase();
+        lines.push(
+            if self.names.len() >= MIN_ENTRANTS && self.typing.is_empty() {
+                format!("{confirm} ON AN EMPTY LINE TO START")
+            } else {
+                format!("{confirm} TO ADD, {MIN_ENTRANTS} TO {MAX_ENTRANTS} PLAYERS")
+            },
+        );
+        lines.join("\n")
+    }
+}
+
+#[derive(Component)]
+struct EntryScreen;
+
+#[derive(Component)]
+struct EntryText;
+
+fn spawn_entry_screen(mut commands: Commands, asset_server: Res<AssetServer>, input: MenuInput) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let entrants = Entrants::default();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                ..default()
+            },
+            EntryScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "TOURNAMENT",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 48.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(24.)),
+                    ..default()
+                }),
+            );
+            parent.spawn((
+                TextBundle::from_section(
+                    entrants.lines(&input),
+                    TextStyle {
+                        font,
+                        font_size: 24.,
+                        color: Color::WHITE,
+                    },
+                ),
+                EntryText,
+            ));
+        });
+    commands.insert_resource(entrants);
+}
+
+fn enter_names(
+    mut commands: Commands,
+    mut entrants: ResMut<Entrants>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut query: Query<&mut Text, With<EntryText>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    (keys, input): (Res<Input<KeyCode>>, MenuInput),
+) {
+    for event in characters.iter() {
+        entrants.push(event.char);
+    }
+    if keys.just_pressed(KeyCode::Back) {
+        entrants.backspace();
+    }
+    if input.just_pressed(MenuAction::Back) {
+        next_state.set(AppState::Menu);
+    } else if input.just_pressed(MenuAction::Confirm) && entrants.submit() {
+        commands.insert_resource(Tournament::bracket(std::mem::take(&mut entrants.names)));
+        next_state.set(AppState::CharacterSelect);
+    }
+
+    if entrants.is_changed() {
+        for mut text in &mut query {
+            text.sections[0].value = entrants.lines(&input);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn starts_on_an_empty_line() {
+        let mut entrants = Entrants::default();
+        for name in ["Ana", "ana ", "Ben"] {
+            name.chars().for_each(|c| entrants.push(c));
+            assert!(!entrants.submit());
+            entrants.typing.clear();
+        }
+        // a taken name stays out
+        assert_eq!(entrants.names, ["Ana", "Ben"]);
+        assert!(!entrants.submit());
+
+        "Cho".chars().for_each(|c| entrants.push(c));
+        entrants.backspace();
+        entrants.push('e');
+        assert!(!entrants.submit());
+        assert_eq!(entrants.names[2], "Che");
+        entrants.backspace();
+        assert_eq!(entrants.names.len(), 2);
+        "Dee".chars().for_each(|c| entrants.push(c));
+        entrants.submit();
+        assert!(entrants.submit());
+    }
+}
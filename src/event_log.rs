@@ -0,0 +1,132 @@
+//! Gameplay event log. Collisions, goals, pickups, specials and state changes
+//! are sent as [`GameplayEvent`]s, which anything (stats, achievements) can
+//! read; the log keeps the most recent ones with their frame numbers so a
+//! "the ball went through my paddle" report can be checked against what the
+//! game actually saw. F7 writes the buffer to `event_log.txt`.
+
+use std::{collections::VecDeque, fmt::Write as _, fs};
+
+use bevy::{core::FrameCount, prelude::*};
+
+use crate::{
+    breakout::BrickBroken,
+    hotkey::Hotkeys,
+    pickup::{Pickup, PickupCollected},
+    special::{Special, SpecialActivated},
+    AppState,
+};
+
+pub const DUMP_LOG_KEY: KeyCode = KeyCode::F7;
+
+const LOG_CAPACITY: usize = 4096;
+const LOG_PATH: &str = "event_log.txt";
+
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameplayEvent>()
+            .init_resource::<EventLog>()
+            .add_system(forward_events)
+            .add_system(record_events.after(forward_events))
+            .add_system(dump_log.after(record_events));
+    }
+}
+
+// fields are only read through `Debug` in the dump until stats consume them
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug)]
+pub enum GameplayEvent {
+    WallHit {
+        ball: Vec3,
+        normal: Vec3,
+    },
+    PaddleHit {
+        ball: Vec3,
+        paddle: Vec3,
+        dir: Vec3,
+    },
+    /// `goal` indexes `Arena::goals`.
+    Goal {
+        ball: Vec3,
+        goal: usize,
+        score: (u32, u32),
+    },
+    BrickHit {
+        brick: Vec3,
+    },
+    Pickup(Pickup),
+    Special(Special),
+    StateChanged(AppState),
+}
+
+#[derive(Resource, Default)]
+pub struct EventLog {
+    entries: VecDeque<(u32, GameplayEvent)>,
+}
+
+impl EventLog {
+    pub fn entries(&self) -> impl Iterator<Item = &(u32, GameplayEvent)> {
+        self.entries.iter()
+    }
+
+    fn push(&mut self, frame: u32, event: GameplayEvent) {
+        if self.entries.len() == LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((frame, event));
+    }
+}
+
+// turns events owned by other modules into log entries
+fn forward_events(
+    mut events: EventWriter<GameplayEvent>,
+    mut bricks: EventReader<BrickBroken>,
+    mut pickups: EventReader<PickupCollected>,
+    mut specials: EventReader<SpecialActivated>,
+    state: Res<State<AppState>>,
+) {
+    events.send_batch(
+        bricks
+            .iter()
+            .map(|event| GameplayEvent::BrickHit { brick: event.brick }),
+    );
+    events.send_batch(
+        pickups
+            .iter()
+            .map(|event| GameplayEvent::Pickup(event.pickup)),
+    );
+    events.send_batch(
+        specials
+            .iter()
+            .map(|event| GameplayEvent::Special(event.special)),
+    );
+    if state.is_changed() {
+        events.send(GameplayEvent::StateChanged(state.0));
+    }
+}
+
+fn record_events(
+    mut log: ResMut<EventLog>,
+    mut events: EventReader<GameplayEvent>,
+    frame: Res<FrameCount>,
+) {
+    for &event in events.iter() {
+        log.push(frame.0, event);
+    }
+}
+
+fn dump_log(log: Res<EventLog>, hotkeys: Hotkeys) {
+    if !hotkeys.just_pressed(DUMP_LOG_KEY) {
+        return;
+    }
+
+    let mut dump = String::new();
+    for (frame, event) in log.entries() {
+        let _ = writeln!(dump, "{frame:>8} {event:?}");
+    }
+    match fs::write(LOG_PATH, dump) {
+        Ok(()) => info!("wrote {} events to {LOG_PATH}", log.entries.len()),
+        Err(err) => error!("couldn't write {LOG_PATH}: {err}"),
+    }
+}
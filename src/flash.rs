@@ -0,0 +1,60 @@
+//! Full-screen flashes for big hits.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::time_scale::{scaled, TimeScale};
+
+const FLASH_DURATION: f32 = 0.15;
+const FLASH_SIZE: Vec2 = Vec2::new(4000., 4000.);
+
+pub struct FlashPlugin;
+
+impl Plugin for FlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(fade_flashes);
+    }
+}
+
+#[derive(Component)]
+pub struct Flash {
+    timer: Timer,
+    alpha: f32,
+}
+
+/// Covers the screen in `color`, fading out from the color's alpha.
+pub fn spawn_flash(commands: &mut Commands, color: Color) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(FLASH_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(0., 0., 20.),
+            ..default()
+        },
+        Flash {
+            timer: Timer::from_seconds(FLASH_DURATION, TimerMode::Once),
+            alpha: color.a(),
+        },
+    ));
+}
+
+fn fade_flashes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Flash, &mut Sprite, Option<&TimeScale>)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut flash, mut sprite, time_scale) in &mut query {
+        let delta = scaled(timer.delta_seconds(), time_scale);
+        flash.timer.tick(Duration::from_secs_f32(delta));
+
+        if flash.timer.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            sprite.color.set_a(flash.alpha * flash.timer.percent_left());
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Goal-line flick: a last-ditch save. Up lunges the paddle a short way up
+//! the field and snaps it back, so a ball that's about to slip past the paddle
+//! line can still be met. Lunge a moment too late and the ball is already
+//! behind the paddle, too early and it's not there yet, and either way the
+//! flick is spent for a while.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena,
+    bindings::KeyBindings,
+    callout::spawn_callout,
+    engine::ScheduleSetup,
+    event_log::GameplayEvent,
+    paddle::{Player, Side},
+    AppState,
+};
+
+pub const FLICK_KEY: KeyCode = KeyCode::Up;
+
+const FLICK_REACH: f32 = 30.;
+// seconds out to full reach, then back to the line
+const FLICK_OUT: f32 = 0.06;
+const FLICK_BACK: f32 = 0.14;
+const FLICK_COOLDOWN: f32 = 1.5;
+
+pub struct FlickPlugin;
+
+impl Plugin for FlickPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(
+            AppState::Playing,
+            (flick_input, animate_flicks.after(flick_input), call_saves),
+        );
+    }
+}
+
+#[derive(Component, Default, Clone)]
+pub struct Flick {
+    /// Seconds into the current lunge.
+    elapsed: Option<f32>,
+    /// How far the lunge has the paddle off its line right now.
+    offset: f32,
+    cooldown: f32,
+}
+
+/// How far off the line the paddle is `elapsed` seconds into a lunge, or
+/// `None` once it's back.
+fn lunge_offset(elapsed: f32) -> Option<f32> {
+    if elapsed < FLICK_OUT {
+        Some(FLICK_REACH * elapsed / FLICK_OUT)
+    } else if elapsed < FLICK_OUT + FLICK_BACK {
+        Some(FLICK_REACH * (1. - (elapsed - FLICK_OUT) / FLICK_BACK))
+    } else {
+        None
+    }
+}
+
+fn flick_input(
+    mut query: Query<(&mut Flick, &Side)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    for (mut flick, side) in &mut query {
+        let pressed = keyboard_input.just_pressed(bindings.reserved(side.0, FLICK_KEY));
+        if pressed && flick.elapsed.is_none() && flick.cooldown <= 0. {
+            flick.elapsed = Some(0.);
+            flick.cooldown = FLICK_COOLDOWN;
+        }
+    }
+}
+
+// moves by the change in offset, so steering and other movement still apply
+fn animate_flicks(
+    mut query: Query<(&mut Transform, &mut Flick)>,
+    arena: Res<Arena>,
+    timer: Res<Time>,
+) {
+    let up = arena.edge(arena.goals[0]).normal().extend(0.);
+    let delta = timer.delta_seconds();
+
+    for (mut transform, mut flick) in &mut query {
+        flick.cooldown = (flick.cooldown - delta).max(0.);
+        let Some(elapsed) = flick.elapsed else {
+            continue;
+        };
+
+        let elapsed = elapsed + delta;
+        let offset = lunge_offset(elapsed);
+        flick.elapsed = offset.map(|_| elapsed);
+
+        let offset = offset.unwrap_or(0.);
+        transform.translation += up * (offset - flick.offset);
+        flick.offset = offset;
+    }
+}
+
+fn call_saves(
+    mut commands: Commands,
+    mut events: EventReader<GameplayEvent>,
+    query: Query<&Flick, With<Player>>,
+    asset_server: Res<AssetServer>,
+) {
+    let lunging = query.iter().any(|flick| flick.elapsed.is_some());
+    for event in events.iter() {
+        if let (true, GameplayEvent::PaddleHit { ball, .. }) = (lunging, event) {
+            spawn_callout(&mut commands, &asset_server, "SAVE!", *ball);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lunge_reaches_out_and_returns() {
+        assert_eq!(lunge_offset(0.), Some(0.));
+        assert_eq!(lunge_offset(FLICK_OUT), Some(FLICK_REACH));
+        assert!(
+            lunge_offset(FLICK_OUT + FLICK_BACK / 2.).map_or(false, |offset| offset < FLICK_REACH)
+        );
+        assert_eq!(lunge_offset(FLICK_OUT + FLICK_BACK), None);
+    }
+}
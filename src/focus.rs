@@ -0,0 +1,174 @@
+//! Menu focus, so every screen works from the keyboard or a gamepad alone.
+//! Each visible [`Focusable`] node takes part: the menu directions move focus
+//! to the nearest one that way, wrapping round to the far side at an edge,
+//! Confirm or Toggle activates it and Back asks the screen to close. One that
+//! `adjusts` keeps Left/Right for itself, to step a value, instead of moving.
+//! Screens hear about all of it as [`FocusEvent`]s.
+
+use bevy::prelude::*;
+
+use crate::prompt::{MenuAction, MenuInput};
+
+const FOCUS_COLOR: Color = Color::rgb(1., 0.85, 0.2);
+// how much being off to the side counts against a candidate, against being ahead
+const ACROSS_WEIGHT: f32 = 2.;
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Focus>()
+            .add_event::<FocusEvent>()
+            .add_system(navigate_focus)
+            .add_system(highlight_focus.after(navigate_focus));
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Focusable {
+    /// Takes Left/Right as [`FocusEvent::Adjusted`] rather than moving on.
+    pub adjusts: bool,
+}
+
+/// The focused node, if any screen has one.
+#[derive(Resource, Default)]
+pub struct Focus(pub Option<Entity>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FocusEvent {
+    Activated(Entity),
+    /// Left (-1) or Right (1) on a node that adjusts.
+    Adjusted(Entity, i32),
+    Back,
+}
+
+/// The candidate to move to from `from` in screen direction `dir` (y down),
+/// or, with none that way, the one furthest back the other way.
+fn next_focus(from: Vec2, dir: Vec2, candidates: &[(Entity, Vec2)]) -> Option<Entity> {
+    let score = |offset: Vec2| offset.dot(dir) + ACROSS_WEIGHT * offset.perp_dot(dir).abs();
+    let ahead = candidates
+        .iter()
+        .filter(|(_, position)| (*position - from).dot(dir) > 1.)
+        .min_by(|a, b| score(a.1 - from).total_cmp(&score(b.1 - from)));
+    let wrapped = || {
+        candidates
+            .iter()
+            .filter(|(_, position)| (*position - from).dot(dir) < -1.)
+            .min_by(|a, b| score(a.1 - from).total_cmp(&score(b.1 - from)))
+    };
+    ahead.or_else(wrapped).map(|(entity, _)| *entity)
+}
+
+pub fn navigate_focus(
+    mut focus: ResMut<Focus>,
+    mut events: EventWriter<FocusEvent>,
+    query: Query<(Entity, &Focusable, &GlobalTransform, &ComputedVisibility)>,
+    input: MenuInput,
+) {
+    let candidates: Vec<_> = query
+        .iter()
+        .filter(|(.., visibility)| visibility.is_visible())
+        .map(|(entity, _, transform, _)| (entity, transform.translation().truncate()))
+        .collect();
+
+    // a new screen, or the focused node went away: start at the top left
+    let current = focus.0.and_then(|entity| {
+        candidates
+            .iter()
+            .find(|(candidate, _)| *candidate == entity)
+    });
+    let Some(&(entity, position)) = current.or_else(|| {
+        candidates
+            .iter()
+            .min_by(|a, b| a.1.y.total_cmp(&b.1.y).then(a.1.x.total_cmp(&b.1.x)))
+    }) else {
+        focus.0 = None;
+        return;
+    };
+    if focus.0 != Some(entity) {
+        focus.0 = Some(entity);
+    }
+
+    if input.just_pressed(MenuAction::Back) {
+        events.send(FocusEvent::Back);
+    }
+    if input.just_pressed(MenuAction::Confirm) || input.just_pressed(MenuAction::Toggle) {
+        events.send(FocusEvent::Activated(entity));
+    }
+
+    let adjusts = query
+        .get(entity)
+        .map_or(false, |(_, focusable, ..)| focusable.adjusts);
+    for (action, dir) in [
+        (MenuAction::Up, Vec2::NEG_Y),
+        (MenuAction::Down, Vec2::Y),
+        (MenuAction::Left, Vec2::NEG_X),
+        (MenuAction::Right, Vec2::X),
+    ] {
+        if !input.just_pressed(action) {
+            continue;
+        }
+        if adjusts && dir.y == 0. {
+            events.send(FocusEvent::Adjusted(entity, dir.x as i32));
+        } else if let Some(next) = next_focus(position, dir, &candidates) {
+            focus.0 = Some(next);
+        }
+    }
+}
+
+// recolors focusable text, keeping whatever alpha a fade has it at
+fn highlight_focus(focus: Res<Focus>, mut query: Query<(Entity, &mut Text), With<Focusable>>) {
+    for (entity, mut text) in &mut query {
+        let color = if focus.0 == Some(entity) {
+            FOCUS_COLOR
+        } else {
+            Color::WHITE
+        };
+        for section in &mut text.sections {
+            let alpha = section.style.color.a();
+            section.style.color = color.with_a(alpha);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid() -> Vec<(Entity, Vec2)> {
+        // two rows of two, 100 apart
+        (0..4)
+            .map(|i| {
+                let position = Vec2::new((i % 2) as f32, (i / 2) as f32) * 100.;
+                (Entity::from_raw(i), position)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn moves_to_the_nearest_node_that_way() {
+        let grid = grid();
+        assert_eq!(
+            next_focus(Vec2::ZERO, Vec2::X, &grid),
+            Some(Entity::from_raw(1))
+        );
+        assert_eq!(
+            next_focus(Vec2::ZERO, Vec2::Y, &grid),
+            Some(Entity::from_raw(2))
+        );
+    }
+
+    #[test]
+    fn wraps_round_at_the_edge() {
+        let grid = grid();
+        assert_eq!(
+            next_focus(Vec2::new(0., 100.), Vec2::Y, &grid),
+            Some(Entity::from_raw(0))
+        );
+        assert_eq!(
+            next_focus(Vec2::new(100., 0.), Vec2::X, &grid),
+            Some(Entity::from_raw(0))
+        );
+        assert_eq!(next_focus(Vec2::ZERO, Vec2::X, &grid[..1]), None);
+    }
+}
@@ -0,0 +1,273 @@
+//! Font fallback, so text in other scripts comes out as letters instead of
+//! boxes. `assets/ui.fonts.ron` lists a chain of fonts for each locale,
+//! tried ahead of the default chain; any text section drawn in a font of the
+//! chain is switched to the first one that has every character it shows,
+//! whenever the text changes. The locale comes from `LANG` (or `LC_ALL`),
+//! and fonts listed but missing are left out of the chain.
+//!
+//! Only DejaVu ships with the game, which covers Latin, Greek and Cyrillic.
+//! For CJK the chains name the fonts Windows, macOS and the usual Linux
+//! packages install, by absolute path; the Linux Noto collection is read
+//! from its first face, the Japanese one.
+
+use std::path::Path;
+
+use ab_glyph::{Font as _, FontArc};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use crate::engine::{ScheduleSetup, Stage};
+
+pub const FONT_CHAINS_PATH: &str = "ui.fonts.ron";
+
+pub struct FontsPlugin;
+
+impl Plugin for FontsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<FontChains>()
+            .init_asset_loader::<FontChainsLoader>()
+            .init_asset_loader::<FontCollectionLoader>()
+            .init_resource::<Locale>()
+            .add_systems_at(Stage::Startup, load_font_chains)
+            .add_system(build_fallback)
+            .add_system(fall_back.after(build_fallback));
+    }
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "b37e4a1c-5d92-4f08-8c6e-2a9d1f70c4e3"]
+pub struct FontChains {
+    /// Tried after the locale's own chain, for every locale. Paths are
+    /// under `assets` unless absolute.
+    pub default: Vec<String>,
+    /// By language ("ja"), or language and region ("zh-TW") where the
+    /// region draws its characters differently.
+    pub locales: HashMap<String, Vec<String>>,
+}
+
+impl FontChains {
+    /// The font paths to try for `locale`, in order.
+    pub fn chain(&self, locale: &str) -> Vec<&str> {
+        let language = locale.split('-').next().unwrap_or(locale);
+        let own = self
+            .locales
+            .get(locale)
+            .or_else(|| self.locales.get(language));
+        let mut chain: Vec<&str> = Vec::new();
+        for path in own.into_iter().flatten().chain(&self.default) {
+            if !chain.contains(&path.as_str()) {
+                chain.push(path);
+            }
+        }
+        chain
+    }
+}
+
+/// The player's language, like "en" or "zh-TW".
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct Locale(pub String);
+
+impl Default for Locale {
+    fn default() -> Self {
+        let var = std::env::var("LC_ALL")
+            .ok()
+            .filter(|value| !value.is_empty())
+            .or_else(|| std::env::var("LANG").ok())
+            .unwrap_or_default();
+        Self(Locale::from_posix(&var))
+    }
+}
+
+impl Locale {
+    /// The locale of a POSIX setting like "zh_TW.UTF-8"; English for "C",
+    /// "POSIX" or nothing at all.
+    fn from_posix(var: &str) -> String {
+        let name = var.split(['.', '@']).next().unwrap_or_default();
+        if name.is_empty() || name == "C" || name == "POSIX" {
+            return "en".to_owned();
+        }
+        name.replace('_', "-")
+    }
+}
+
+/// The loaded chain for the current locale.
+#[derive(Resource, Default)]
+pub struct FontFallback {
+    chains: Handle<FontChains>,
+    pub fonts: Vec<Handle<Font>>,
+}
+
+impl FontFallback {
+    /// The first font of the chain with every character of `text`, if one
+    /// has loaded.
+    pub fn font_for(&self, text: &str, fonts: &Assets<Font>) -> Option<Handle<Font>> {
+        let loaded: Vec<_> = self
+            .fonts
+            .iter()
+            .filter_map(|handle| Some((handle, &fonts.get(handle)?.font)))
+            .collect();
+        let index = covering(text, loaded.iter().map(|(_, font)| *font))?;
+        Some(loaded[index].0.clone())
+    }
+}
+
+/// The index of the first of `fonts` with a glyph for every visible
+/// character of `text`.
+pub fn covering<'a>(text: &str, fonts: impl IntoIterator<Item = &'a FontArc>) -> Option<usize> {
+    fonts.into_iter().position(|font| {
+        text.chars()
+            .filter(|c| !c.is_whitespace() && !c.is_control())
+            .all(|c| font.glyph_id(c).0 != 0)
+    })
+}
+
+fn load_font_chains(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(FontFallback {
+        chains: asset_server.load(FONT_CHAINS_PATH),
+        fonts: Vec::new(),
+    });
+}
+
+// again if the locale changes, once the chains are in
+fn build_fallback(
+    mut fallback: ResMut<FontFallback>,
+    mut built: Local<Option<Locale>>,
+    locale: Res<Locale>,
+    chains: Res<Assets<FontChains>>,
+    asset_server: Res<AssetServer>,
+) {
+    if built.as_ref() == Some(&*locale) {
+        return;
+    }
+    let Some(chains) = chains.get(&fallback.chains) else {
+        return;
+    };
+    fallback.fonts = chains
+        .chain(&locale.0)
+        .into_iter()
+        .filter(|path| {
+            let found = asset_server.asset_io().is_file(Path::new(path));
+            if !found {
+                debug!("no {path}, leaving it out of the font chain");
+            }
+            found
+        })
+        .map(|path| asset_server.load(path))
+        .collect();
+    *built = Some(locale.clone());
+}
+
+fn fall_back(
+    mut query: Query<&mut Text>,
+    mut font_events: EventReader<AssetEvent<Font>>,
+    fallback: Res<FontFallback>,
+    fonts: Res<Assets<Font>>,
+) {
+    // a font arriving can change the answer for text that has sat still
+    let arrived = font_events
+        .iter()
+        .any(|event| matches!(event, AssetEvent::Created { .. }));
+    let rebuilt = fallback.is_changed();
+    for mut text in &mut query {
+        if !(text.is_changed() || arrived || rebuilt) {
+            continue;
+        }
+        for index in 0..text.sections.len() {
+            let section = &text.sections[index];
+            if !fallback.fonts.contains(&section.style.font) {
+                continue;
+            }
+            let Some(font) = fallback.font_for(&section.value, &fonts) else {
+                continue;
+            };
+            // only on a switch, or every text would look changed every frame
+            if section.style.font != font {
+                text.sections[index].style.font = font;
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct FontChainsLoader;
+
+impl AssetLoader for FontChainsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let chains: FontChains = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(chains));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["fonts.ron"]
+    }
+}
+
+/// System fonts often come as `.ttc` collections, which the engine's own
+/// font loader doesn't take.
+#[derive(Default)]
+struct FontCollectionLoader;
+
+impl AssetLoader for FontCollectionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let font = Font::try_from_bytes(bytes.to_vec())?;
+            load_context.set_default_asset(LoadedAsset::new(font));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ttc"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn chains_fall_back_by_script() {
+        let chains: FontChains = ron::from_str(include_str!("../assets/ui.fonts.ron")).unwrap();
+        let taiwan = chains.chain(&Locale::from_posix("zh_TW.UTF-8"));
+        assert_eq!(taiwan[0], chains.locales["zh-TW"][0]);
+        assert_eq!(chains.chain("en"), chains.chain(&Locale::from_posix("C")));
+        // absolute paths are the system's; "C:/" is only absolute on Windows
+        for path in chains
+            .default
+            .iter()
+            .chain(chains.locales.values().flatten())
+        {
+            if Path::new(path).is_relative() && !path.contains(':') {
+                assert!(
+                    Path::new("assets").join(path).is_file(),
+                    "{path} isn't shipped"
+                );
+            }
+        }
+
+        let dejavu = FontArc::try_from_vec(
+            fs::read("assets/fonts/DejaVuSans-Bold.ttf").expect("font asset"),
+        )
+        .unwrap();
+        assert_eq!(covering("Иван WINS", [&dejavu]), Some(0));
+        assert_eq!(covering("太郎", [&dejavu]), None);
+    }
+}
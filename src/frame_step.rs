@@ -0,0 +1,178 @@
+//! Frame stepping, built with the `dev` feature. The freeze key (F11 unless
+//! the bindings file says otherwise) freezes the game; while frozen the step
+//! key (F12) runs exactly one more frame and an overlay lists the collisions
+//! and goals resolved in it, with where each ball ended up. The freeze key
+//! again resumes.
+
+use bevy::{core::FrameCount, prelude::*};
+
+use crate::{
+    bindings::{FrameStepKeys, KeyBindings},
+    engine::{ScheduleSetup, Stage},
+    event_log::GameplayEvent,
+    hotkey::Hotkeys,
+    Ball, Speed,
+};
+
+pub struct FrameStepPlugin;
+
+impl Plugin for FrameStepPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameStep>()
+            .add_systems_at(Stage::Startup, spawn_overlay)
+            .add_system(step_keys)
+            .add_systems_at(Stage::PostUpdate, describe_step)
+            .add_systems_at(Stage::Last, advance);
+    }
+}
+
+#[derive(Resource, Default)]
+struct FrameStep {
+    frozen: bool,
+    /// Set by the step key, picked up at the end of the frame.
+    requested: bool,
+    /// The frame being stepped through is running.
+    running: bool,
+}
+
+#[derive(Component)]
+struct FrameStepOverlay;
+
+#[derive(Component)]
+struct FrameStepText;
+
+fn spawn_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.),
+                        top: Val::Px(40.),
+                        ..default()
+                    },
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            FrameStepOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 14.,
+                        color: Color::WHITE,
+                    },
+                ),
+                FrameStepText,
+            ));
+        });
+}
+
+fn step_keys(
+    mut step: ResMut<FrameStep>,
+    mut time: ResMut<Time>,
+    mut query_overlay: Query<&mut Visibility, With<FrameStepOverlay>>,
+    mut query_text: Query<&mut Text, With<FrameStepText>>,
+    hotkeys: Hotkeys,
+    bindings: Res<KeyBindings>,
+) {
+    let keys = bindings.frame_step;
+    if hotkeys.just_pressed(keys.freeze) {
+        step.frozen = !step.frozen;
+        step.requested = false;
+        if step.frozen {
+            time.pause();
+            for mut text in &mut query_text {
+                text.sections[0].value = heading(keys);
+            }
+        } else {
+            time.unpause();
+        }
+        for mut visibility in &mut query_overlay {
+            *visibility = if step.frozen {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+
+    if step.frozen && hotkeys.just_pressed(keys.step) {
+        step.requested = true;
+    }
+}
+
+fn heading(keys: FrameStepKeys) -> String {
+    format!("FROZEN ({:?} resume, {:?} step)", keys.freeze, keys.step)
+}
+
+// the unpaused frame's own systems ran in Update, so everything it sent is in by now
+fn describe_step(
+    mut query_text: Query<&mut Text, With<FrameStepText>>,
+    mut events: EventReader<GameplayEvent>,
+    query_ball: Query<(&Transform, &Speed), With<Ball>>,
+    step: Res<FrameStep>,
+    frame: Res<FrameCount>,
+    timer: Res<Time>,
+    bindings: Res<KeyBindings>,
+) {
+    if !step.running {
+        events.clear();
+        return;
+    }
+
+    let mut lines = vec![format!(
+        "{}\nframe {}, {:.1} ms",
+        heading(bindings.frame_step),
+        frame.0,
+        timer.delta_seconds() * 1000.,
+    )];
+    lines.extend(
+        events
+            .iter()
+            .filter(|event| {
+                matches!(
+                    event,
+                    GameplayEvent::WallHit { .. }
+                        | GameplayEvent::PaddleHit { .. }
+                        | GameplayEvent::Goal { .. }
+                )
+            })
+            .map(|event| format!("{event:?}")),
+    );
+    lines.extend(query_ball.iter().map(|(transform, speed)| {
+        format!(
+            "ball at ({:.1}, {:.1}) moving ({:.2}, {:.2}) x{:.0}",
+            transform.translation.x,
+            transform.translation.y,
+            speed.dir.x,
+            speed.dir.y,
+            speed.speed_multiplier,
+        )
+    }));
+
+    for mut text in &mut query_text {
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+// a requested step unpauses for the next frame and pauses again at its end
+fn advance(mut step: ResMut<FrameStep>, mut time: ResMut<Time>) {
+    if step.running {
+        step.running = false;
+        time.pause();
+    }
+    if step.requested {
+        step.requested = false;
+        step.running = true;
+        time.unpause();
+    }
+}
@@ -0,0 +1,197 @@
+//! The end of a game and of the match: the first side to
+//! [`Tunables::point_limit`] points wins the game, and the first to win
+//! more than half of [`Tunables::best_of`] games wins the match; until then
+//! each game won goes to the intermission. In survival the first goal ends
+//! it, and the time survived is the score; a player can also quit from the
+//! pause menu. The game-over screen puts the winner, the final score (and
+//! games) and the longest rally over the last frame, and offers the match
+//! again from the top, a scorecard to save for sharing, or quitting after
+//! the session's summary.
+
+use bevy::prelude::*;
+
+use crate::{
+    despawn_screen,
+    engine::ScheduleSetup,
+    focus::{FocusEvent, Focusable},
+    paddle::{Player, Side},
+    pause::MatchFlow,
+    scoreboard::{games_won, match_winner, score_line, side_names, winner},
+    scorecard::SaveScorecard,
+    session::EndSession,
+    share::MatchCode,
+    stats::MatchStats,
+    survival::{survival_time, Survival},
+    tournament::{record_result, Tournament},
+    AppState, GameState, Tunables,
+};
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, check_win)
+            .add_systems_on_enter(AppState::GameOver, spawn_game_over.after(record_result))
+            .add_systems_during(AppState::GameOver, choose_game_over_item)
+            .add_systems_on_exit(AppState::GameOver, despawn_screen::<GameOverScreen>);
+    }
+}
+
+#[derive(Component)]
+struct GameOverScreen;
+
+/// The game-over screen's entries, top to bottom.
+#[derive(Component, Clone, Copy)]
+enum GameOverItem {
+    PlayAgain,
+    SaveCard,
+    Quit,
+}
+
+impl GameOverItem {
+    const ALL: [GameOverItem; 3] = [
+        GameOverItem::PlayAgain,
+        GameOverItem::SaveCard,
+        GameOverItem::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            GameOverItem::PlayAgain => "PLAY AGAIN",
+            GameOverItem::SaveCard => "SAVE SCORECARD",
+            GameOverItem::Quit => "QUIT",
+        }
+    }
+}
+
+fn check_win(
+    mut game_state: ResMut<GameState>,
+    tunables: Res<Tunables>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if !game_state.is_changed() || winner(game_state.score, tunables.point_limit).is_none() {
+        return;
+    }
+    let score = game_state.score;
+    game_state.games.push(score);
+    if match_winner(&game_state.games, tunables.best_of).is_some() {
+        next_state.set(AppState::GameOver);
+    } else {
+        next_state.set(AppState::Intermission);
+    }
+}
+
+fn spawn_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query_player: Query<(&Player, &Side)>,
+    (game_state, stats, tunables): (Res<GameState>, Res<MatchStats>, Res<Tunables>),
+    (survival, tournament, code): (
+        Res<Survival>,
+        Option<Res<Tournament>>,
+        Option<Res<MatchCode>>,
+    ),
+) {
+    // no winner when the match was quit early
+    let title = match_winner(&game_state.games, tunables.best_of).map_or_else(
+        || "GAME OVER".to_owned(),
+        |side| format!("{} WINS", side_names(&query_player)[side].to_uppercase()),
+    );
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let text = |value: String, font_size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(8.)),
+            ..default()
+        })
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(title, 64., Color::WHITE));
+            // in survival the time is the score
+            let score = if tunables.survival {
+                format!("SURVIVED {}", survival_time(survival.elapsed))
+            } else {
+                score_line(game_state.score)
+            };
+            parent.spawn(text(score, 48., Color::WHITE));
+            if tunables.best_of > 1 {
+                let [first, far] = games_won(&game_state.games);
+                parent.spawn(text(format!("GAMES {first} : {far}"), 28., Color::WHITE));
+            }
+            parent.spawn(text(
+                format!("BEST RALLY {}", stats.longest_rally),
+                18.,
+                Color::GRAY,
+            ));
+            // a bracket's matches are set by the bracket, not a code
+            if let (Some(code), None) = (&code, &tournament) {
+                parent.spawn(text(
+                    format!("MATCH CODE {}", code.encode()),
+                    18.,
+                    Color::GRAY,
+                ));
+            }
+            for item in GameOverItem::ALL {
+                // a tournament plays on through its fixtures instead
+                let label = match (item, &tournament) {
+                    (GameOverItem::PlayAgain, Some(tournament)) => {
+                        if tournament.next_fixture().is_none() {
+                            continue;
+                        }
+                        "NEXT MATCH"
+                    }
+                    _ => item.label(),
+                };
+                parent.spawn((
+                    text(label.into(), 32., Color::WHITE),
+                    Focusable::default(),
+                    item,
+                ));
+            }
+        });
+}
+
+fn choose_game_over_item(
+    mut events: EventReader<FocusEvent>,
+    mut flow: MatchFlow,
+    mut scorecards: EventWriter<SaveScorecard>,
+    mut ends: EventWriter<EndSession>,
+    query: Query<&GameOverItem>,
+) {
+    for event in events.iter() {
+        let FocusEvent::Activated(entity) = *event else {
+            continue;
+        };
+        match query.get(entity) {
+            Ok(GameOverItem::PlayAgain) => flow.restart(),
+            Ok(GameOverItem::SaveCard) => scorecards.send(SaveScorecard),
+            Ok(GameOverItem::Quit) => ends.send(EndSession),
+            Err(_) => {}
+        }
+    }
+}
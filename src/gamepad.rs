@@ -0,0 +1,240 @@
+//! Gamepad steering. Each human side gets a seat for a pad: the first pad to
+//! press a button takes the first free seat, so in a two-player match the
+//! second pad pressed plays the far goal, and in single play whichever pad
+//! was pressed last has the paddle. A seated pad steers with its left stick,
+//! the paddle moving in proportion to how far it's pushed, or at full speed
+//! on the d-pad, along with the side's keys; with the side's controls
+//! mirrored it's the right stick and the bumpers instead (the face buttons
+//! already pause and confirm). A pad that disconnects gives its seat up. With [`Tunables::mouse`] on, the first side's paddle chases
+//! the mouse cursor instead, as far along its goal as the arena lets it, and
+//! a finger dragged on a touchscreen steers the same way whichever side's
+//! goal it's nearest.
+
+use bevy::{
+    ecs::system::SystemParam,
+    input::gamepad::GamepadConnectionEvent,
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{PrimaryWindow, WindowRef},
+};
+
+use crate::{
+    arena::Arena, bindings::KeyBindings, bot::Difficulty, paddle::Side, side_swap::SideSwap,
+    touch::on_pause_button, Tunables,
+};
+
+pub struct GamepadPlugin;
+
+impl Plugin for GamepadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PadSeats>().add_system(seat_pads);
+    }
+}
+
+/// The pad steering each side, by side index.
+#[derive(Resource, Default, Debug)]
+pub struct PadSeats(pub Vec<Option<Gamepad>>);
+
+impl PadSeats {
+    /// Seats `pad` after a press, unless it has a seat already: in the
+    /// first free one of `sides`, or in single play in place of the last.
+    fn claim(&mut self, pad: Gamepad, sides: usize) {
+        if self.0.contains(&Some(pad)) {
+            return;
+        }
+        self.0.resize(sides.max(1), None);
+        match self.0.iter_mut().find(|seat| seat.is_none()) {
+            Some(seat) => *seat = Some(pad),
+            None if sides <= 1 => self.0[0] = Some(pad),
+            None => {}
+        }
+    }
+
+    fn leave(&mut self, pad: Gamepad) {
+        for seat in &mut self.0 {
+            if *seat == Some(pad) {
+                *seat = None;
+            }
+        }
+    }
+}
+
+/// How many sides people play, counted from the first goal.
+fn human_sides(arena: &Arena, bot: bool) -> usize {
+    // the bot plays every goal after the first
+    if bot {
+        1
+    } else {
+        arena.goals.len()
+    }
+}
+
+fn seat_pads(
+    mut seats: ResMut<PadSeats>,
+    mut connections: EventReader<GamepadConnectionEvent>,
+    buttons: Res<Input<GamepadButton>>,
+    arena: Res<Arena>,
+    bot: Option<Res<Difficulty>>,
+) {
+    for event in connections.iter() {
+        if !event.connected() {
+            seats.leave(event.gamepad);
+        }
+    }
+    let sides = human_sides(&arena, bot.is_some());
+    for button in buttons.get_just_pressed() {
+        seats.claim(button.gamepad, sides);
+    }
+}
+
+/// Steering from the keyboard, each side's seated pad, the mouse and touch.
+#[derive(SystemParam)]
+pub struct Steering<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    bindings: Res<'w, KeyBindings>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    axes: Res<'w, Axis<GamepadAxis>>,
+    seats: Res<'w, PadSeats>,
+    arena: Res<'w, Arena>,
+    tunables: Res<'w, Tunables>,
+    touches: Res<'w, Touches>,
+    bot: Option<Res<'w, Difficulty>>,
+    swap: Res<'w, SideSwap>,
+    windows: Query<'w, 's, &'static Window, With<PrimaryWindow>>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+}
+
+impl Steering<'_, '_> {
+    /// How hard `side` is steering a paddle at `position`: toward a finger
+    /// or the cursor when one is steering it, at full tilt once it's more
+    /// than `reach` away, and otherwise as [`Steering::direction`] has it.
+    pub fn toward(&self, side: Side, position: Vec3, reach: f32) -> f32 {
+        match self.pointer(side) {
+            Some(pointer) => {
+                let offset = (pointer - position.truncate()).dot(self.arena.goal_axis(side.0));
+                (offset / reach).clamp(-1., 1.)
+            }
+            None => self.direction(side),
+        }
+    }
+
+    /// Where `side` is being steered to in the world: by a finger down
+    /// nearest its goal, or by the cursor if the first player is there and
+    /// follows the mouse.
+    fn pointer(&self, side: Side) -> Option<Vec2> {
+        let window = self.windows.get_single().ok()?;
+        let (camera, transform) = self.cameras.iter().find(|(camera, _)| {
+            matches!(camera.target, RenderTarget::Window(WindowRef::Primary))
+        })?;
+        // viewports count up from the bottom, touches down from the top
+        let to_world = |viewport: Vec2| {
+            camera
+                .viewport_to_world(transform, viewport)
+                .map(|ray| ray.origin.truncate())
+        };
+
+        let sides = human_sides(&self.arena, self.bot.is_some());
+        let mirrored = self.bindings.player(0).handedness.mirror_hud;
+        let touched = self
+            .touches
+            .iter()
+            .filter(|touch| !on_pause_button(window, touch.start_position(), mirrored))
+            .filter_map(|touch| {
+                to_world(Vec2::new(
+                    touch.position().x,
+                    window.height() - touch.position().y,
+                ))
+            })
+            .find(|&point| self.nearest_goal(point, sides) == side.0);
+        if touched.is_some() {
+            return touched;
+        }
+        if !self.tunables.mouse || self.swap.player(side) != 0 {
+            return None;
+        }
+        to_world(window.cursor_position()?)
+    }
+
+    /// Which of the first `sides` goals `point` is closest to.
+    fn nearest_goal(&self, point: Vec2, sides: usize) -> usize {
+        (0..sides)
+            .min_by(|&a, &b| {
+                let distance = |goal: usize| {
+                    self.arena
+                        .edge(self.arena.goals[goal])
+                        .signed_distance(point)
+                };
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap_or(0)
+    }
+
+    /// How hard `side` is steering, from -1 to 1 the way its keys go: left
+    /// to right, or down to up on an upright goal.
+    pub fn direction(&self, side: Side) -> f32 {
+        let [left, right] = self.bindings.steering(side, &self.arena);
+        let mut direction = 0.;
+        if self.keys.pressed(left) {
+            direction -= 1.;
+        }
+        if self.keys.pressed(right) {
+            direction += 1.;
+        }
+        let Some(pad) = self.seats.0.get(side.0).copied().flatten() else {
+            return direction;
+        };
+
+        let upright = self.arena.goal_axis(side.0) == Vec2::Y;
+        let mirrored = self.bindings.player(side.0).handedness.mirror_controls;
+        let (stick, [back, forward]) = pad_steering(upright, mirrored);
+        direction += self
+            .axes
+            .get(GamepadAxis::new(pad, stick))
+            .unwrap_or_default();
+        if self.buttons.pressed(GamepadButton::new(pad, back)) {
+            direction -= 1.;
+        }
+        if self.buttons.pressed(GamepadButton::new(pad, forward)) {
+            direction += 1.;
+        }
+        direction.clamp(-1., 1.)
+    }
+}
+
+/// The stick and the back and forward buttons a pad steers with, along an
+/// `upright` goal or a flat one, in the `mirrored` hand or the usual one.
+fn pad_steering(upright: bool, mirrored: bool) -> (GamepadAxisType, [GamepadButtonType; 2]) {
+    use GamepadButtonType::*;
+    match (upright, mirrored) {
+        (false, false) => (GamepadAxisType::LeftStickX, [DPadLeft, DPadRight]),
+        (true, false) => (GamepadAxisType::LeftStickY, [DPadDown, DPadUp]),
+        (false, true) => (GamepadAxisType::RightStickX, [LeftTrigger, RightTrigger]),
+        (true, true) => (GamepadAxisType::RightStickY, [LeftTrigger, RightTrigger]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pads_take_seats_in_turn() {
+        let mut seats = PadSeats::default();
+        seats.claim(Gamepad::new(3), 2);
+        seats.claim(Gamepad::new(3), 2);
+        seats.claim(Gamepad::new(1), 2);
+        // both seats taken
+        seats.claim(Gamepad::new(2), 2);
+        assert_eq!(seats.0, [Some(Gamepad::new(3)), Some(Gamepad::new(1))]);
+
+        seats.leave(Gamepad::new(3));
+        seats.claim(Gamepad::new(2), 2);
+        assert_eq!(seats.0, [Some(Gamepad::new(2)), Some(Gamepad::new(1))]);
+
+        // one player hands over to whichever pad they pick up
+        let mut single = PadSeats::default();
+        single.claim(Gamepad::new(0), 1);
+        single.claim(Gamepad::new(1), 1);
+        assert_eq!(single.0, [Some(Gamepad::new(1))]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Offscreen rendering of fixed game scenes for the golden-image tests in
+//! `tests/golden.rs`, built with the `golden` feature. The game runs headless
+//! with time paused, its camera draws into an image, and once the scene has
+//! settled the render app copies that image back to the CPU.
+//!
+//! UI is laid out against the primary window, and there isn't one here, so
+//! the HUD isn't part of the images.
+
+use std::{
+    num::NonZeroU32,
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{
+    app::AppExit,
+    log::LogPlugin,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        pipelined_rendering::PipelinedRenderingPlugin,
+        render_asset::RenderAssets,
+        render_resource::{
+            BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+            ImageDataLayout, MapMode, TextureDescriptor, TextureDimension, TextureFormat,
+            TextureUsages,
+        },
+        renderer::{RenderDevice, RenderQueue},
+        RenderApp, RenderSet,
+    },
+    sprite::Mesh2dHandle,
+    window::{ExitCondition, WindowPlugin},
+    winit::WinitPlugin,
+};
+
+use crate::{
+    archetype::ChosenArchetype, arena::Arena, paddle::Player, pickup::Pickup, AppState, Ball,
+    BallAssets, ControlMode, GamePlugin, Speed, Wall,
+};
+
+pub const GOLDEN_WIDTH: u32 = 640;
+pub const GOLDEN_HEIGHT: u32 = 640;
+
+// how long to wait for assets before giving up
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+// frames to let extraction and text layout catch up once everything is placed
+const SETTLE_FRAMES: usize = 3;
+
+#[derive(Clone, Copy, Debug)]
+pub enum GoldenScene {
+    /// The first ball waiting at the serve spot.
+    Serve,
+    /// Two balls in the field and the paddle off-center.
+    MidRally,
+}
+
+impl GoldenScene {
+    pub const ALL: [GoldenScene; 2] = [GoldenScene::Serve, GoldenScene::MidRally];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GoldenScene::Serve => "serve",
+            GoldenScene::MidRally => "mid_rally",
+        }
+    }
+}
+
+/// A rendered frame as tightly packed sRGB RGBA8 rows.
+pub struct Frame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Renders `scene` offscreen. Panics if there's no usable GPU adapter.
+pub fn render(scene: GoldenScene) -> Frame {
+    let (sender, receiver) = channel();
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>()
+            // keep the render app in step so each update draws that update's world
+            .disable::<PipelinedRenderingPlugin>(),
+    )
+    .add_plugin(GamePlugin {
+        arena: Arena::default(),
+        control_mode: ControlMode::default(),
+        layout: "scenes/square.scn.ron".to_owned(),
+        training: false,
+        streamer: None,
+        bot: None,
+        seed: Some(0),
+        tournament: None,
+        bindings: None,
+        code: None,
+        tick_rate: None,
+    })
+    .add_plugin(ExtractResourcePlugin::<CaptureTarget>::default())
+    .insert_resource(ChosenArchetype::default());
+    app.sub_app_mut(RenderApp)
+        .insert_resource(FrameSender(Mutex::new(sender)))
+        .add_system(copy_frame.in_set(RenderSet::Cleanup));
+
+    let image = app
+        .world
+        .resource_mut::<Assets<Image>>()
+        .add(target_image());
+    app.insert_resource(CaptureTarget {
+        image: image.clone(),
+        requested: false,
+    });
+    app.world.resource_mut::<Time>().pause();
+
+    app.update();
+    for mut camera in app.world.query::<&mut Camera>().iter_mut(&mut app.world) {
+        camera.target = RenderTarget::Image(image.clone());
+    }
+    // cameras only pick up a new target's size when that image changes
+    app.world.resource_mut::<Assets<Image>>().get_mut(&image);
+
+    // the serve needs the ball look from the layout scene
+    wait_for(&mut app, |world| world.contains_resource::<BallAssets>());
+    app.world
+        .insert_resource(NextState(Some(AppState::Playing)));
+    wait_for(&mut app, |world| {
+        let walls = world
+            .query_filtered::<(), (With<Wall>, With<Mesh2dHandle>)>()
+            .iter(world)
+            .count();
+        let pickups = world.query::<&Pickup>().iter(world).count();
+        let balls = world.query::<&Ball>().iter(world).count();
+        walls > 0 && pickups == Pickup::ALL.len() && balls > 0
+    });
+
+    arrange(&mut app.world, scene);
+    for _ in 0..SETTLE_FRAMES {
+        app.update();
+    }
+
+    app.world.resource_mut::<CaptureTarget>().requested = true;
+    app.update();
+    app.world.send_event(AppExit);
+    receiver_frame(&receiver)
+}
+
+fn wait_for(app: &mut App, ready: impl Fn(&mut World) -> bool) {
+    // assets load on other threads, so give them time rather than frames
+    let deadline = Instant::now() + LOAD_TIMEOUT;
+    while Instant::now() < deadline {
+        if ready(&mut app.world) {
+            return;
+        }
+        app.update();
+        thread::sleep(Duration::from_millis(5));
+    }
+    panic!("golden scene didn't finish loading");
+}
+
+fn arrange(world: &mut World, scene: GoldenScene) {
+    match scene {
+        GoldenScene::Serve => {}
+        GoldenScene::MidRally => {
+            for (mut transform, mut speed) in world
+                .query_filtered::<(&mut Transform, &mut Speed), With<Ball>>()
+                .iter_mut(world)
+            {
+                transform.translation = Vec3::new(120., 40., 0.);
+                speed.dir = Vec3::new(4., 7., 0.);
+            }
+            let assets = world.resource::<BallAssets>();
+            let bundle = crate::ball_bundle(
+                assets,
+                Vec3::new(-90., 170., 0.),
+                Speed {
+                    dir: Vec3::new(-5., -6., 0.),
+                    ..default()
+                },
+            );
+            world.spawn(bundle);
+            for mut transform in world
+                .query_filtered::<&mut Transform, With<Player>>()
+                .iter_mut(world)
+            {
+                transform.translation.x = -60.;
+            }
+        }
+    }
+}
+
+fn target_image() -> Image {
+    let size = Extent3d {
+        width: GOLDEN_WIDTH,
+        height: GOLDEN_HEIGHT,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("golden target"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+#[derive(Resource, Clone, ExtractResource)]
+struct CaptureTarget {
+    image: Handle<Image>,
+    requested: bool,
+}
+
+#[derive(Resource)]
+struct FrameSender(Mutex<Sender<Frame>>);
+
+fn receiver_frame(receiver: &Receiver<Frame>) -> Frame {
+    receiver
+        .try_recv()
+        .expect("render app didn't hand back a frame")
+}
+
+// runs after the frame's render graph has been submitted
+fn copy_frame(
+    target: Res<CaptureTarget>,
+    images: Res<RenderAssets<Image>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    sender: Res<FrameSender>,
+) {
+    if !target.requested {
+        return;
+    }
+    let Some(gpu_image) = images.get(&target.image) else {
+        return;
+    };
+
+    let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+    let row_bytes = width as usize * 4;
+    let padded_row_bytes = RenderDevice::align_copy_bytes_per_row(row_bytes);
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("golden readback"),
+        size: (padded_row_bytes * height as usize) as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        gpu_image.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_row_bytes as u32),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+    device.map_buffer(&slice, MapMode::Read, |result| {
+        result.expect("couldn't map the golden readback buffer");
+    });
+    device.poll(wgpu::Maintain::Wait);
+
+    let rgba = slice
+        .get_mapped_range()
+        .chunks(padded_row_bytes)
+        .flat_map(|row| &row[..row_bytes])
+        .copied()
+        .collect();
+    buffer.unmap();
+
+    let _ = sender.0.lock().unwrap().send(Frame {
+        width,
+        height,
+        rgba,
+    });
+}
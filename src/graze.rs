@@ -0,0 +1,112 @@
+//! The graze assist, an option for newer players: when a ball is about to
+//! get past a player's paddle and slowing it would give them time to get
+//! there, the balls drop into slow motion, the same slowdown as the time-slow
+//! special, until that ball reaches the paddle. It only steps in when full
+//! speed would miss and the slowdown wouldn't, and then not again for
+//! [`GRAZE_COOLDOWN`] seconds of play, so it can't be leaned on. Tournament
+//! matches are played without it.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena,
+    engine::ScheduleSetup,
+    paddle::{PaddleStats, Side, Steered, PADDLE_SPEED},
+    pause::starting_match,
+    serve::MatchPhase,
+    special::{SlowMotion, TIME_SLOW_SCALE},
+    tournament::Tournament,
+    AppState, Ball, Speed, Tunables,
+};
+
+pub const GRAZE_COOLDOWN: f32 = 10.;
+// how close to the paddle's line a ball has to be, in pixels
+const GRAZE_DISTANCE: f32 = 80.;
+
+pub struct GrazePlugin;
+
+impl Plugin for GrazePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GrazeAssist>()
+            .add_systems_on_enter(AppState::Playing, reset_assist.run_if(starting_match))
+            .add_systems_during(
+                AppState::Playing,
+                assist_grazes.run_if(in_state(MatchPhase::Rally)),
+            );
+    }
+}
+
+/// Seconds until the assist can step in again.
+#[derive(Resource, Default)]
+struct GrazeAssist {
+    cooldown: f32,
+}
+
+/// Whether a paddle `gap` pixels short of the ball, moving at `speed` pixels
+/// a second, misses it with `time` seconds left but makes it when the ball is
+/// slowed to `scale`.
+fn rescuable(gap: f32, time: f32, speed: f32, scale: f32) -> bool {
+    gap > speed * time && gap <= speed * time / scale
+}
+
+fn reset_assist(mut assist: ResMut<GrazeAssist>) {
+    assist.cooldown = 0.;
+}
+
+fn assist_grazes(
+    mut assist: ResMut<GrazeAssist>,
+    mut slow_motion: ResMut<SlowMotion>,
+    query_ball: Query<(&Transform, &Speed), With<Ball>>,
+    query_paddle: Query<(&Transform, &PaddleStats, &Side), With<Steered>>,
+    (arena, tunables, timer, tournament): (
+        Res<Arena>,
+        Res<Tunables>,
+        Res<Time>,
+        Option<Res<Tournament>>,
+    ),
+) {
+    assist.cooldown = (assist.cooldown - timer.delta_seconds()).max(0.);
+    if !tunables.graze_assist || tournament.is_some() || assist.cooldown > 0. {
+        return;
+    }
+    let ball_radius = tunables.ball_size().y / 2.;
+
+    for (paddle, stats, side) in &query_paddle {
+        let goal = arena.edge(arena.goals[side.0]);
+        let axis = arena.goal_axis(side.0);
+        let line = goal.signed_distance(paddle.translation.truncate()) + stats.size.y / 2.;
+        for (ball, speed) in &query_ball {
+            let velocity = speed.dir.truncate() * speed.speed_multiplier;
+            let approach = -velocity.dot(goal.normal());
+            let distance = goal.signed_distance(ball.translation.truncate()) - ball_radius - line;
+            if approach <= 0. || !(0. ..GRAZE_DISTANCE).contains(&distance) {
+                continue;
+            }
+            let time = distance / approach;
+            let crossing = ball.translation.truncate() + velocity * time;
+            let gap = (crossing - paddle.translation.truncate()).dot(axis).abs()
+                - stats.size.x / 2.
+                - ball_radius;
+            if rescuable(gap, time, PADDLE_SPEED * stats.speed, TIME_SLOW_SCALE) {
+                slow_motion.slow_for(time / TIME_SLOW_SCALE);
+                assist.cooldown = GRAZE_COOLDOWN;
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_in_only_when_it_makes_the_difference() {
+        // 100 px to cover at 600 px/s
+        assert!(!rescuable(100., 0.2, 600., 0.4));
+        assert!(rescuable(100., 0.1, 600., 0.4));
+        assert!(!rescuable(100., 0.05, 600., 0.4));
+        // already there
+        assert!(!rescuable(-5., 0.1, 600., 0.4));
+    }
+}
@@ -0,0 +1,114 @@
+//! Handedness, for left-handed players and one-handed pads. Each player's
+//! bindings carry a [`Handedness`]: mirrored controls trade every key they
+//! play with for its twin on the other side of the keyboard (the arrows for
+//! WASD, right Shift and Control for left) and steer a pad with its right
+//! stick and face buttons instead of the left stick and d-pad. The first
+//! player can mirror the match HUD as well, left corners for right.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{bindings::KeyBindings, engine::ScheduleSetup, AppState};
+
+// each key and its twin across the keyboard
+const KEY_TWINS: [(KeyCode, KeyCode); 7] = [
+    (KeyCode::Left, KeyCode::A),
+    (KeyCode::Right, KeyCode::D),
+    (KeyCode::Up, KeyCode::W),
+    (KeyCode::Down, KeyCode::S),
+    (KeyCode::RShift, KeyCode::LShift),
+    (KeyCode::RControl, KeyCode::LControl),
+    (KeyCode::RAlt, KeyCode::LAlt),
+];
+
+pub struct HandednessPlugin;
+
+impl Plugin for HandednessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, mirror_hud);
+    }
+}
+
+/// How one player holds the controls.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Handedness {
+    #[serde(default)]
+    pub mirror_controls: bool,
+    /// Only the first player's counts, the HUD being theirs.
+    #[serde(default)]
+    pub mirror_hud: bool,
+}
+
+impl Handedness {
+    /// The key this player presses for `key`.
+    pub fn key(self, key: KeyCode) -> KeyCode {
+        if self.mirror_controls {
+            mirror_key(key)
+        } else {
+            key
+        }
+    }
+}
+
+/// `key`'s twin on the other side of the keyboard, or `key` itself if it
+/// hasn't one.
+pub fn mirror_key(key: KeyCode) -> KeyCode {
+    KEY_TWINS
+        .iter()
+        .find_map(|&(right, left)| {
+            if key == right {
+                Some(left)
+            } else if key == left {
+                Some(right)
+            } else {
+                None
+            }
+        })
+        .unwrap_or(key)
+}
+
+/// Marks a HUD root swapped left for right.
+#[derive(Component)]
+struct MirroredHud;
+
+// swaps each top-level node's left and right offsets, and back when the
+// option's turned off
+fn mirror_hud(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Style, Option<&MirroredHud>), (With<Node>, Without<Parent>)>,
+    bindings: Res<KeyBindings>,
+) {
+    let mirror = bindings.player(0).handedness.mirror_hud;
+    for (entity, mut style, mirrored) in &mut query {
+        if mirrored.is_some() == mirror {
+            continue;
+        }
+        let position = &mut style.position;
+        std::mem::swap(&mut position.left, &mut position.right);
+        if mirror {
+            commands.entity(entity).insert(MirroredHud);
+        } else {
+            commands.entity(entity).remove::<MirroredHud>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_trade_with_their_twins_both_ways() {
+        assert_eq!(mirror_key(KeyCode::Left), KeyCode::A);
+        assert_eq!(mirror_key(KeyCode::A), KeyCode::Left);
+        assert_eq!(mirror_key(KeyCode::RShift), KeyCode::LShift);
+        assert_eq!(mirror_key(KeyCode::Return), KeyCode::Return);
+
+        let mirrored = Handedness {
+            mirror_controls: true,
+            ..default()
+        };
+        assert_eq!(mirrored.key(KeyCode::Up), KeyCode::W);
+        assert_eq!(Handedness::default().key(KeyCode::Up), KeyCode::Up);
+    }
+}
@@ -0,0 +1,118 @@
+//! Heatmap overlay. F10 lays the stats sampler's [`Heatmap`] over the arena:
+//! warm colors where the ball spent the most time, cyan where it met the
+//! paddle, so gaps in coverage stand out.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::{
+    engine::{ScheduleSetup, Stage},
+    hotkey::Hotkeys,
+    stats::{MatchStats, HEATMAP_CELLS, HEATMAP_EXTENT},
+};
+
+pub const HEATMAP_KEY: KeyCode = KeyCode::F10;
+
+// above the field, below the fog and callouts
+const HEATMAP_Z: f32 = 4.;
+const CONTACT_COLOR: [u8; 3] = [0, 255, 255];
+
+pub struct HeatmapPlugin;
+
+impl Plugin for HeatmapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_at(Stage::Startup, spawn_overlay)
+            .add_system(toggle_overlay)
+            .add_system(paint_overlay.after(toggle_overlay));
+    }
+}
+
+#[derive(Component)]
+struct HeatmapOverlay;
+
+fn spawn_overlay(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let image = Image::new_fill(
+        Extent3d {
+            width: HEATMAP_CELLS as u32,
+            height: HEATMAP_CELLS as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::splat(2. * HEATMAP_EXTENT)),
+                ..default()
+            },
+            texture: images.add(image),
+            transform: Transform::from_xyz(0., 0., HEATMAP_Z),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        HeatmapOverlay,
+    ));
+}
+
+fn toggle_overlay(mut query: Query<&mut Visibility, With<HeatmapOverlay>>, hotkeys: Hotkeys) {
+    if !hotkeys.just_pressed(HEATMAP_KEY) {
+        return;
+    }
+
+    for mut visibility in &mut query {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// The overlay color of one cell, given how it compares with the busiest cell.
+fn cell_color(balls: u32, most_balls: u32, contacts: u32) -> [u8; 4] {
+    if contacts > 0 {
+        let [r, g, b] = CONTACT_COLOR;
+        return [r, g, b, 220];
+    }
+    if balls == 0 || most_balls == 0 {
+        return [0, 0, 0, 0];
+    }
+
+    // red through yellow as it heats up, square-rooted so sparse cells still show
+    let heat = (balls as f32 / most_balls as f32).sqrt();
+    [255, (heat * 255.) as u8, 0, (60. + heat * 160.) as u8]
+}
+
+fn paint_overlay(
+    mut images: ResMut<Assets<Image>>,
+    query: Query<(&Handle<Image>, &Visibility), With<HeatmapOverlay>>,
+    stats: Res<MatchStats>,
+) {
+    for (handle, visibility) in &query {
+        if *visibility == Visibility::Hidden || !stats.is_changed() {
+            continue;
+        }
+        let Some(image) = images.get_mut(handle) else {
+            continue;
+        };
+
+        let heatmap = &stats.heatmap;
+        let most_balls = heatmap.balls.iter().copied().max().unwrap_or(0);
+        // image rows run top to bottom, heatmap rows bottom to top
+        for (row, pixels) in image.data.chunks_mut(HEATMAP_CELLS * 4).enumerate() {
+            let heat_row = HEATMAP_CELLS - 1 - row;
+            for (column, pixel) in pixels.chunks_mut(4).enumerate() {
+                let cell = heat_row * HEATMAP_CELLS + column;
+                pixel.copy_from_slice(&cell_color(
+                    heatmap.balls[cell],
+                    most_balls,
+                    heatmap.contacts[cell],
+                ));
+            }
+        }
+    }
+}
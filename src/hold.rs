@@ -0,0 +1,215 @@
+//! Hold-to-confirm buttons for destructive actions. The key (or gamepad
+//! button) has to be held while a ring of dots fills; letting go early empties
+//! it again. A completed hold sends [`HoldConfirmed`] with the widget's entity,
+//! once per press.
+
+use std::f32::consts::TAU;
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+const RING_DOTS: usize = 16;
+const RING_RADIUS: f32 = 10.;
+const DOT_SIZE: f32 = 4.;
+const DOT_OFF: Color = Color::rgba(1., 1., 1., 0.2);
+const DOT_ON: Color = Color::ORANGE_RED;
+
+pub struct HoldPlugin;
+
+impl Plugin for HoldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<HoldConfirmed>()
+            .add_system(hold_to_confirm)
+            .add_system(fill_rings.after(hold_to_confirm));
+    }
+}
+
+/// Sent with the widget's entity when its hold completes.
+pub struct HoldConfirmed(pub Entity);
+
+#[derive(Component)]
+pub struct HoldToConfirm {
+    key: KeyCode,
+    button: GamepadButtonType,
+    /// Seconds the input has to be held.
+    duration: f32,
+    held: f32,
+    /// Cleared after a confirm until the input is let go.
+    armed: bool,
+}
+
+impl HoldToConfirm {
+    fn progress(&self) -> f32 {
+        (self.held / self.duration).min(1.)
+    }
+
+    /// Advances the hold; returns whether it completed this frame.
+    fn update(&mut self, pressed: bool, delta: f32) -> bool {
+        if !pressed {
+            self.held = 0.;
+            self.armed = true;
+            return false;
+        }
+        if !self.armed {
+            return false;
+        }
+
+        self.held += delta;
+        if self.held < self.duration {
+            return false;
+        }
+        self.held = 0.;
+        self.armed = false;
+        true
+    }
+}
+
+#[derive(Component)]
+struct RingDot {
+    widget: Entity,
+    index: usize,
+}
+
+/// Adds a hold-to-confirm row (ring and label) under `parent`. The returned
+/// entity is the one [`HoldConfirmed`] carries, for the caller to tag.
+pub fn spawn_hold_button<'w, 's, 'a>(
+    parent: &'a mut ChildBuilder<'w, 's, '_>,
+    asset_server: &AssetServer,
+    label: &str,
+    (key, button): (KeyCode, GamepadButtonType),
+    duration: f32,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut widget = parent.spawn((
+        NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                margin: UiRect::top(Val::Px(6.)),
+                ..default()
+            },
+            ..default()
+        },
+        HoldToConfirm {
+            key,
+            button,
+            duration,
+            held: 0.,
+            armed: true,
+        },
+    ));
+    let entity = widget.id();
+
+    widget.with_children(|row| {
+        row.spawn(NodeBundle {
+            style: Style {
+                size: Size::all(Val::Px(2. * RING_RADIUS + DOT_SIZE)),
+                margin: UiRect::right(Val::Px(6.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|ring| {
+            for index in 0..RING_DOTS {
+                // clockwise from the top
+                let angle = TAU * index as f32 / RING_DOTS as f32;
+                let offset = RING_RADIUS * Vec2::new(angle.sin(), -angle.cos());
+                ring.spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Px(RING_RADIUS + offset.x),
+                                top: Val::Px(RING_RADIUS + offset.y),
+                                ..default()
+                            },
+                            size: Size::all(Val::Px(DOT_SIZE)),
+                            ..default()
+                        },
+                        background_color: DOT_OFF.into(),
+                        ..default()
+                    },
+                    RingDot {
+                        widget: entity,
+                        index,
+                    },
+                ));
+            }
+        });
+
+        row.spawn(TextBundle::from_section(
+            label,
+            TextStyle {
+                font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                font_size: 14.,
+                color: Color::WHITE,
+            },
+        ));
+    });
+
+    widget
+}
+
+fn hold_to_confirm(
+    mut query: Query<(Entity, &mut HoldToConfirm, &ComputedVisibility)>,
+    mut confirmed: EventWriter<HoldConfirmed>,
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    timer: Res<Time>,
+) {
+    for (entity, mut hold, visibility) in &mut query {
+        // hidden widgets can't be confirmed
+        let pressed = visibility.is_visible()
+            && (keyboard_input.pressed(hold.key)
+                || gamepads.iter().any(|gamepad| {
+                    gamepad_input.pressed(GamepadButton::new(gamepad, hold.button))
+                }));
+        // real time, so holds still fill while the game is paused
+        if hold.update(pressed, timer.raw_delta_seconds()) {
+            confirmed.send(HoldConfirmed(entity));
+        }
+    }
+}
+
+fn fill_rings(
+    mut query_dots: Query<(&mut BackgroundColor, &RingDot)>,
+    query_holds: Query<&HoldToConfirm, Changed<HoldToConfirm>>,
+) {
+    for (mut color, dot) in &mut query_dots {
+        let Ok(hold) = query_holds.get(dot.widget) else {
+            continue;
+        };
+        let lit = (hold.progress() * RING_DOTS as f32) as usize;
+        *color = if dot.index < lit { DOT_ON } else { DOT_OFF }.into();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hold() -> HoldToConfirm {
+        HoldToConfirm {
+            key: KeyCode::Back,
+            button: GamepadButtonType::North,
+            duration: 1.,
+            held: 0.,
+            armed: true,
+        }
+    }
+
+    #[test]
+    fn letting_go_early_cancels() {
+        let mut hold = hold();
+        assert!(!hold.update(true, 0.8));
+        assert!(!hold.update(false, 0.1));
+        assert!(!hold.update(true, 0.8));
+    }
+
+    #[test]
+    fn confirms_once_per_press() {
+        let mut hold = hold();
+        assert!(hold.update(true, 1.));
+        assert!(!hold.update(true, 1.));
+        assert!(!hold.update(false, 0.));
+        assert!(hold.update(true, 1.));
+    }
+}
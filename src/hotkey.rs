@@ -0,0 +1,54 @@
+//! Tool hotkeys on the function row (latency probe, save-states, event log
+//! dump, spectator view, inspector, heatmap, mini mode). Normally a plain
+//! press fires them; with the guard on, as in streamer mode, they also need
+//! Ctrl and Shift held so a stray reach for the function row mid-match
+//! doesn't change what's on stream.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+#[derive(Resource, Default)]
+pub struct HotkeyGuard {
+    pub chorded: bool,
+}
+
+impl HotkeyGuard {
+    pub fn just_pressed(&self, keys: &Input<KeyCode>, key: KeyCode) -> bool {
+        keys.just_pressed(key)
+            && (!self.chorded
+                || keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+                    && keys.any_pressed([KeyCode::LShift, KeyCode::RShift]))
+    }
+}
+
+/// Keyboard input as seen through the [`HotkeyGuard`].
+#[derive(SystemParam)]
+pub struct Hotkeys<'w> {
+    keys: Res<'w, Input<KeyCode>>,
+    guard: Res<'w, HotkeyGuard>,
+}
+
+impl Hotkeys<'_> {
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        self.guard.just_pressed(&self.keys, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chorded_hotkeys_need_ctrl_and_shift() {
+        let guard = HotkeyGuard { chorded: true };
+        let mut keys = Input::<KeyCode>::default();
+        keys.press(KeyCode::F7);
+        assert!(!guard.just_pressed(&keys, KeyCode::F7));
+
+        keys.press(KeyCode::LControl);
+        assert!(!guard.just_pressed(&keys, KeyCode::F7));
+
+        keys.press(KeyCode::RShift);
+        assert!(guard.just_pressed(&keys, KeyCode::F7));
+        assert!(HotkeyGuard::default().just_pressed(&keys, KeyCode::F7));
+    }
+}
@@ -0,0 +1,162 @@
+//! Auto-pause for an absent player. With no keyboard, mouse or gamepad input
+//! for [`Tunables::idle_timeout`] seconds of play, the game pauses behind a
+//! dimmed "press any key" screen instead of letting the ball rack up goals,
+//! and the next press resumes it. Mini mode is meant to be left alone, so it
+//! never idles.
+
+use bevy::{input::mouse::MouseMotion, prelude::*};
+
+use crate::{
+    engine::{ScheduleSetup, Stage},
+    mini::MiniMode,
+    prompt::{any_input_prompt, LastDevice},
+    AppState, Tunables,
+};
+
+pub struct IdlePlugin;
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Idle>()
+            .add_systems_at(Stage::Startup, spawn_idle_screen)
+            .add_systems_during(AppState::Playing, watch_idle);
+    }
+}
+
+#[derive(Resource, Default)]
+struct Idle {
+    /// Seconds of play since the last input.
+    quiet: f32,
+    paused: bool,
+}
+
+impl Idle {
+    /// Advances the idle clock; returns whether the game should now be paused.
+    fn tick(&mut self, active: bool, delta: f32, timeout: f32) -> bool {
+        if active || timeout <= 0. {
+            self.quiet = 0.;
+        } else {
+            self.quiet += delta;
+        }
+        self.quiet >= timeout && timeout > 0.
+    }
+}
+
+#[derive(Component)]
+struct IdleScreen;
+
+#[derive(Component)]
+struct IdlePrompt;
+
+fn spawn_idle_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            IdleScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 40.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_text_alignment(TextAlignment::Center),
+                IdlePrompt,
+            ));
+        });
+}
+
+fn watch_idle(
+    mut idle: ResMut<Idle>,
+    mut time: ResMut<Time>,
+    mut query: Query<&mut Visibility, With<IdleScreen>>,
+    mut query_prompt: Query<&mut Text, With<IdlePrompt>>,
+    mut motion: EventReader<MouseMotion>,
+    (keyboard_input, mouse_input, gamepad_input): (
+        Res<Input<KeyCode>>,
+        Res<Input<MouseButton>>,
+        Res<Input<GamepadButton>>,
+    ),
+    (tunables, device, mini): (Res<Tunables>, Res<LastDevice>, Res<MiniMode>),
+) {
+    let moved = motion.iter().count() > 0;
+    let pressed = keyboard_input.get_just_pressed().len() > 0
+        || mouse_input.get_just_pressed().len() > 0
+        || gamepad_input.get_just_pressed().len() > 0;
+    // a held direction is the player still playing
+    let held = keyboard_input.get_pressed().len() > 0 || gamepad_input.get_pressed().len() > 0;
+
+    let pause = if idle.paused {
+        !pressed
+    } else {
+        // game time, so a stretch in the paused inspector doesn't count as idling
+        idle.tick(
+            pressed || held || moved || mini.active(),
+            time.delta_seconds(),
+            tunables.idle_timeout,
+        )
+    };
+    if pause == idle.paused {
+        return;
+    }
+
+    idle.paused = pause;
+    idle.quiet = 0.;
+    if pause {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+    for mut text in &mut query_prompt {
+        text.sections[0].value = format!("PAUSED\n{}", any_input_prompt(*device));
+    }
+    for mut visibility in &mut query {
+        *visibility = if pause {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pauses_after_the_timeout_without_input() {
+        let mut idle = Idle::default();
+        assert!(!idle.tick(false, 20., 30.));
+        assert!(idle.tick(false, 10., 30.));
+    }
+
+    #[test]
+    fn input_restarts_the_clock() {
+        let mut idle = Idle::default();
+        idle.tick(false, 25., 30.);
+        idle.tick(true, 1., 30.);
+        assert!(!idle.tick(false, 25., 30.));
+    }
+
+    #[test]
+    fn zero_timeout_never_pauses() {
+        let mut idle = Idle::default();
+        assert!(!idle.tick(false, 1000., 0.));
+    }
+}
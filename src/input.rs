@@ -0,0 +1,89 @@
+//! Buffered button presses. A press made a few frames before it's valid
+//! (a smash tap just ahead of contact, a special just before the meter fills)
+//! is remembered for a short while and spent when the moment arrives.
+
+use bevy::{input::InputSystem, prelude::*, utils::HashMap};
+
+use crate::{
+    bindings::KeyBindings,
+    engine::{ScheduleSetup, Stage},
+    paddle::Side,
+    smash::SMASH_KEY,
+    special::SPECIAL_KEY,
+    AppState,
+};
+
+// presses older than this are forgotten whatever the consumer's window
+const MAX_BUFFER_AGE: f32 = 0.5;
+
+pub struct InputPlugin;
+
+impl Plugin for InputPlugin {
+    fn build(&self, app: &mut App) {
+        // ahead of the physics ticks, whose returns spend the presses
+        app.add_systems_at(
+            Stage::PreUpdate,
+            buffer_input
+                .in_set(InputSet)
+                .after(InputSystem)
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// Runs before anything that consumes buffered presses, the physics ticks
+/// included.
+#[derive(SystemSet, Debug, Hash, PartialEq, Eq, Clone)]
+pub struct InputSet;
+
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Action {
+    Smash,
+    Special,
+}
+
+impl Action {
+    const ALL: [Action; 2] = [Action::Smash, Action::Special];
+
+    fn key(self) -> KeyCode {
+        match self {
+            Action::Smash => SMASH_KEY,
+            Action::Special => SPECIAL_KEY,
+        }
+    }
+}
+
+#[derive(Component, Default, Clone)]
+pub struct InputBuffer {
+    /// Seconds since each action's last unspent press.
+    presses: HashMap<Action, f32>,
+}
+
+impl InputBuffer {
+    /// Spends a buffered press of `action` if it was made within `window` seconds.
+    pub fn take(&mut self, action: Action, window: f32) -> bool {
+        self.presses
+            .remove(&action)
+            .map_or(false, |age| age <= window)
+    }
+}
+
+fn buffer_input(
+    mut query: Query<(&mut InputBuffer, &Side)>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    timer: Res<Time>,
+) {
+    for (mut buffer, side) in &mut query {
+        buffer.presses.retain(|_, age| {
+            *age += timer.delta_seconds();
+            *age <= MAX_BUFFER_AGE
+        });
+
+        for action in Action::ALL {
+            if keyboard_input.just_pressed(bindings.reserved(side.0, action.key())) {
+                buffer.presses.insert(action, 0.);
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! Entity inspector, built with the `dev` feature. F9 pauses the game and
+//! opens a world view where live components and resources (`Speed`,
+//! `Transform`, `GameState` and the rest) can be read and edited; F9 again
+//! closes it and resumes.
+
+use bevy::prelude::*;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+
+use crate::{hotkey::Hotkeys, GameState, Speed};
+
+pub const INSPECTOR_KEY: KeyCode = KeyCode::F9;
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Speed>()
+            .register_type::<GameState>()
+            .init_resource::<Inspecting>()
+            .add_system(toggle_inspector)
+            .add_plugin(
+                WorldInspectorPlugin::new().run_if(|inspecting: Res<Inspecting>| inspecting.0),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct Inspecting(bool);
+
+fn toggle_inspector(mut inspecting: ResMut<Inspecting>, mut time: ResMut<Time>, hotkeys: Hotkeys) {
+    if !hotkeys.just_pressed(INSPECTOR_KEY) {
+        return;
+    }
+
+    inspecting.0 = !inspecting.0;
+    if inspecting.0 {
+        time.pause();
+    } else {
+        time.unpause();
+    }
+}
@@ -0,0 +1,138 @@
+//! The break between the games of a best-of match: who took the game just
+//! finished, the games each side has won, and every finished game's score,
+//! over the frozen arena. The next game starts from 0 : 0 on Confirm or
+//! after [`INTERMISSION_SECONDS`], served by the side that let in the
+//! last goal.
+
+use bevy::prelude::*;
+
+use crate::{
+    despawn_screen,
+    engine::ScheduleSetup,
+    paddle::{Player, Side},
+    pause::MatchFlow,
+    prompt::{MenuAction, MenuInput},
+    scoreboard::{games_won, points, score_line, side_names},
+    AppState, GameState,
+};
+
+pub const INTERMISSION_SECONDS: f32 = 5.;
+
+pub struct IntermissionPlugin;
+
+impl Plugin for IntermissionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Intermission, spawn_intermission)
+            .add_systems_during(AppState::Intermission, next_game)
+            .add_systems_on_exit(AppState::Intermission, despawn_screen::<IntermissionScreen>);
+    }
+}
+
+#[derive(Component)]
+struct IntermissionScreen;
+
+/// Seconds until the next game starts by itself.
+#[derive(Component)]
+struct NextGameCountdown(Timer);
+
+fn countdown_line(remaining: f32, input: &MenuInput) -> String {
+    format!(
+        "NEXT GAME IN {}   {} TO PLAY ON",
+        remaining.ceil().max(1.) as u32,
+        input.glyph(MenuAction::Confirm).to_uppercase()
+    )
+}
+
+fn spawn_intermission(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    query_player: Query<(&Player, &Side)>,
+    game_state: Res<GameState>,
+    input: MenuInput,
+) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let text = |value: String, font_size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(6.)),
+            ..default()
+        })
+    };
+    let names = side_names(&query_player);
+    let [first, far] = games_won(&game_state.games);
+    let last = game_state.games.last().copied().unwrap_or_default();
+    let [last_first, last_far] = points(last);
+    let taker = &names[usize::from(last_far > last_first)];
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.7).into(),
+                // over the HUD
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            IntermissionScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(
+                format!(
+                    "GAME {} TO {}",
+                    game_state.games.len(),
+                    taker.to_uppercase()
+                ),
+                48.,
+                Color::WHITE,
+            ));
+            parent.spawn(text(format!("GAMES {first} : {far}"), 36., Color::WHITE));
+            for (number, &score) in game_state.games.iter().enumerate() {
+                parent.spawn(text(
+                    format!("GAME {}   {}", number + 1, score_line(score)),
+                    18.,
+                    Color::GRAY,
+                ));
+            }
+            parent.spawn((
+                text(
+                    countdown_line(INTERMISSION_SECONDS, &input),
+                    24.,
+                    Color::WHITE,
+                ),
+                NextGameCountdown(Timer::from_seconds(INTERMISSION_SECONDS, TimerMode::Once)),
+            ));
+        });
+}
+
+fn next_game(
+    mut query: Query<(&mut NextGameCountdown, &mut Text)>,
+    mut game_state: ResMut<GameState>,
+    mut flow: MatchFlow,
+    input: MenuInput,
+    timer: Res<Time>,
+) {
+    let mut done = input.just_pressed(MenuAction::Confirm);
+    for (mut countdown, mut text) in &mut query {
+        countdown.0.tick(timer.delta());
+        text.sections[0].value = countdown_line(countdown.0.remaining_secs(), &input);
+        done |= countdown.0.finished();
+    }
+    if done {
+        game_state.score = (0, 0);
+        flow.resume();
+    }
+}
@@ -0,0 +1,261 @@
+//! The way in: a short splash that fades the name in, then the title screen,
+//! the main menu, with a ball bouncing behind the logo and Play, Options and
+//! Quit sliding in from the left, and Tournament after Play on an arena with
+//! two players. Any key or button skips the splash; the
+//! title menu is worked through the menu focus like every other screen.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    arena::Arena,
+    despawn_screen,
+    engine::ScheduleSetup,
+    focus::{FocusEvent, Focusable},
+    session::EndSession,
+    tween::{Tween, TweenLens},
+    AppState, Difficulty,
+};
+
+// seconds the splash stays up unless skipped
+const SPLASH_DURATION: f32 = 2.;
+const SPLASH_FADE: f32 = 0.6;
+// how far off to the left the menu items start, and how they stagger in
+const SLIDE_DISTANCE: f32 = 400.;
+const SLIDE_DURATION: f32 = 0.5;
+const SLIDE_STAGGER: f32 = 0.15;
+const TITLE_BALL_SIZE: f32 = 20.;
+const TITLE_BALL_SPEED: Vec2 = Vec2::new(260., 190.);
+
+pub struct IntroPlugin;
+
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Splash, spawn_splash)
+            .add_systems_during(AppState::Splash, skip_splash)
+            .add_systems_on_exit(AppState::Splash, despawn_screen::<SplashScreen>)
+            .add_systems_on_enter(AppState::Menu, spawn_title)
+            .add_systems_during(AppState::Menu, bounce_title_ball)
+            .add_systems_during(AppState::Menu, choose_title_item)
+            .add_systems_on_exit(AppState::Menu, despawn_screen::<TitleScreen>);
+    }
+}
+
+#[derive(Component)]
+struct SplashScreen {
+    shown: f32,
+}
+
+#[derive(Component)]
+struct TitleScreen;
+
+#[derive(Component)]
+struct TitleBall {
+    velocity: Vec2,
+}
+
+/// The title menu's entries, top to bottom.
+#[derive(Component, Clone, Copy)]
+enum TitleItem {
+    Play,
+    Tournament,
+    Options,
+    Quit,
+}
+
+impl TitleItem {
+    const ALL: [TitleItem; 4] = [
+        TitleItem::Play,
+        TitleItem::Tournament,
+        TitleItem::Options,
+        TitleItem::Quit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            TitleItem::Play => "PLAY",
+            TitleItem::Tournament => "TOURNAMENT",
+            TitleItem::Options => "OPTIONS",
+            TitleItem::Quit => "QUIT",
+        }
+    }
+}
+
+fn spawn_splash(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+            SplashScreen { shown: 0. },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "pong-rs",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 48.,
+                        color: Color::rgba(1., 1., 1., 0.),
+                    },
+                ),
+                Tween::new(TweenLens::TextAlpha, 0., 1., SPLASH_FADE),
+            ));
+        });
+}
+
+fn skip_splash(
+    mut query: Query<&mut SplashScreen>,
+    mut next_state: ResMut<NextState<AppState>>,
+    (keyboard_input, gamepad_input): (Res<Input<KeyCode>>, Res<Input<GamepadButton>>),
+    timer: Res<Time>,
+) {
+    let skipped =
+        keyboard_input.get_just_pressed().len() > 0 || gamepad_input.get_just_pressed().len() > 0;
+    for mut splash in &mut query {
+        splash.shown += timer.raw_delta_seconds();
+        if skipped || splash.shown >= SPLASH_DURATION {
+            next_state.set(AppState::Menu);
+        }
+    }
+}
+
+fn spawn_title(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    arena: Res<Arena>,
+    bot: Option<Res<Difficulty>>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    // a tournament needs someone at the far goal, and not the computer
+    let items = TitleItem::ALL.into_iter().filter(|item| {
+        !matches!(item, TitleItem::Tournament) || (arena.goals.len() >= 2 && bot.is_none())
+    });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                ..default()
+            },
+            TitleScreen,
+        ))
+        .with_children(|parent| {
+            // first child, so the logo and menu draw over it
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::all(Val::Px(TITLE_BALL_SIZE)),
+                        position: UiRect {
+                            left: Val::Px(40.),
+                            top: Val::Px(60.),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    background_color: Color::rgba(1., 0., 0., 0.8).into(),
+                    ..default()
+                },
+                TitleBall {
+                    velocity: TITLE_BALL_SPEED,
+                },
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    "PONG",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 120.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(40.)),
+                    ..default()
+                }),
+            );
+            for (i, item) in items.enumerate() {
+                parent.spawn((
+                    TextBundle::from_section(
+                        item.label(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 36.,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_style(Style {
+                        position: UiRect::left(Val::Px(-SLIDE_DISTANCE)),
+                        margin: UiRect::vertical(Val::Px(6.)),
+                        ..default()
+                    }),
+                    Tween::new(TweenLens::Left, -SLIDE_DISTANCE, 0., SLIDE_DURATION)
+                        .with_delay(SLIDE_STAGGER * i as f32),
+                    Focusable::default(),
+                    item,
+                ));
+            }
+        });
+}
+
+// a UI node rather than a game ball, so it lives and dies with the screen
+fn bounce_title_ball(
+    mut query: Query<(&mut Style, &mut TitleBall)>,
+    query_window: Query<&Window, With<PrimaryWindow>>,
+    timer: Res<Time>,
+) {
+    let Ok(window) = query_window.get_single() else {
+        return;
+    };
+    let bounds = Vec2::new(window.width(), window.height()) - TITLE_BALL_SIZE;
+    let delta = timer.raw_delta_seconds();
+
+    for (mut style, mut ball) in &mut query {
+        let (Val::Px(left), Val::Px(top)) = (style.position.left, style.position.top) else {
+            continue;
+        };
+        let mut position = Vec2::new(left, top) + ball.velocity * delta;
+        for axis in 0..2 {
+            if position[axis] < 0. || position[axis] > bounds[axis] {
+                ball.velocity[axis] = -ball.velocity[axis];
+                position[axis] = position[axis].clamp(0., bounds[axis].max(0.));
+            }
+        }
+        style.position.left = Val::Px(position.x);
+        style.position.top = Val::Px(position.y);
+    }
+}
+
+fn choose_title_item(
+    mut events: EventReader<FocusEvent>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut ends: EventWriter<EndSession>,
+    query: Query<&TitleItem>,
+) {
+    for event in events.iter() {
+        let FocusEvent::Activated(entity) = *event else {
+            continue;
+        };
+        match query.get(entity) {
+            Ok(TitleItem::Play) => next_state.set(AppState::CharacterSelect),
+            Ok(TitleItem::Tournament) => next_state.set(AppState::Entry),
+            Ok(TitleItem::Options) => next_state.set(AppState::Options),
+            Ok(TitleItem::Quit) => ends.send(EndSession),
+            Err(_) => {}
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Input latency diagnostics. F3 toggles an overlay that times each
+//! Left/Right press from the frame it was read to the end of the frame in
+//! which the paddle moved, plus one frame for the pipelined renderer to put it
+//! on screen. F4 adds a white patch in the corner that lights on those frames,
+//! so a photodiode or high-speed camera can measure the full chain against the
+//! on-screen estimate.
+//!
+//! Bevy doesn't timestamp OS input events, so time spent before the frame
+//! picks up the press isn't included.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{
+    arena::Arena,
+    bindings::KeyBindings,
+    engine::{ScheduleSetup, Stage},
+    hotkey::Hotkeys,
+    paddle::{Player, Side},
+};
+
+pub const LATENCY_KEY: KeyCode = KeyCode::F3;
+pub const PHOTODIODE_KEY: KeyCode = KeyCode::F4;
+
+const MAX_SAMPLES: usize = 60;
+const PATCH_SIZE: f32 = 80.;
+
+pub struct LatencyPlugin;
+
+impl Plugin for LatencyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LatencyProbe>()
+            .add_systems_at(Stage::Startup, spawn_overlay)
+            .add_system(toggle_probe)
+            .add_systems_at(Stage::PreUpdate, stamp_input.after(InputSystem))
+            .add_systems_at(Stage::Last, detect_paddle_motion)
+            .add_systems_at(Stage::Last, update_overlay.after(detect_paddle_motion));
+    }
+}
+
+#[derive(Resource, Default)]
+struct LatencyProbe {
+    enabled: bool,
+    photodiode: bool,
+    pressed_at: Option<Instant>,
+    last_x: Option<f32>,
+    moved_this_frame: bool,
+    samples: VecDeque<Duration>,
+}
+
+#[derive(Component)]
+struct LatencyText;
+
+#[derive(Component)]
+struct PhotodiodePatch;
+
+fn spawn_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        TextBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 16.,
+                    color: Color::LIME_GREEN,
+                },
+            ),
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        LatencyText,
+    ));
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(0.),
+                    top: Val::Px(0.),
+                    ..default()
+                },
+                size: Size::new(Val::Px(PATCH_SIZE), Val::Px(PATCH_SIZE)),
+                ..default()
+            },
+            background_color: Color::BLACK.into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        PhotodiodePatch,
+    ));
+}
+
+fn toggle_probe(mut probe: ResMut<LatencyProbe>, hotkeys: Hotkeys) {
+    if hotkeys.just_pressed(LATENCY_KEY) {
+        probe.enabled = !probe.enabled;
+        probe.samples.clear();
+        probe.pressed_at = None;
+    }
+    if hotkeys.just_pressed(PHOTODIODE_KEY) && probe.enabled {
+        probe.photodiode = !probe.photodiode;
+    }
+}
+
+fn stamp_input(
+    mut probe: ResMut<LatencyProbe>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    arena: Res<Arena>,
+) {
+    if probe.enabled
+        && probe.pressed_at.is_none()
+        && keyboard_input.any_just_pressed(bindings.steering(Side(0), &arena))
+    {
+        probe.pressed_at = Some(Instant::now());
+    }
+}
+
+fn detect_paddle_motion(
+    mut probe: ResMut<LatencyProbe>,
+    query: Query<&GlobalTransform, With<Player>>,
+    timer: Res<Time>,
+) {
+    let x = query
+        .iter()
+        .next()
+        .map(|transform| transform.translation().x);
+    let moved = x.is_some() && x != probe.last_x;
+    probe.last_x = x;
+    probe.moved_this_frame = moved;
+
+    if !moved {
+        return;
+    }
+
+    if let Some(pressed_at) = probe.pressed_at.take() {
+        // the frame simulated now is drawn while the next one runs
+        let sample = pressed_at.elapsed() + timer.delta();
+        if probe.samples.len() == MAX_SAMPLES {
+            probe.samples.pop_front();
+        }
+        probe.samples.push_back(sample);
+    }
+}
+
+fn update_overlay(
+    probe: Res<LatencyProbe>,
+    mut query_text: Query<(&mut Text, &mut Visibility), With<LatencyText>>,
+    mut query_patch: Query<
+        (&mut BackgroundColor, &mut Visibility),
+        (With<PhotodiodePatch>, Without<LatencyText>),
+    >,
+    timer: Res<Time>,
+) {
+    for (mut text, mut visibility) in &mut query_text {
+        *visibility = if probe.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if !probe.enabled {
+            continue;
+        }
+
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.;
+        let stats = if probe.samples.is_empty() {
+            "press Left/Right to sample".to_owned()
+        } else {
+            let total: Duration = probe.samples.iter().sum();
+            format!(
+                "last {:.1} ms  avg {:.1} ms  min {:.1} ms  max {:.1} ms  ({} samples)",
+                millis(*probe.samples.back().unwrap()),
+                millis(total / probe.samples.len() as u32),
+                millis(*probe.samples.iter().min().unwrap()),
+                millis(*probe.samples.iter().max().unwrap()),
+                probe.samples.len(),
+            )
+        };
+
+        text.sections[0].value = format!(
+            "INPUT LATENCY (F3)\nframe {:.1} ms\n{}\nphotodiode patch (F4): {}",
+            timer.delta_seconds_f64() * 1000.,
+            stats,
+            if probe.photodiode { "on" } else { "off" },
+        );
+    }
+
+    for (mut color, mut visibility) in &mut query_patch {
+        *visibility = if probe.enabled && probe.photodiode {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        *color = if probe.moved_this_frame {
+            Color::WHITE.into()
+        } else {
+            Color::BLACK.into()
+        };
+    }
+}
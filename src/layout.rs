@@ -0,0 +1,361 @@
+//! Playfield layouts as Bevy scenes. The walls go up along the [`Arena`]'s
+//! edges, as its file describes them; how the field is dressed (the ball's
+//! look, the paddles' look and the court lines painted on the field) lives in
+//! `assets/scenes/<layout>.scn.ron`, along with any prefabs (bricks and the
+//! like) placed in the field. This module registers the component types
+//! those files use, spawns the scene and dresses the plain data components
+//! with meshes as they appear, so a reloaded scene comes back fully drawn.
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    arena::Arena,
+    engine,
+    engine::{ScheduleSetup, Stage},
+    prefab::{spawn_prefab, PrefabLibrary, PrefabOverrides, Prefabs},
+    BallAssets, Wall,
+};
+
+pub struct LayoutPlugin {
+    /// Scene asset path, relative to `assets/`.
+    pub scene: String,
+}
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BallTemplate>()
+            .register_type::<PaddleTemplate>()
+            .register_type::<PrefabSpot>()
+            .register_type::<CourtMarkings>()
+            .insert_resource(LayoutScene(self.scene.clone()))
+            .add_systems_at(Stage::Startup, spawn_layout)
+            .add_systems_at(Stage::Startup, spawn_walls)
+            .add_system(paint_markings)
+            .add_system(load_ball_template)
+            .add_system(spawn_prefab_spots);
+    }
+}
+
+/// How balls look; one per layout.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct BallTemplate {
+    pub radius: f32,
+    pub color: Color,
+}
+
+/// How paddles look; one per layout.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct PaddleTemplate {
+    pub color: Color,
+}
+
+/// Court lines painted on the field, under everything in play; one per
+/// layout, or none for a bare field.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct CourtMarkings {
+    pub color: Color,
+    /// Dash length of the center line halfway up the field, with gaps as
+    /// long; 0 leaves it out.
+    pub dash: f32,
+    /// How far into the field from each goal its service line runs, with a
+    /// line from there to the center splitting it into two boxes; 0 leaves
+    /// them out.
+    pub service_depth: f32,
+    /// Spacing of a faint grid under the other lines; 0 leaves it out.
+    pub grid: f32,
+}
+
+/// A prefab the layout places in the field.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct PrefabSpot {
+    pub prefab: String,
+    pub position: Vec2,
+}
+
+#[derive(Resource)]
+struct LayoutScene(String);
+
+// the 2d camera sees down to z = -0.1, so this is behind everything in play
+// at 0 and still drawn
+const MARKINGS_Z: f32 = -0.05;
+const CENTER_LINE_WIDTH: f32 = 4.;
+const SERVICE_LINE_WIDTH: f32 = 2.;
+const GRID_LINE_WIDTH: f32 = 1.;
+// of the markings' own alpha
+const GRID_ALPHA: f32 = 0.4;
+
+/// One straight line of the court markings.
+#[derive(Debug, PartialEq)]
+struct Stroke {
+    start: Vec2,
+    end: Vec2,
+    width: f32,
+    color: Color,
+}
+
+// the lines run right across and get clipped to the arena, so they suit any shape
+fn strokes(markings: &CourtMarkings, arena: &Arena) -> Vec<Stroke> {
+    let reach = arena.vertices.iter().map(|v| v.length()).fold(0., f32::max) * 2.;
+    let across =
+        |point: Vec2, along: Vec2| arena.clip(point - along * reach, point + along * reach);
+    let goal = arena.edge(arena.goals[0]);
+    let center = goal.midpoint() + goal.normal() * arena.depth() / 2.;
+    let mut strokes = Vec::new();
+    let mut stroke = |(start, end): (Vec2, Vec2), width: f32, color: Color| {
+        strokes.push(Stroke {
+            start,
+            end,
+            width,
+            color,
+        });
+    };
+
+    if markings.grid > 0. {
+        let faint = markings.color.with_a(markings.color.a() * GRID_ALPHA);
+        let lines = (reach / markings.grid) as i32;
+        for i in -lines..=lines {
+            let offset = i as f32 * markings.grid;
+            for (point, along) in [
+                (Vec2::new(offset, 0.), Vec2::Y),
+                (Vec2::new(0., offset), Vec2::X),
+            ] {
+                if let Some(line) = across(point, along) {
+                    stroke(line, GRID_LINE_WIDTH, faint);
+                }
+            }
+        }
+    }
+
+    if markings.service_depth > 0. {
+        for line in arena.goal_lines() {
+            let normal = line.normal();
+            let service = line.midpoint() + normal * markings.service_depth;
+            if let Some(service_line) = across(service, (line.end - line.start).normalize()) {
+                stroke(service_line, SERVICE_LINE_WIDTH, markings.color);
+            }
+            let to_center = (center - service).dot(normal);
+            if to_center > 0. {
+                stroke(
+                    (service, service + normal * to_center),
+                    SERVICE_LINE_WIDTH,
+                    markings.color,
+                );
+            }
+        }
+    }
+
+    if markings.dash > 0. {
+        if let Some((start, end)) = arena.center_line() {
+            let length = start.distance(end);
+            let dir = (end - start) / length;
+            let mut at = 0.;
+            // with no sliver of a dash left over from rounding at the far end
+            while at < length - 0.5 {
+                let dash_end = (at + markings.dash).min(length);
+                stroke(
+                    (start + dir * at, start + dir * dash_end),
+                    CENTER_LINE_WIDTH,
+                    markings.color,
+                );
+                at += markings.dash * 2.;
+            }
+        }
+    }
+    strokes
+}
+
+fn spawn_layout(mut commands: Commands, layout: Res<LayoutScene>, asset_server: Res<AssetServer>) {
+    commands.spawn(DynamicSceneBundle {
+        scene: asset_server.load(layout.0.as_str()),
+        ..default()
+    });
+}
+
+// walls are centered on the arena edges, overlapping a little at the corners
+fn spawn_walls(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    arena: Res<Arena>,
+) {
+    let material = materials.add(ColorMaterial::from(Color::WHITE));
+    for edge in arena.walls() {
+        let collider = edge.collider();
+        commands.spawn((
+            MaterialMesh2dBundle {
+                mesh: meshes.add(engine::rectangle(collider.size)).into(),
+                material: material.clone(),
+                transform: edge.transform(),
+                ..default()
+            },
+            edge,
+            collider,
+            Wall,
+        ));
+    }
+}
+
+// repainted from scratch whenever the scene's markings change
+fn paint_markings(
+    mut commands: Commands,
+    query: Query<(Entity, &CourtMarkings), Changed<CourtMarkings>>,
+    arena: Res<Arena>,
+) {
+    for (entity, markings) in &query {
+        commands.entity(entity).despawn_descendants();
+        commands
+            .entity(entity)
+            .insert(SpatialBundle::default())
+            .with_children(|parent| {
+                for stroke in strokes(markings, &arena) {
+                    let dir = stroke.end - stroke.start;
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: stroke.color,
+                            custom_size: Some(Vec2::new(dir.length(), stroke.width)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            ((stroke.start + stroke.end) / 2.).extend(MARKINGS_Z),
+                        )
+                        .with_rotation(Quat::from_rotation_z(dir.y.atan2(dir.x))),
+                        ..default()
+                    });
+                }
+            });
+    }
+}
+
+fn load_ball_template(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<&BallTemplate, Changed<BallTemplate>>,
+) {
+    for template in &query {
+        commands.insert_resource(BallAssets {
+            mesh: meshes.add(engine::circle(template.radius)),
+            material: materials.add(ColorMaterial::from(template.color)),
+        });
+    }
+}
+
+// the prefab hangs off the spot so it goes away with the scene instance
+fn spawn_prefab_spots(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &PrefabSpot)>,
+    prefabs: Res<Prefabs>,
+    libraries: Res<Assets<PrefabLibrary>>,
+) {
+    let Some(library) = libraries.get(&prefabs.0) else {
+        return;
+    };
+
+    for (entity, spot) in &query {
+        let overrides = PrefabOverrides {
+            translation: spot.position.extend(0.),
+            ..default()
+        };
+        commands
+            .entity(entity)
+            .remove::<PrefabSpot>()
+            .insert(SpatialBundle::default());
+        if let Some(child) = spawn_prefab(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            library,
+            &spot.prefab,
+            overrides,
+        ) {
+            commands.entity(entity).add_child(child);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{reflect::TypeRegistryInternal, scene::serde::SceneDeserializer};
+    use serde::de::DeserializeSeed;
+
+    use super::*;
+    use crate::arena::ArenaFile;
+
+    fn registry() -> TypeRegistryInternal {
+        let mut registry = TypeRegistryInternal::default();
+        registry.register::<BallTemplate>();
+        registry.register::<PaddleTemplate>();
+        registry.register::<PrefabSpot>();
+        registry.register::<CourtMarkings>();
+        registry.register::<String>();
+        registry.register::<Vec2>();
+        registry.register::<Color>();
+        registry.register::<f32>();
+        registry
+    }
+
+    // `layout` relative to `assets/`, as the plugin takes it
+    fn load(layout: &str) -> (DynamicScene, TypeRegistryInternal) {
+        let path = format!("{}/assets/{layout}", env!("CARGO_MANIFEST_DIR"));
+        let text = std::fs::read_to_string(&path).unwrap();
+        let registry = registry();
+        let mut deserializer = ron::de::Deserializer::from_str(&text).unwrap();
+        let scene = SceneDeserializer {
+            type_registry: &registry,
+        }
+        .deserialize(&mut deserializer)
+        .unwrap_or_else(|err| panic!("{path}: {err}"));
+        (scene, registry)
+    }
+
+    // the walls come from the arena file, so a scene only dresses the field
+    // (walls left in one wouldn't load, their types going unregistered)
+    #[test]
+    fn each_arena_has_a_scene_to_dress_it() {
+        for name in ["square", "hex", "triangle", "classic"] {
+            let (scene, registry) = load(&ArenaFile::load(name).unwrap().layout(name));
+            let mut world = World::new();
+            world.insert_resource(AppTypeRegistry::default());
+            *world.resource::<AppTypeRegistry>().write() = registry;
+            scene.write_to_world(&mut world, &mut default()).unwrap();
+
+            assert_eq!(world.query::<&BallTemplate>().iter(&world).count(), 1);
+            assert_eq!(world.query::<&PaddleTemplate>().iter(&world).count(), 1);
+        }
+    }
+
+    #[test]
+    fn markings_stay_on_the_field() {
+        let arena = Arena::square(600.);
+        let markings = CourtMarkings {
+            color: Color::WHITE,
+            dash: 20.,
+            service_depth: 100.,
+            grid: 0.,
+        };
+        let lines = strokes(&markings, &arena);
+        // the service line and the line up to the center, then 15 dashes across the middle
+        let dashes: Vec<_> = lines
+            .iter()
+            .filter(|stroke| stroke.width == CENTER_LINE_WIDTH)
+            .collect();
+        assert_eq!(dashes.len(), 15);
+        assert!(dashes.iter().all(|dash| dash.start.y.abs() < 0.01));
+        assert_eq!(lines.len(), 17);
+        assert!(lines[0].start.abs_diff_eq(Vec2::new(-300., -200.), 0.01));
+        assert!(lines[1].end.abs_diff_eq(Vec2::ZERO, 0.01));
+
+        let grid = CourtMarkings {
+            grid: 50.,
+            ..default()
+        };
+        // 13 lines each way, the outer two under the walls
+        assert_eq!(strokes(&grid, &arena).len(), 26);
+    }
+}
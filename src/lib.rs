@@ -0,0 +1,889 @@
+//! Pong in Bevy. [`GamePlugin`] wires up the whole game; [`sim`] steps the
+//! core ball physics headlessly for tests and tooling.
+
+// Bevy system queries are long by nature
+#![allow(clippy::type_complexity)]
+
+use std::{ops::RangeInclusive, path::PathBuf};
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use rand::{rngs::StdRng, SeedableRng};
+
+pub mod agent;
+mod announcer;
+mod archetype;
+pub mod arena;
+mod bindings;
+mod block;
+mod bot;
+mod breakout;
+mod callout;
+mod charge;
+mod collision;
+mod controls;
+pub mod desync;
+mod dilation;
+mod disconnect;
+mod engine;
+mod entry;
+mod event_log;
+mod flash;
+mod flick;
+mod focus;
+mod fonts;
+#[cfg(feature = "dev")]
+mod frame_step;
+mod game_over;
+mod gamepad;
+#[cfg(feature = "golden")]
+pub mod golden;
+mod graze;
+mod handedness;
+mod heatmap;
+mod hold;
+mod hotkey;
+mod idle;
+mod input;
+#[cfg(feature = "dev")]
+mod inspector;
+mod intermission;
+mod intro;
+mod latency;
+mod layout;
+mod lives;
+mod loading;
+mod low_power;
+mod mini;
+mod multi_ball;
+mod mutator;
+#[cfg(feature = "observe")]
+pub mod observe;
+mod options;
+mod ownership;
+mod paddle;
+mod pause;
+pub mod physics;
+mod pickup;
+mod power_up;
+mod practice;
+mod prefab;
+mod prompt;
+#[cfg(feature = "remote")]
+mod remote;
+mod rescue;
+mod rewind;
+mod scoreboard;
+mod scorecard;
+mod select;
+mod serve;
+mod session;
+mod share;
+mod shot_chart;
+mod side_swap;
+pub mod sim;
+mod smash;
+pub mod snapshot;
+mod special;
+mod spectator;
+mod spin;
+mod stats;
+mod streamer;
+mod survival;
+mod tick;
+mod time_scale;
+mod toast;
+mod touch;
+mod tournament;
+#[cfg(feature = "dev")]
+mod tuning;
+mod tween;
+mod win_meter;
+
+use announcer::AnnouncerPlugin;
+use archetype::ArchetypePlugin;
+use arena::{Arena, Collider};
+use bindings::BindingsPlugin;
+use block::BlockPlugin;
+use bot::BotPlugin;
+use breakout::{BreakoutPlugin, BrickBroken};
+use callout::CalloutPlugin;
+use charge::ChargePlugin;
+use collision::{
+    log_hits, ramp_speed, return_ball, score_goals, serve_after_goal, BallHitPaddle, BallHitWall,
+    BallOutOfBounds, CollisionPlugin,
+};
+use controls::ControlsPlugin;
+use dilation::DilationPlugin;
+use disconnect::DisconnectPlugin;
+use engine::{ScheduleSetup, Stage};
+use entry::EntryPlugin;
+use event_log::EventLogPlugin;
+use flash::FlashPlugin;
+use flick::FlickPlugin;
+use focus::FocusPlugin;
+use fonts::FontsPlugin;
+use game_over::GameOverPlugin;
+use gamepad::GamepadPlugin;
+use graze::GrazePlugin;
+use handedness::HandednessPlugin;
+use heatmap::HeatmapPlugin;
+use hold::HoldPlugin;
+use hotkey::HotkeyGuard;
+use idle::IdlePlugin;
+use input::InputPlugin;
+use intermission::IntermissionPlugin;
+use intro::IntroPlugin;
+use latency::LatencyPlugin;
+use layout::LayoutPlugin;
+use lives::LivesPlugin;
+use loading::LoadingPlugin;
+use low_power::LowPowerPlugin;
+use mini::MiniPlugin;
+use multi_ball::MultiBallPlugin;
+use mutator::MutatorPlugin;
+use options::OptionsPlugin;
+use ownership::OwnershipPlugin;
+use paddle::{Paddle, PaddlePlugin, PaddleStats, Player, Side};
+use pause::{starting_match, PausePlugin};
+use physics::{
+    crossed_goal, paddle_bounce, push_out_of_paddle, push_out_of_wall, reflect, sweep_box,
+    sweep_wall, turned_size, MAX_SWEEP_BOUNCES,
+};
+use pickup::PickupPlugin;
+use power_up::PowerUpPlugin;
+use practice::PracticePlugin;
+use prefab::{Brick, PrefabPlugin};
+use prompt::PromptPlugin;
+use rescue::RescuePlugin;
+use rewind::RewindPlugin;
+use scoreboard::ScoreboardPlugin;
+use scorecard::ScorecardPlugin;
+use select::SelectPlugin;
+use serve::{rally_goes_on, serve_spots, MatchPhase, ServePattern, ServePlugin};
+use session::SessionPlugin;
+use shot_chart::ShotChartPlugin;
+use side_swap::SideSwapPlugin;
+use special::{SlowMotion, SpecialPlugin};
+use spectator::SpectatorPlugin;
+use spin::SpinPlugin;
+use stats::StatsPlugin;
+use streamer::StreamerPlugin;
+use survival::SurvivalPlugin;
+use tick::{remember_positions, tick_seconds, TickPlugin};
+use time_scale::{scaled, TimeScale, TimeScalePlugin};
+use toast::ToastPlugin;
+use touch::TouchPlugin;
+use tournament::{Tournament, TournamentPlugin};
+use tween::TweenPlugin;
+use win_meter::WinMeterPlugin;
+
+pub use bot::Difficulty;
+pub use paddle::ControlMode;
+pub use share::MatchCode;
+pub use streamer::StreamerSettings;
+pub use tick::DEFAULT_TICK_RATE;
+
+pub const DEFAULT_SPEED: f32 = 50.;
+/// The ball's mesh size, and its size at the default [`Tunables`].
+pub const BALL_SIZE: Vec2 = Vec2::new(10., 10.);
+/// The default paddle size; archetypes set their own widths.
+pub const PLAYER_SIZE: Vec2 = Vec2::new(100., 10.);
+
+// paddle returns faster than this split the ball in two
+const SPLIT_SPEED: f32 = 450.;
+const SPLIT_ANGLE: f32 = 15. * std::f32::consts::PI / 180.;
+const SPLIT_SLOWDOWN: f32 = 0.7;
+const MAX_BALLS: usize = 4;
+// how far off straight a return off the very end of a paddle goes
+const MAX_BOUNCE_ANGLE: f32 = 60. * std::f32::consts::PI / 180.;
+// seconds after a paddle bounce before the same paddle can bounce that ball again
+const PADDLE_HIT_COOLDOWN: f32 = 0.1;
+
+/// The whole game, minus window and engine plugins.
+pub struct GamePlugin {
+    pub arena: Arena,
+    pub control_mode: ControlMode,
+    /// Scene dressing the arena, with the ball and paddle looks, relative to `assets/`.
+    pub layout: String,
+    /// Enables practice tools like save-states.
+    pub training: bool,
+    /// Turns on streamer mode with these options.
+    pub streamer: Option<StreamerSettings>,
+    /// Lets the computer defend every goal after the first, at this difficulty.
+    pub bot: Option<Difficulty>,
+    /// Seeds the serves, so a match can be played again; a random one if unset.
+    pub seed: Option<u64>,
+    /// Runs a round-robin tournament between these entrants.
+    pub tournament: Option<Vec<String>>,
+    /// Key bindings file, written with the defaults if it's missing; the
+    /// defaults alone if unset.
+    pub bindings: Option<PathBuf>,
+    /// The setup as a match code, shown after the match; its mutators are
+    /// the ones the select screen starts with.
+    pub code: Option<MatchCode>,
+    /// Physics ticks a second; [`DEFAULT_TICK_RATE`] if unset.
+    pub tick_rate: Option<f32>,
+}
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<AppState>()
+            .add_plugin(AnnouncerPlugin)
+            .add_plugin(ArchetypePlugin)
+            .add_plugin(BindingsPlugin {
+                path: self.bindings.clone(),
+            })
+            .add_plugin(BlockPlugin)
+            .add_plugin(BotPlugin)
+            .add_plugin(BreakoutPlugin)
+            .add_plugin(CalloutPlugin)
+            .add_plugin(ChargePlugin)
+            .add_plugin(CollisionPlugin)
+            .add_plugin(ControlsPlugin)
+            .add_plugin(DilationPlugin)
+            .add_plugin(DisconnectPlugin)
+            .add_plugin(EntryPlugin)
+            .add_plugin(EventLogPlugin)
+            .add_plugin(FlashPlugin)
+            .add_plugin(FlickPlugin)
+            .add_plugin(FocusPlugin)
+            .add_plugin(FontsPlugin)
+            .add_plugin(GameOverPlugin)
+            .add_plugin(GamepadPlugin)
+            .add_plugin(GrazePlugin)
+            .add_plugin(HandednessPlugin)
+            .add_plugin(HeatmapPlugin)
+            .add_plugin(HoldPlugin)
+            .add_plugin(IdlePlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(IntermissionPlugin)
+            .add_plugin(IntroPlugin)
+            .add_plugin(LatencyPlugin)
+            .add_plugin(LayoutPlugin {
+                scene: self.layout.clone(),
+            })
+            .add_plugin(LivesPlugin)
+            .add_plugin(LoadingPlugin)
+            .add_plugin(LowPowerPlugin)
+            .add_plugin(MiniPlugin)
+            .add_plugin(MultiBallPlugin)
+            .add_plugin(MutatorPlugin)
+            .add_plugin(OptionsPlugin)
+            .add_plugin(OwnershipPlugin)
+            .add_plugin(PaddlePlugin)
+            .add_plugin(PausePlugin)
+            .add_plugin(PickupPlugin)
+            .add_plugin(PowerUpPlugin)
+            .add_plugin(PrefabPlugin)
+            .add_plugin(PromptPlugin)
+            .add_plugin(RescuePlugin)
+            .add_plugin(RewindPlugin)
+            .add_plugin(ScoreboardPlugin)
+            .add_plugin(ScorecardPlugin)
+            .add_plugin(SelectPlugin)
+            .add_plugin(ServePlugin)
+            .add_plugin(SessionPlugin)
+            .add_plugin(ShotChartPlugin)
+            .add_plugin(SideSwapPlugin)
+            .add_plugin(SpecialPlugin)
+            .add_plugin(SpectatorPlugin)
+            .add_plugin(SpinPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(SurvivalPlugin)
+            .add_plugin(TickPlugin {
+                rate: self.tick_rate.unwrap_or(DEFAULT_TICK_RATE),
+            })
+            .add_plugin(TimeScalePlugin)
+            .add_plugin(ToastPlugin)
+            .add_plugin(TouchPlugin)
+            .add_plugin(TournamentPlugin)
+            .add_plugin(TweenPlugin)
+            .add_plugin(WinMeterPlugin)
+            .init_resource::<GameState>()
+            .init_resource::<HotkeyGuard>()
+            .init_resource::<Tunables>()
+            .insert_resource(self.arena.clone())
+            .insert_resource(self.control_mode)
+            .add_systems_at(Stage::Startup, setup)
+            .add_systems_on_enter(AppState::Playing, serve_first_ball.run_if(starting_match))
+            .add_systems_during(AppState::Playing, scale_balls)
+            .add_systems_at(
+                Stage::FixedUpdate,
+                (
+                    move_ball,
+                    bounce_ball,
+                    ramp_speed,
+                    return_ball,
+                    log_hits,
+                    depenetrate_balls,
+                    out_of_bounds,
+                    score_goals,
+                    serve_after_goal,
+                )
+                    .chain()
+                    // InputSet has buffered this frame's presses in PreUpdate
+                    .after(remember_positions)
+                    .distributive_run_if(in_state(MatchPhase::Rally))
+                    .distributive_run_if(rally_goes_on)
+                    .distributive_run_if(in_state(AppState::Playing))
+                    // splits need the ball look, which arrives with the layout scene
+                    .distributive_run_if(resource_exists::<BallAssets>()),
+            );
+
+        if self.training {
+            app.add_plugin(PracticePlugin);
+        }
+        if let Some(settings) = self.streamer {
+            app.add_plugin(StreamerPlugin { settings });
+        }
+        let seed = self.seed.unwrap_or_else(rand::random);
+        app.insert_resource(MatchSeed(seed))
+            .insert_resource(GameRng(StdRng::seed_from_u64(seed)));
+        if let Some(difficulty) = self.bot {
+            app.insert_resource(difficulty);
+        }
+        if let Some(code) = &self.code {
+            app.insert_resource(code.clone());
+        }
+        if let Some(entrants) = &self.tournament {
+            app.insert_resource(Tournament::round_robin(entrants.clone()));
+        }
+
+        #[cfg(feature = "dev")]
+        app.add_plugin(tuning::TuningPlugin)
+            .add_plugin(inspector::InspectorPlugin)
+            .add_plugin(frame_step::FrameStepPlugin);
+        #[cfg(feature = "observe")]
+        app.add_plugin(observe::ObservePlugin);
+        #[cfg(feature = "remote")]
+        app.add_plugin(remote::RemotePlugin);
+    }
+}
+
+/// Where the game is. It opens `Loading`, then the splash; gameplay systems
+/// run only in `Playing`; `Paused`, `Intermission` and `GameOver` keep the
+/// match on screen under their menus.
+#[derive(States, Clone, Copy, Default, Debug, Hash, PartialEq, Eq)]
+enum AppState {
+    /// Preloading the assets.
+    #[default]
+    Loading,
+    Splash,
+    Menu,
+    Options,
+    /// Rebinding keys, off the options screen.
+    Controls,
+    /// Entering the names for a tournament bracket.
+    Entry,
+    CharacterSelect,
+    Playing,
+    Paused,
+    /// Between the games of a match.
+    Intermission,
+    GameOver,
+}
+
+#[derive(Resource, Reflect, Default, Clone)]
+#[reflect(Resource)]
+struct GameState {
+    /// Goals let in at the first goal, then at the second (the other
+    /// player's), this game.
+    score: (u32, u32),
+    /// The final `score` of each game of the match finished so far.
+    games: Vec<(u32, u32)>,
+}
+
+/// Gameplay numbers that can be changed while the game runs.
+#[derive(Resource, Clone, Copy)]
+pub struct Tunables {
+    /// Base ball speed multiplier.
+    pub speed: f32,
+    /// Multiplier applied for the frame after each bounce.
+    pub ramp: f32,
+    /// Paddle width relative to the chosen archetype's.
+    pub paddle_scale: f32,
+    /// Paddle thickness, in pixels.
+    pub paddle_height: f32,
+    /// Ball radius before `ball_scale`, in pixels.
+    pub ball_radius: f32,
+    /// Ball size relative to `ball_radius`, for effects that grow it.
+    pub ball_scale: f32,
+    /// Pull toward the bottom of the screen, in pixels per second squared.
+    pub gravity: f32,
+    /// Seconds of play without input before the game pauses itself; 0 never does.
+    pub idle_timeout: f32,
+    /// Seconds a ball can go without crossing the center line or touching a
+    /// paddle before it's turned back toward play; 0 leaves it be.
+    pub rescue_after: f32,
+    /// Points that win the match; 0 plays on forever.
+    pub point_limit: u32,
+    /// Where serves come from.
+    pub serve: ServePattern,
+    /// Returns of a rally between extra balls joining it; 0 never adds any.
+    pub extra_ball_every: u32,
+    /// Rows of breakout bricks across the far half; 0 lays none.
+    pub brick_rows: u32,
+    /// Whether the first goal ends the match, scored by time survived.
+    pub survival: bool,
+    /// Misses single play allows before the run ends, each costing a life
+    /// instead of a point; 0 plays without lives.
+    pub lives: u32,
+    /// Games in a match; the first side to win more than half takes it.
+    pub best_of: u32,
+    /// Whether balls slow down for a moment when a player is about to just
+    /// miss one.
+    pub graze_assist: bool,
+    /// Whether the first player's paddle follows the mouse cursor, in place
+    /// of their keys and pad.
+    pub mouse: bool,
+    /// Whether two players change ends halfway through the match.
+    pub side_swap: bool,
+    /// Whether the scoreboard shows each side's chance of taking the game.
+    pub win_meter: bool,
+}
+
+impl Default for Tunables {
+    fn default() -> Self {
+        Self {
+            speed: DEFAULT_SPEED,
+            ramp: 2.,
+            paddle_scale: 1.,
+            paddle_height: PLAYER_SIZE.y,
+            ball_radius: BALL_SIZE.x / 2.,
+            ball_scale: 1.,
+            gravity: 0.,
+            idle_timeout: 30.,
+            rescue_after: 8.,
+            point_limit: 11,
+            serve: ServePattern::Paddle,
+            extra_ball_every: 0,
+            brick_rows: 0,
+            survival: false,
+            lives: 0,
+            best_of: 1,
+            graze_assist: false,
+            mouse: false,
+            side_swap: false,
+            win_meter: false,
+        }
+    }
+}
+
+impl Tunables {
+    // how far the options menu, the tuning panel's sliders and remote
+    // `start` commands go
+    pub const POINT_LIMIT_RANGE: RangeInclusive<u32> = 0..=21;
+    /// Always odd within it, so a match can't end level.
+    pub const BEST_OF_RANGE: RangeInclusive<u32> = 1..=9;
+    pub const LIVES_RANGE: RangeInclusive<u32> = 0..=9;
+    pub const SPEED_RANGE: RangeInclusive<f32> = 10.0..=4. * DEFAULT_SPEED;
+    pub const PADDLE_SCALE_RANGE: RangeInclusive<f32> = 0.25..=3.0;
+    pub const PADDLE_HEIGHT_RANGE: RangeInclusive<f32> = 2.0..=60.0;
+    pub const BALL_RADIUS_RANGE: RangeInclusive<f32> = 1.0..=30.0;
+    pub const BALL_SCALE_RANGE: RangeInclusive<f32> = 0.5..=4.0;
+    pub const GRAVITY_RANGE: RangeInclusive<f32> = -200.0..=200.0;
+
+    /// The ball's size as it collides and is drawn.
+    pub fn ball_size(&self) -> Vec2 {
+        Vec2::splat(2. * self.ball_radius * self.ball_scale)
+    }
+}
+
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq)]
+#[reflect(Component)]
+pub struct Speed {
+    pub dir: Vec3,
+    pub speed_multiplier: f32,
+}
+
+impl Default for Speed {
+    fn default() -> Self {
+        Self {
+            dir: Default::default(),
+            speed_multiplier: DEFAULT_SPEED,
+        }
+    }
+}
+
+/// The seed the match's [`GameRng`] started from.
+#[derive(Resource, Clone, Copy)]
+struct MatchSeed(u64);
+
+/// Drives serves; a resource rather than `thread_rng` so snapshots can rewind it.
+#[derive(Resource, Clone)]
+pub struct GameRng(pub StdRng);
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self(StdRng::from_entropy())
+    }
+}
+
+#[derive(Component, Default)]
+struct Ball;
+
+/// The paddle a ball last bounced off, and how long ago. A ball clipped
+/// into a paddle can still overlap it, heading into one of its faces, for a
+/// few frames after the bounce; this keeps that to one bounce rather than a
+/// jitter or the paddle carrying it.
+#[derive(Component, Clone, Copy)]
+struct LastPaddleHit {
+    paddle: Entity,
+    age: f32,
+}
+
+/// How far a ball still has to go this tick.
+#[derive(Component, Clone, Copy, Default)]
+struct Travel(Vec3);
+
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+struct Wall;
+
+#[derive(Resource)]
+struct BallAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+fn ball_bundle(
+    assets: &BallAssets,
+    translation: Vec3,
+    speed: Speed,
+) -> (MaterialMesh2dBundle<ColorMaterial>, Ball, Speed, Travel) {
+    (
+        MaterialMesh2dBundle {
+            mesh: assets.mesh.clone().into(),
+            material: assets.material.clone(),
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        Ball,
+        speed,
+        Travel::default(),
+    )
+}
+
+fn spawn_ball(
+    commands: &mut Commands,
+    assets: &BallAssets,
+    translation: Vec3,
+    dir: Vec3,
+) -> Entity {
+    commands
+        .spawn(ball_bundle(
+            assets,
+            translation,
+            Speed {
+                dir,
+                speed_multiplier: DEFAULT_SPEED,
+            },
+        ))
+        .id()
+}
+
+/// Clears away a screen's UI, everything under its `T` root node.
+fn despawn_screen<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// the walls and ball look come from the layout scene
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
+
+fn serve_first_ball(
+    mut commands: Commands,
+    query_player: Query<(&Transform, &PaddleStats), With<Paddle>>,
+    ball_assets: Res<BallAssets>,
+    arena: Res<Arena>,
+    tunables: Res<Tunables>,
+    mut rng: ResMut<GameRng>,
+) {
+    let spots = serve_spots(
+        tunables.serve,
+        &arena,
+        0,
+        0,
+        &paddle_boxes(&query_player),
+        tunables.ball_size(),
+        &mut rng.0,
+    );
+    for (translation, dir) in spots {
+        spawn_ball(&mut commands, &ball_assets, translation, dir);
+    }
+}
+
+/// Each paddle's position and size, for the spawn checks.
+fn paddle_boxes<'a>(
+    paddles: impl IntoIterator<Item = (&'a Transform, &'a PaddleStats)>,
+) -> Vec<(Vec3, Vec2)> {
+    paddles
+        .into_iter()
+        .map(|(transform, stats)| {
+            (
+                transform.translation,
+                turned_size(stats.size, transform.rotation),
+            )
+        })
+        .collect()
+}
+
+// works out how far each ball goes this tick; bounce_ball carries it there
+fn move_ball(
+    mut query: Query<(&mut Speed, &mut Travel, Option<&TimeScale>), With<Ball>>,
+    fixed_time: Res<FixedTime>,
+    slow_motion: Res<SlowMotion>,
+    tunables: Res<Tunables>,
+) {
+    let delta = tick_seconds(&fixed_time) * slow_motion.scale();
+
+    for (mut speed, mut travel, time_scale) in &mut query {
+        let delta = scaled(delta, time_scale);
+        speed.dir.y -= tunables.gravity / tunables.speed * delta;
+        travel.0 = speed.dir * delta * speed.speed_multiplier;
+        speed.speed_multiplier = tunables.speed;
+    }
+}
+
+fn scale_balls(mut query: Query<&mut Transform, With<Ball>>, tunables: Res<Tunables>) {
+    for mut transform in &mut query {
+        transform.scale = Vec3::splat(tunables.ball_size().x / BALL_SIZE.x);
+    }
+}
+
+/// What a ball sweeping along its travel ran into first.
+enum Obstacle {
+    Brick {
+        brick: Entity,
+        at: Vec3,
+        normal: Vec3,
+    },
+    Wall {
+        normal: Vec3,
+    },
+    Paddle {
+        paddle: Entity,
+        normal: Vec3,
+    },
+}
+
+// each ball is swept along its travel and turned at the first thing it
+// meets, however far it goes in a tick, then carries on with what's left;
+// what comes of each hit is left to the collision systems
+fn bounce_ball(
+    mut commands: Commands,
+    mut query_ball: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Speed,
+            &mut Travel,
+            Option<&mut LastPaddleHit>,
+        ),
+        With<Ball>,
+    >,
+    (query_walls, query_bricks, query_player): (
+        Query<(&Transform, &Collider), (With<Wall>, Without<Ball>)>,
+        Query<(Entity, &Transform, &Brick), Without<Ball>>,
+        Query<(Entity, &Transform, &PaddleStats), (With<Paddle>, Without<Ball>)>,
+    ),
+    (mut wall_hits, mut paddle_hits, mut bricks_broken): (
+        EventWriter<BallHitWall>,
+        EventWriter<BallHitPaddle>,
+        EventWriter<BrickBroken>,
+    ),
+    (tunables, fixed_time): (Res<Tunables>, Res<FixedTime>),
+) {
+    let ball_size = tunables.ball_size();
+    // two balls into one brick on the same tick only knock it out once
+    let mut broken = Vec::new();
+
+    for (ball, mut ball_trans, mut speed, mut travel, mut last_hit) in &mut query_ball {
+        if let Some(last_hit) = &mut last_hit {
+            last_hit.age += tick_seconds(&fixed_time);
+        }
+        let mut recent = last_hit.as_deref().copied();
+
+        for _ in 0..MAX_SWEEP_BOUNCES {
+            let (start, motion) = (ball_trans.translation, travel.0);
+            let mut first: Option<(f32, Obstacle)> = None;
+            let mut meet = |toi: f32, obstacle: Obstacle| {
+                if !first
+                    .as_ref()
+                    .map_or(false, |(earliest, _)| *earliest <= toi)
+                {
+                    first = Some((toi, obstacle));
+                }
+            };
+
+            for (brick, brick_trans, Brick { size }) in &query_bricks {
+                if broken.contains(&brick) {
+                    continue;
+                }
+                let size = turned_size(*size, brick_trans.rotation);
+                if let Some((toi, normal)) =
+                    sweep_box(start, motion, brick_trans.translation, size, ball_size)
+                {
+                    let at = brick_trans.translation;
+                    meet(toi, Obstacle::Brick { brick, at, normal });
+                }
+            }
+            for (wall_trans, wall) in &query_walls {
+                if let Some(toi) =
+                    sweep_wall(start, motion, wall_trans.translation, wall, ball_size)
+                {
+                    let normal = wall.normal.extend(0.);
+                    meet(toi, Obstacle::Wall { normal });
+                }
+            }
+            for (paddle, player_trans, stats) in &query_player {
+                if recent.map_or(false, |hit| {
+                    hit.paddle == paddle && hit.age < PADDLE_HIT_COOLDOWN
+                }) {
+                    continue;
+                }
+                let size = turned_size(stats.size, player_trans.rotation);
+                if let Some((toi, normal)) =
+                    sweep_box(start, motion, player_trans.translation, size, ball_size)
+                {
+                    meet(toi, Obstacle::Paddle { paddle, normal });
+                }
+            }
+
+            let Some((toi, obstacle)) = first else {
+                ball_trans.translation += motion;
+                break;
+            };
+            ball_trans.translation += motion * toi;
+            let left = motion.length() * (1. - toi);
+
+            match obstacle {
+                Obstacle::Brick { brick, at, normal } => {
+                    speed.dir = reflect(speed.dir, normal);
+                    commands.entity(brick).despawn();
+                    broken.push(brick);
+                    bricks_broken.send(BrickBroken { ball, brick: at });
+                }
+                Obstacle::Wall { normal } => {
+                    speed.dir = reflect(speed.dir, normal);
+                    wall_hits.send(BallHitWall {
+                        ball,
+                        at: ball_trans.translation,
+                        normal,
+                    });
+                }
+                Obstacle::Paddle { paddle, normal } => {
+                    let Ok((_, player_trans, stats)) = query_player.get(paddle) else {
+                        break;
+                    };
+                    speed.dir = paddle_bounce(
+                        speed.dir,
+                        normal,
+                        ball_trans.translation,
+                        player_trans.translation,
+                        turned_size(stats.size, player_trans.rotation),
+                        MAX_BOUNCE_ANGLE,
+                    );
+                    let hit = LastPaddleHit { paddle, age: 0. };
+                    recent = Some(hit);
+                    match &mut last_hit {
+                        Some(last_hit) => **last_hit = hit,
+                        None => {
+                            commands.entity(ball).insert(hit);
+                        }
+                    }
+                    paddle_hits.send(BallHitPaddle {
+                        ball,
+                        paddle,
+                        at: ball_trans.translation,
+                        normal,
+                    });
+                }
+            }
+            travel.0 = speed.dir.normalize_or_zero() * left;
+        }
+        // a ball still bouncing after the last sweep stays where it last
+        // touched rather than jumping the rest of the way
+        travel.0 = Vec3::ZERO;
+    }
+}
+
+// runs after the bounces have turned the ball around, so pushing it back to
+// the surface doesn't stop it bouncing
+fn depenetrate_balls(
+    mut query_ball: Query<&mut Transform, With<Ball>>,
+    query_player: Query<(&Transform, &PaddleStats), (With<Paddle>, Without<Ball>)>,
+    query_walls: Query<(&Transform, &Collider), (With<Wall>, Without<Ball>)>,
+    tunables: Res<Tunables>,
+) {
+    let ball_size = tunables.ball_size();
+    for mut ball in &mut query_ball {
+        // walls last, so a ball squeezed against one stays in the arena
+        for (paddle, stats) in &query_player {
+            let size = turned_size(stats.size, paddle.rotation);
+            if let Some(pushed) =
+                push_out_of_paddle(ball.translation, paddle.translation, size, ball_size)
+            {
+                ball.translation = pushed;
+            }
+        }
+        for (wall_trans, wall) in &query_walls {
+            if let Some(pushed) =
+                push_out_of_wall(ball.translation, wall_trans.translation, wall, ball_size)
+            {
+                ball.translation = pushed;
+            }
+        }
+    }
+}
+
+fn out_of_bounds(
+    query: Query<(Entity, &Transform), With<Ball>>,
+    mut goals: EventWriter<BallOutOfBounds>,
+    arena: Res<Arena>,
+    tunables: Res<Tunables>,
+) {
+    let ball_size = tunables.ball_size();
+    for (ball, transform) in &query {
+        if let Some(goal) = crossed_goal(&arena, transform.translation, ball_size) {
+            goals.send(BallOutOfBounds {
+                ball,
+                at: transform.translation,
+                goal,
+            });
+        }
+    }
+}
+
+/// The paddle to serve after a goal at `goal`: the player's there nearest
+/// the goal line, or with nobody there the first goal's.
+fn server<'a>(
+    arena: &Arena,
+    goal: usize,
+    paddles: impl IntoIterator<
+            Item = (
+                Entity,
+                &'a Transform,
+                &'a PaddleStats,
+                &'a Side,
+                Option<&'a Player>,
+            ),
+        > + Copy,
+) -> Option<Entity> {
+    let nearest = |goal: usize| {
+        let line = arena.edge(arena.goals[goal]);
+        paddles
+            .into_iter()
+            .filter(|(_, _, _, side, player)| side.0 == goal && player.is_some())
+            .min_by(|a, b| {
+                let distance =
+                    |transform: &Transform| line.signed_distance(transform.translation.truncate());
+                distance(a.1).total_cmp(&distance(b.1))
+            })
+            .map(|(entity, ..)| entity)
+    };
+    nearest(goal).or_else(|| nearest(0))
+}
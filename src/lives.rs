@@ -0,0 +1,156 @@
+//! Lives, for single play: with [`Tunables::lives`] set, a miss at the
+//! player's goal costs a life instead of giving the walls a point, and the
+//! run ends when the last one goes. The lives left show as hearts under the
+//! scoreboard. Survival, where one miss ends it anyway, leaves them off.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena, engine::ScheduleSetup, mutator::start_mutators, pause::starting_match,
+    win_meter::METER_SIZE, AppState, Tunables,
+};
+
+const HEART: &str = "\u{2665}";
+const HEART_COLOR: Color = Color::rgb(0.9, 0.15, 0.2);
+const LOST_HEART_COLOR: Color = Color::rgba(1., 1., 1., 0.2);
+
+pub struct LivesPlugin;
+
+impl Plugin for LivesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lives>()
+            .add_systems_on_enter(
+                AppState::Playing,
+                start_lives.after(start_mutators).run_if(starting_match),
+            )
+            .add_systems_during(
+                AppState::Playing,
+                (show_lives, end_run).distributive_run_if(resource_changed::<Lives>()),
+            );
+    }
+}
+
+/// The lives of the run under way.
+#[derive(Resource, Default, Clone)]
+pub struct Lives {
+    /// Whether this match is played on lives at all.
+    pub on: bool,
+    pub remaining: u32,
+}
+
+impl Lives {
+    /// Takes a life for a miss at `goal`, if it's the player's and the run
+    /// is on lives; false leaves the miss to score as usual.
+    pub fn take(&mut self, goal: usize) -> bool {
+        if !self.on || goal != 0 {
+            return false;
+        }
+        self.remaining = self.remaining.saturating_sub(1);
+        true
+    }
+}
+
+#[derive(Component)]
+struct LivesHud;
+
+/// One heart, the `.0`th life.
+#[derive(Component)]
+struct Heart(u32);
+
+fn start_lives(
+    mut commands: Commands,
+    mut lives: ResMut<Lives>,
+    query: Query<Entity, With<LivesHud>>,
+    (tunables, arena, asset_server): (Res<Tunables>, Res<Arena>, Res<AssetServer>),
+) {
+    let on = tunables.lives > 0 && !tunables.survival && arena.goals.len() == 1;
+    *lives = Lives {
+        on,
+        remaining: tunables.lives,
+    };
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !on {
+        return;
+    }
+
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                // under the scoreboard, and the win meter in it
+                position: UiRect {
+                    top: Val::Px(if tunables.win_meter {
+                        70. + 2. * METER_SIZE.y
+                    } else {
+                        70.
+                    }),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(LivesHud)
+        .with_children(|parent| {
+            for life in 0..tunables.lives {
+                parent.spawn((
+                    TextBundle::from_section(
+                        HEART,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 28.,
+                            color: HEART_COLOR,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::horizontal(Val::Px(3.)),
+                        ..default()
+                    }),
+                    Heart(life),
+                ));
+            }
+        });
+}
+
+// also on a rewind, which can hand a life back
+fn show_lives(mut query: Query<(&mut Text, &Heart)>, lives: Res<Lives>) {
+    for (mut text, heart) in &mut query {
+        text.sections[0].style.color = if heart.0 < lives.remaining {
+            HEART_COLOR
+        } else {
+            LOST_HEART_COLOR
+        };
+    }
+}
+
+fn end_run(lives: Res<Lives>, mut next_state: ResMut<NextState<AppState>>) {
+    if lives.on && lives.remaining == 0 {
+        next_state.set(AppState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_players_misses_cost_a_life() {
+        let mut lives = Lives {
+            on: true,
+            remaining: 2,
+        };
+        assert!(!lives.take(1));
+        assert!(lives.take(0));
+        assert!(lives.take(0));
+        assert!(lives.take(0));
+        assert_eq!(lives.remaining, 0);
+
+        let mut off = Lives::default();
+        assert!(!off.take(0));
+    }
+}
@@ -0,0 +1,145 @@
+//! The loading screen the game opens on. Everything under `assets` that has
+//! a loader (sounds, fonts, scenes and the RON data files) starts loading
+//! at once and is held for the whole run, so nothing is read from disk for
+//! the first time mid-match; a bar fills as it arrives, and the splash
+//! follows once all of it has loaded or failed to.
+
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{despawn_screen, engine::ScheduleSetup, AppState};
+
+const BAR_SIZE: Vec2 = Vec2::new(300., 12.);
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Loading, start_loading)
+            .add_systems_during(AppState::Loading, track_loading)
+            .add_systems_on_exit(AppState::Loading, despawn_screen::<LoadingScreen>);
+    }
+}
+
+/// Every asset loaded up front, kept so none of them is ever dropped.
+#[derive(Resource, Default)]
+pub struct Preloaded(pub Vec<HandleUntyped>);
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingBar;
+
+/// The share of `states` that has finished, failed ones included so a
+/// missing file can't hold the game up.
+fn progress(states: &[LoadState]) -> f32 {
+    if states.is_empty() {
+        return 1.;
+    }
+    let done = states
+        .iter()
+        .filter(|state| matches!(state, LoadState::Loaded | LoadState::Failed))
+        .count();
+    done as f32 / states.len() as f32
+}
+
+fn start_loading(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = asset_server.load_folder("").unwrap_or_else(|err| {
+        error!("couldn't list the assets to preload: {err}");
+        Vec::new()
+    });
+    commands.insert_resource(Preloaded(handles));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::BLACK.into(),
+                ..default()
+            },
+            LoadingScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "LOADING",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 24.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(12.)),
+                    ..default()
+                }),
+            );
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_SIZE.x), Val::Px(BAR_SIZE.y)),
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                        LoadingBar,
+                    ));
+                });
+        });
+}
+
+fn track_loading(
+    mut query: Query<&mut Style, With<LoadingBar>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    preloaded: Res<Preloaded>,
+    asset_server: Res<AssetServer>,
+) {
+    let states: Vec<_> = preloaded
+        .0
+        .iter()
+        .map(|handle| asset_server.get_load_state(handle))
+        .collect();
+    let done = progress(&states);
+    for mut style in &mut query {
+        style.size.width = Val::Percent(done * 100.);
+    }
+    if done >= 1. {
+        next_state.set(AppState::Splash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_count_as_done() {
+        assert_eq!(progress(&[]), 1.);
+        assert_eq!(
+            progress(&[
+                LoadState::Loaded,
+                LoadState::Loading,
+                LoadState::Failed,
+                LoadState::NotLoaded
+            ]),
+            0.5
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Low-power menus. Outside a match nothing on screen moves much, so rather
+//! than redrawing as fast as the GPU allows, the game waits for input and
+//! redraws at most [`MENU_FPS`] times a second, drops to once a second while
+//! another app has focus, and turns multisampling off. `Playing` goes back to
+//! full rate and full quality, focused or not, so a mini-mode rally keeps
+//! going in the corner of the screen.
+//!
+//! A minimized window reports no size and isn't drawn at all; a match under
+//! way then keeps updating at a steady 60 a second rather than spinning.
+
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    window::PrimaryWindow,
+    winit::{UpdateMode, WinitSettings},
+};
+
+use crate::AppState;
+
+pub const MENU_FPS: f64 = 30.;
+// a hidden match has nothing to draw, but the ball still has to move smoothly
+const HIDDEN_MATCH_FPS: f64 = 60.;
+// while nobody's looking, just often enough for timers to keep ticking
+const BACKGROUND_WAIT: Duration = Duration::from_secs(1);
+
+pub struct LowPowerPlugin;
+
+impl Plugin for LowPowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(set_power_mode);
+    }
+}
+
+/// How often to update in `state`, with the window `minimized` or not.
+fn winit_settings(state: AppState, minimized: bool) -> WinitSettings {
+    let every = |fps: f64| Duration::from_secs_f64(1. / fps);
+    let background = || UpdateMode::ReactiveLowPower {
+        max_wait: BACKGROUND_WAIT,
+    };
+    match (state, minimized) {
+        (AppState::Playing, false) => WinitSettings::game(),
+        (AppState::Playing, true) => WinitSettings {
+            focused_mode: UpdateMode::Reactive {
+                max_wait: every(HIDDEN_MATCH_FPS),
+            },
+            unfocused_mode: UpdateMode::Reactive {
+                max_wait: every(HIDDEN_MATCH_FPS),
+            },
+            ..default()
+        },
+        (_, false) => WinitSettings {
+            focused_mode: UpdateMode::Reactive {
+                max_wait: every(MENU_FPS),
+            },
+            unfocused_mode: background(),
+            ..default()
+        },
+        (_, true) => WinitSettings {
+            focused_mode: background(),
+            unfocused_mode: background(),
+            ..default()
+        },
+    }
+}
+
+fn set_power_mode(
+    mut commands: Commands,
+    mut msaa: ResMut<Msaa>,
+    mut last: Local<Option<(AppState, bool)>>,
+    state: Res<State<AppState>>,
+    query_window: Query<&Window, With<PrimaryWindow>>,
+) {
+    let minimized = query_window
+        .get_single()
+        .map_or(false, |window| window.physical_width() == 0);
+    if *last == Some((state.0, minimized)) {
+        return;
+    }
+    *last = Some((state.0, minimized));
+
+    commands.insert_resource(winit_settings(state.0, minimized));
+    let samples = if state.0 == AppState::Playing {
+        Msaa::Sample4
+    } else {
+        Msaa::Off
+    };
+    // switching rebuilds the render pipelines, so only on an actual change
+    if *msaa != samples {
+        *msaa = samples;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_a_match_runs_flat_out() {
+        let playing = winit_settings(AppState::Playing, false);
+        assert!(matches!(playing.focused_mode, UpdateMode::Continuous));
+        assert!(matches!(playing.unfocused_mode, UpdateMode::Continuous));
+
+        let menu = winit_settings(AppState::Menu, false);
+        let UpdateMode::Reactive { max_wait } = menu.focused_mode else {
+            panic!("menus wait for input");
+        };
+        assert!((max_wait.as_secs_f64() - 1. / MENU_FPS).abs() < 1e-6);
+        assert!(matches!(
+            menu.unfocused_mode,
+            UpdateMode::ReactiveLowPower { .. }
+        ));
+
+        let hidden = winit_settings(AppState::Paused, true);
+        assert!(matches!(
+            hidden.focused_mode,
+            UpdateMode::ReactiveLowPower { .. }
+        ));
+    }
+}
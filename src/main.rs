@@ -1,220 +1,155 @@
 //! Shows how to render simple primitive shapes with a single color.
 
+use std::time::Duration;
+
 use bevy::{
+    app::{ScheduleRunnerPlugin, ScheduleRunnerSettings},
     diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin},
     prelude::*,
-    sprite::{collide_aabb::collide, MaterialMesh2dBundle},
+    window::ExitCondition,
+    winit::WinitPlugin,
 };
-use rand::{thread_rng, Rng};
-
-const DEFAULT_SPEED: f32 = 50.;
-const BALL_SIZE: Vec2 = Vec2::new(10., 10.);
-const PLAYER_SIZE: Vec2 = Vec2::new(100., 10.);
-const BALL_INITIAL: Vec3 = Vec3::new(0., -250., 0.);
+use pong_rs::{arena::ArenaFile, ControlMode, Difficulty, GamePlugin, MatchCode, StreamerSettings};
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugin(LogDiagnosticsPlugin::default())
-        .add_plugin(FrameTimeDiagnosticsPlugin::default())
-        .init_resource::<GameState>()
-        .add_startup_system(setup)
-        .add_system(move_ball)
-        .add_system(bounce_ball)
-        .add_system(out_of_bounds)
-        .add_system(keyboard_input)
-        .run();
-}
-
-#[derive(Resource, Default)]
-struct GameState {
-    score: (u32, u32),
-}
-
-#[derive(Component)]
-struct Player {
-    name: String,
-}
-
-#[derive(Component)]
-struct Speed {
-    dir: Vec3,
-    speed_multiplier: f32,
-}
-
-impl Default for Speed {
-    fn default() -> Self {
-        Self {
-            dir: Default::default(),
-            speed_multiplier: DEFAULT_SPEED,
-        }
-    }
-}
-
-#[derive(Component, Default)]
-struct Ball;
-
-#[derive(Component)]
-struct Wall;
-
-// spawns ball and player
-fn setup(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<ColorMaterial>>,
-) {
-    let mut rng = thread_rng();
-
-    commands.spawn(Camera2dBundle::default());
-
-    let mut spawn_wall = |dim_x: f32, dim_y: f32, translation: Vec3| {
-        commands.spawn((
-            MaterialMesh2dBundle {
-                mesh: meshes.add(shape::Box::new(dim_x, dim_y, 0.).into()).into(),
-                material: materials.add(ColorMaterial::from(Color::WHITE)),
-                transform: Transform::from_translation(translation),
-                ..default()
-            },
-            Wall,
-        ));
+    // `--code <code>` plays the match another game-over screen showed, in place
+    // of the arena, opponent, paddle and seed flags below
+    let shared = arg_value("--code").map(|code| {
+        MatchCode::decode(&code).unwrap_or_else(|| {
+            eprintln!("{code} isn't a match code");
+            std::process::exit(2);
+        })
+    });
+
+    // `--arena <name>` plays the arena in `assets/arenas/<name>.ron` (square,
+    // hex, triangle, classic or one of your own) dressed by its layout scene;
+    // classic has goals on the left and right
+    let arena_name = match &shared {
+        Some(code) => code.arena.clone(),
+        None => arg_value("--arena").unwrap_or_else(|| "square".to_owned()),
+    };
+    let (arena_file, mut arena) = ArenaFile::load(&arena_name)
+        .and_then(|file| {
+            let arena = file.arena()?;
+            Ok((file, arena))
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            std::process::exit(2);
+        });
+
+    // `--two-player` makes the far edge a second goal with its own paddle on
+    // A/D, and `--bot <easy|normal|hard>` puts the computer there instead;
+    // arenas with an odd number of sides have no far edge
+    let (bot, two_player) = match &shared {
+        Some(code) => (code.bot, code.two_player),
+        None => (
+            arg_value("--bot").and_then(|name| Difficulty::from_name(&name)),
+            std::env::args().any(|arg| arg == "--two-player"),
+        ),
     };
 
-    spawn_wall(600., 10., Vec3::new(0., 300., 0.));
-
-    spawn_wall(10., 600., Vec3::new(300., 0., 0.));
-
-    spawn_wall(10., 600., Vec3::new(-300., 0., 0.));
-
-    // spawning ball
-    commands.spawn((
-        (MaterialMesh2dBundle {
-            mesh: meshes.add(shape::Circle::new(10.).into()).into(),
-            material: materials.add(ColorMaterial::from(Color::RED)),
-            transform: Transform::from_translation(BALL_INITIAL),
-            ..default()
-        }),
-        Ball,
-        Speed {
-            dir: Vec3::new(rng.gen_range(-10.0..10.0), rng.gen_range(0.0..10.0), 0.),
-            speed_multiplier: DEFAULT_SPEED,
-        },
-    ));
-
-    // spawning player
-    commands.spawn((
-        MaterialMesh2dBundle {
-            mesh: meshes.add(shape::Box::new(100., 10., 0.).into()).into(),
-            material: materials.add(ColorMaterial::from(Color::BLACK)),
-            transform: Transform::from_translation(Vec3::new(0., -290., 0.)),
-            ..default()
-        },
-        Player {
-            name: "Player".to_owned(),
-        },
-    ));
-}
-
-fn move_ball(mut query: Query<(&mut Transform, &mut Speed), With<Ball>>, timer: Res<Time>) {
-    for (mut transform, mut speed) in &mut query {
-        transform.translation += speed.dir * timer.delta_seconds() * speed.speed_multiplier;
-        speed.speed_multiplier = DEFAULT_SPEED;
+    // `--tournament <name,name,...>` runs a round robin between those entrants,
+    // two players at a time
+    let tournament = arg_value("--tournament")
+        .map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_owned())
+                .filter(|name| !name.is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|entrants| entrants.len() >= 2);
+    if bot.is_some() || tournament.is_some() || two_player {
+        arena = arena.with_opposite_goal();
     }
-}
-
-fn get_wall_size(wall_trans: &Transform) -> Vec2 {
-    if wall_trans.translation.x == 0. {
-        Vec2::new(600., 10.)
-    } else {
-        Vec2::new(10., 600.)
-    }
-}
-
-fn bounce_ball(
-    mut query_ball: Query<(&Transform, &mut Speed), With<Ball>>,
-    query_walls: Query<&Transform, With<Wall>>,
-    query_player: Query<&Transform, With<Player>>,
-) {
-    for (ball_trans, mut speed) in &mut query_ball {
-        for wall_trans in &query_walls {
-            let wall_size = get_wall_size(wall_trans);
-            let collided = collide(
-                wall_trans.translation,
-                wall_size,
-                ball_trans.translation,
-                BALL_SIZE,
-            );
 
-            if collided.is_some() {
-                let (x, y) = (wall_trans.translation.x, wall_trans.translation.y);
-
-                let wall_normal = if x == 0. && y == 300. {
-                    Vec3::NEG_Y
-                } else if x == 0. && y == -300. {
-                    Vec3::Y
-                } else if x == 300. && y == 0. {
-                    Vec3::NEG_X
-                } else {
-                    Vec3::X
-                };
-
-                speed.dir = speed.dir - (2. * speed.dir.dot(wall_normal)) * wall_normal;
-                speed.speed_multiplier *= 2.;
-                break;
-            }
-        }
-
-        for player_trans in &query_player {
-            let collided = collide(
-                player_trans.translation,
-                PLAYER_SIZE,
-                ball_trans.translation,
-                BALL_SIZE,
-            );
+    // `--paddles <single|mirrored|offset|lanes>` picks how many paddles the player drives
+    let control_mode = match &shared {
+        Some(code) => code.paddles,
+        None => arg_value("--paddles")
+            .and_then(|name| ControlMode::from_name(&name))
+            .unwrap_or_default(),
+    };
 
-            if collided.is_some() {
-                let normal = Vec3::Y;
-                speed.dir = speed.dir - (2. * speed.dir.dot(normal)) * normal;
-                speed.speed_multiplier *= 2.;
-            }
+    // `--training` turns on practice tools (F5 save-state, F6 restore, F2 scenarios)
+    let training = std::env::args().any(|arg| arg == "--training");
+
+    // `--streamer` insets the HUD by `--safe-margin <px>` and guards tool hotkeys
+    // behind Ctrl+Shift; `--score-window` adds a transparent score-only window
+    let streamer = std::env::args().any(|arg| arg == "--streamer").then(|| {
+        let defaults = StreamerSettings::default();
+        StreamerSettings {
+            safe_margin: arg_value("--safe-margin")
+                .and_then(|px| px.parse().ok())
+                .unwrap_or(defaults.safe_margin),
+            score_window: std::env::args().any(|arg| arg == "--score-window"),
         }
-    }
-}
-
-fn out_of_bounds(mut query: Query<&mut Transform, With<Ball>>, mut game_state: ResMut<GameState>) {
-    for mut ball in &mut query {
-        let collided = collide(
-            ball.translation,
-            BALL_SIZE,
-            Vec3::new(0., -300., 0.),
-            Vec2::new(600., 10.),
-        );
+    });
+
+    // `--seed <n>` replays a match's serves, like the seed on a saved scorecard
+    let seed = match &shared {
+        Some(code) => code.seed,
+        None => arg_value("--seed")
+            .and_then(|seed| seed.parse().ok())
+            .unwrap_or_else(rand::random),
+    };
+    let code = MatchCode {
+        seed,
+        arena: arena_name.clone(),
+        two_player: two_player && bot.is_none(),
+        bot,
+        paddles: control_mode,
+        mutators: shared.map_or(0, |code| code.mutators),
+    };
 
-        // place the ball back to the starting position
-        if collided.is_some() {
-            ball.translation = BALL_INITIAL;
-            game_state.score.0 += 1
-        }
+    // `--bindings <path>` reads the keys from another file than bindings.ron,
+    // which is written with the defaults on first run
+    let bindings = arg_value("--bindings").unwrap_or_else(|| "bindings.ron".to_owned());
+
+    // `--tick-rate <hz>` steps the ball physics that many times a second
+    let tick_rate = arg_value("--tick-rate")
+        .and_then(|rate| rate.parse().ok())
+        .filter(|&rate: &f32| rate > 0.);
+
+    let mut app = App::new();
+    // `--headless` runs without a window at 60 updates a second, for driving
+    // the game over the `remote` feature's commands
+    if std::env::args().any(|arg| arg == "--headless") {
+        app.add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .disable::<WinitPlugin>(),
+        )
+        .insert_resource(ScheduleRunnerSettings::run_loop(Duration::from_secs_f64(
+            1. / 60.,
+        )))
+        .add_plugin(ScheduleRunnerPlugin);
+    } else {
+        app.add_plugins(DefaultPlugins);
     }
+    app.add_plugin(LogDiagnosticsPlugin::default())
+        .add_plugin(FrameTimeDiagnosticsPlugin)
+        .add_plugin(GamePlugin {
+            arena,
+            control_mode,
+            layout: arena_file.layout(&arena_name),
+            training,
+            streamer,
+            bot,
+            seed: Some(seed),
+            tournament,
+            bindings: Some(bindings.into()),
+            code: Some(code),
+            tick_rate,
+        })
+        .run();
 }
 
-fn keyboard_input(
-    mut query: Query<&mut Transform, With<Player>>,
-    keyboard_input: Res<Input<KeyCode>>,
-) {
-    if keyboard_input.pressed(KeyCode::Left) {
-        for mut transform in &mut query {
-            if (transform.translation - Vec3::new(25., 0., 0.) - Vec3::new(50., 0., 0.)).x >= -325.
-            {
-                transform.translation -= Vec3::new(10., 0., 0.)
-            }
-        }
-    }
-
-    if keyboard_input.pressed(KeyCode::Right) {
-        for mut transform in &mut query {
-            if (transform.translation + Vec3::new(25., 0., 0.) + Vec3::new(50., 0., 0.)).x <= 325. {
-                transform.translation += Vec3::new(10., 0., 0.)
-            }
-        }
-    }
+fn arg_value(flag: &str) -> Option<String> {
+    std::env::args().skip_while(|arg| arg != flag).nth(1)
 }
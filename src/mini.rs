@@ -0,0 +1,121 @@
+//! Mini mode: F1 shrinks the window to a small borderless square that stays
+//! on top of other windows, zooms the view out to fit the whole arena and
+//! drops the HUD, so a rally can keep going in a corner of the screen. F1
+//! again puts the window back how it was.
+//!
+//! F1 is a focused-window toggle, not a system-wide hotkey: it's read like
+//! any other key, so coming back from another app means clicking the mini
+//! window before pressing it. For the same reason the window doesn't get
+//! keys while another app has focus, so the idle pause holds off in mini
+//! mode.
+
+use bevy::{
+    prelude::*,
+    render::view::RenderLayers,
+    window::{PrimaryWindow, WindowLevel, WindowResolution},
+};
+
+use crate::{arena::Arena, hotkey::Hotkeys};
+
+/// Only heard while the game's window has focus.
+pub const MINI_KEY: KeyCode = KeyCode::F1;
+
+// logical size of the window while mini
+const MINI_SIZE: f32 = 240.;
+// room around the arena so the walls aren't flush with the window edge
+const MINI_MARGIN: f32 = 1.1;
+
+pub struct MiniPlugin;
+
+impl Plugin for MiniPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MiniMode>().add_system(toggle_mini);
+    }
+}
+
+/// The window as it was before going mini, while mini.
+#[derive(Resource, Default)]
+pub struct MiniMode {
+    restore: Option<(WindowResolution, WindowLevel, bool)>,
+}
+
+impl MiniMode {
+    pub fn active(&self) -> bool {
+        self.restore.is_some()
+    }
+}
+
+// how far the arena reaches from the origin along either axis
+fn arena_extent(arena: &Arena) -> f32 {
+    arena
+        .vertices
+        .iter()
+        .map(|vertex| vertex.abs().max_element())
+        .fold(0., f32::max)
+}
+
+/// Projection scale that fits an arena reaching `extent` from the origin in
+/// every direction into a window `size` wide.
+fn fit_scale(extent: f32, size: f32) -> f32 {
+    2. * extent * MINI_MARGIN / size
+}
+
+fn toggle_mini(
+    mut commands: Commands,
+    mut mini: ResMut<MiniMode>,
+    mut query_window: Query<&mut Window, With<PrimaryWindow>>,
+    mut query_camera: Query<(Entity, &mut OrthographicProjection), Without<RenderLayers>>,
+    arena: Res<Arena>,
+    hotkeys: Hotkeys,
+) {
+    if !hotkeys.just_pressed(MINI_KEY) {
+        return;
+    }
+    let Ok(mut window) = query_window.get_single_mut() else {
+        return;
+    };
+
+    match mini.restore.take() {
+        Some((resolution, level, decorations)) => {
+            window.resolution = resolution;
+            window.window_level = level;
+            window.decorations = decorations;
+            for (entity, mut projection) in &mut query_camera {
+                projection.scale = 1.;
+                commands.entity(entity).remove::<UiCameraConfig>();
+            }
+        }
+        None => {
+            mini.restore = Some((
+                window.resolution.clone(),
+                window.window_level,
+                window.decorations,
+            ));
+            window.resolution.set(MINI_SIZE, MINI_SIZE);
+            window.window_level = WindowLevel::AlwaysOnTop;
+            window.decorations = false;
+
+            let extent = arena_extent(&arena);
+            for (entity, mut projection) in &mut query_camera {
+                projection.scale = fit_scale(extent, MINI_SIZE);
+                commands
+                    .entity(entity)
+                    .insert(UiCameraConfig { show_ui: false });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_whole_arena_fits_the_mini_window() {
+        for name in ["square", "hex", "triangle", "classic"] {
+            let extent = arena_extent(&Arena::from_name(name).unwrap());
+            let visible = MINI_SIZE * fit_scale(extent, MINI_SIZE);
+            assert!(visible > 2. * extent, "{name}");
+        }
+    }
+}
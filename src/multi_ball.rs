@@ -0,0 +1,86 @@
+//! Multi-ball, for the mutator that turns it on: every so many returns of a
+//! rally another ball joins from the middle of the field, up to as many as
+//! the field holds. A goal only takes out the ball that went in, and the
+//! point's last ball is served again as usual.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena,
+    callout::spawn_callout,
+    engine::ScheduleSetup,
+    paddle::{Paddle, PaddleStats},
+    paddle_boxes,
+    serve::{serve_spots, MatchPhase, ServePattern},
+    spawn_ball,
+    stats::MatchStats,
+    AppState, Ball, BallAssets, GameRng, Tunables, MAX_BALLS,
+};
+
+pub struct MultiBallPlugin;
+
+impl Plugin for MultiBallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(
+            AppState::Playing,
+            add_balls
+                .run_if(in_state(MatchPhase::Rally))
+                .run_if(resource_exists::<BallAssets>()),
+        );
+    }
+}
+
+/// Whether reaching `rally` returns brings on another ball, at one every `every`.
+fn adds_ball(rally: u32, every: u32) -> bool {
+    every > 0 && rally > 0 && rally % every == 0
+}
+
+// goes by the stats' rally count, so it's the same rally the HUD shows
+fn add_balls(
+    mut commands: Commands,
+    mut seen: Local<u32>,
+    query_ball: Query<(), With<Ball>>,
+    query_player: Query<(&Transform, &PaddleStats), With<Paddle>>,
+    stats: Res<MatchStats>,
+    (ball_assets, asset_server, arena, tunables, mut rng): (
+        Res<BallAssets>,
+        Res<AssetServer>,
+        Res<Arena>,
+        Res<Tunables>,
+        ResMut<GameRng>,
+    ),
+) {
+    if stats.rally == *seen {
+        return;
+    }
+    *seen = stats.rally;
+    if !adds_ball(stats.rally, tunables.extra_ball_every) || query_ball.iter().len() >= MAX_BALLS {
+        return;
+    }
+
+    let spots = serve_spots(
+        ServePattern::Center,
+        &arena,
+        0,
+        0,
+        &paddle_boxes(&query_player),
+        tunables.ball_size(),
+        &mut rng.0,
+    );
+    for (translation, dir) in spots {
+        spawn_ball(&mut commands, &ball_assets, translation, dir);
+        spawn_callout(&mut commands, &asset_server, "EXTRA BALL!", translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_ball_joins_every_fifth_return() {
+        let added: Vec<_> = (0..=12).filter(|&rally| adds_ball(rally, 5)).collect();
+        assert_eq!(added, [5, 10]);
+        assert!(!(0..=12).any(|rally| adds_ball(rally, 0)));
+    }
+}
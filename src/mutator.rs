@@ -0,0 +1,229 @@
+//! Match mutators: named rule tweaks defined in `assets/rules.mutators.ron`
+//! that stack on top of each other. The ones toggled on the select screen are
+//! applied to the [`Tunables`] (and the fog) as the match starts, and listed in
+//! a corner of the HUD for the rest of it.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    arena::{Arena, WALL_THICKNESS},
+    engine::ScheduleSetup,
+    pause::starting_match,
+    serve::ServePattern,
+    AppState, Tunables,
+};
+
+pub const MUTATORS_PATH: &str = "rules.mutators.ron";
+
+// above the balls and pickups, below callouts
+const FOG_Z: f32 = 5.;
+
+pub struct MutatorPlugin;
+
+impl Plugin for MutatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<MutatorList>()
+            .init_asset_loader::<MutatorLoader>()
+            .init_resource::<ActiveMutators>()
+            .add_systems_on_enter(AppState::Playing, start_mutators.run_if(starting_match));
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Modifier {
+    /// Multiplies the base ball speed.
+    Speed(f32),
+    /// Multiplies the per-bounce ramp.
+    Ramp(f32),
+    /// Multiplies the paddle width.
+    PaddleSize(f32),
+    /// Multiplies the ball size.
+    BallSize(f32),
+    /// Sets the ball radius, in pixels, before any multiplier.
+    BallRadius(f32),
+    /// Sets the paddle thickness, in pixels.
+    PaddleHeight(f32),
+    /// Adds pull toward the bottom of the screen, in pixels per second squared.
+    Gravity(f32),
+    /// Hides this share of the field, counted from the far end.
+    Fog(f32),
+    /// Lays this many rows of breakout bricks across the far half.
+    Bricks(u32),
+    /// Adds a ball every this many returns of a rally.
+    MultiBall(u32),
+    /// Serves from here instead; the last one listed wins.
+    Serve(ServePattern),
+    /// Ends the match at the first goal, scored by time survived.
+    Survival,
+    /// Plays single play on this many lives.
+    Lives(u32),
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Mutator {
+    pub name: String,
+    pub description: String,
+    pub modifiers: Vec<Modifier>,
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "0f5e2c6a-3b8d-4e71-9a24-c7d1b8e36f50"]
+pub struct MutatorList {
+    pub mutators: Vec<Mutator>,
+}
+
+/// The mutators picked for this match, in the order they were listed.
+#[derive(Resource, Default)]
+pub struct ActiveMutators(pub Vec<Mutator>);
+
+/// Applies every modifier of `mutators` to `tunables`; returns the share of the
+/// field the fog hides.
+pub fn apply_mutators(mutators: &[Mutator], tunables: &mut Tunables) -> f32 {
+    let mut fog: f32 = 0.;
+    for &modifier in mutators.iter().flat_map(|mutator| &mutator.modifiers) {
+        match modifier {
+            Modifier::Speed(factor) => tunables.speed *= factor,
+            Modifier::Ramp(factor) => tunables.ramp *= factor,
+            Modifier::PaddleSize(factor) => tunables.paddle_scale *= factor,
+            Modifier::BallSize(factor) => tunables.ball_scale *= factor,
+            Modifier::BallRadius(radius) => tunables.ball_radius = radius,
+            Modifier::PaddleHeight(height) => tunables.paddle_height = height,
+            Modifier::Gravity(pull) => tunables.gravity += pull,
+            Modifier::Fog(share) => fog = fog.max(share),
+            Modifier::Bricks(rows) => tunables.brick_rows = rows,
+            Modifier::MultiBall(every) => tunables.extra_ball_every = every,
+            Modifier::Serve(pattern) => tunables.serve = pattern,
+            Modifier::Survival => tunables.survival = true,
+            Modifier::Lives(lives) => tunables.lives = lives,
+        }
+    }
+    fog.clamp(0., 1.)
+}
+
+#[derive(Component)]
+struct Fog;
+
+pub fn start_mutators(
+    mut commands: Commands,
+    mut tunables: ResMut<Tunables>,
+    active: Res<ActiveMutators>,
+    arena: Res<Arena>,
+    clear_color: Res<ClearColor>,
+    asset_server: Res<AssetServer>,
+) {
+    if active.0.is_empty() {
+        return;
+    }
+
+    let fog = apply_mutators(&active.0, &mut tunables);
+    if fog > 0. {
+        // a band across the far end, the background color so it reads as empty field
+        let goal = arena.edge(arena.goals[0]);
+        let depth = arena.depth() + WALL_THICKNESS;
+        let width = arena
+            .vertices
+            .iter()
+            .map(|&vertex| (vertex - goal.midpoint()).perp_dot(goal.normal()).abs())
+            .fold(0., f32::max)
+            * 2.
+            + WALL_THICKNESS;
+        let center = goal.midpoint() + goal.normal() * (depth - fog * depth / 2.);
+
+        let mut color = clear_color.0;
+        color.set_a(0.97);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(width, fog * depth)),
+                    ..default()
+                },
+                transform: goal.transform().with_translation(center.extend(FOG_Z)),
+                ..default()
+            },
+            Fog,
+        ));
+    }
+
+    let names: Vec<_> = active
+        .0
+        .iter()
+        .map(|mutator| mutator.name.as_str())
+        .collect();
+    commands.spawn(
+        TextBundle::from_section(
+            format!("MUTATORS\n{}", names.join("\n")),
+            TextStyle {
+                font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                font_size: 16.,
+                color: Color::WHITE,
+            },
+        )
+        .with_text_alignment(TextAlignment::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Px(10.),
+                bottom: Val::Px(10.),
+                ..default()
+            },
+            ..default()
+        }),
+    );
+}
+
+#[derive(Default)]
+struct MutatorLoader;
+
+impl AssetLoader for MutatorLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let list: MutatorList = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["mutators.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mutators_stack() {
+        let list: MutatorList =
+            ron::from_str(include_str!("../assets/rules.mutators.ron")).unwrap();
+        let mut tunables = Tunables::default();
+        let fog = apply_mutators(&list.mutators, &mut tunables);
+
+        let defaults = Tunables::default();
+        assert!(tunables.ball_scale > defaults.ball_scale);
+        assert!(tunables.ramp > defaults.ramp);
+        assert!(tunables.speed > defaults.speed);
+        assert!(tunables.paddle_scale < defaults.paddle_scale);
+        assert!(tunables.gravity > defaults.gravity);
+        assert_eq!(fog, 0.5);
+        assert_eq!(tunables.extra_ball_every, 5);
+        assert_eq!(tunables.brick_rows, 3);
+        assert!(tunables.survival);
+        assert_eq!(tunables.lives, 3);
+        assert_eq!(tunables.paddle_height, 24.);
+        assert_eq!(tunables.ball_size(), Vec2::splat(12.));
+        // the last serve listed
+        assert_eq!(tunables.serve, ServePattern::Random);
+    }
+}
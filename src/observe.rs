@@ -0,0 +1,651 @@
+//! Read-only live game state for outside tools (dashboards, stream overlays,
+//! bot frameworks, spectators), built with the `observe` feature. A
+//! WebSocket server on [`OBSERVE_ADDR`], or `PONG_OBSERVE_ADDR` if set,
+//! sends every connected client [`Update`]s as JSON text messages: a whole
+//! [`Observation`] when it joins and at least every [`KEYFRAME_INTERVAL`],
+//! and in between only what changed since the last one, or nothing at all
+//! when nothing did (in the menus, or paused). Clients start at the first of
+//! [`SEND_RATES`]; one whose socket falls behind drops to the next, slower
+//! rate, and climbs back once it keeps up, so a crowd of spectators on slow
+//! links doesn't hold up the host. Clients at the same rate share each
+//! encoded message. Text messages from clients come in as [`ClientMessage`]
+//! events, which only the `remote` feature's commands act on.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    paddle::{Paddle, Side},
+    scoreboard::points,
+    AppState, Ball, GameState, Speed,
+};
+
+pub const OBSERVE_ADDR: &str = "127.0.0.1:9001";
+/// Updates per second a client can be sent, fastest first.
+pub const SEND_RATES: [f32; 3] = [30., 10., 2.];
+/// Longest a client goes without a whole observation, in seconds, so one
+/// that's lost track catches up.
+pub const KEYFRAME_INTERVAL: f32 = 1.;
+
+// appended to the client's key for the handshake, per RFC 6455
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+// longer messages from a client drop it; commands are a few dozen bytes
+const MAX_MESSAGE: u64 = 64 * 1024;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+// messages waiting on a client's socket before it drops a rate
+const MAX_BACKLOG: usize = 4;
+// sends in a row with nothing waiting before a client climbs back a rate
+const CATCH_UP_SENDS: u32 = 30;
+
+pub struct ObservePlugin;
+
+impl Plugin for ObservePlugin {
+    fn build(&self, app: &mut App) {
+        let addr = std::env::var("PONG_OBSERVE_ADDR").unwrap_or_else(|_| OBSERVE_ADDR.to_owned());
+        let (inbox, received) = channel();
+        let observers = Observers {
+            clients: Arc::default(),
+            inbox: Mutex::new(received),
+        };
+        match TcpListener::bind(&addr) {
+            Ok(listener) => {
+                info!("observation server on ws://{addr}");
+                let clients = observers.clients.clone();
+                thread::spawn(move || accept_clients(listener, clients, inbox));
+            }
+            Err(err) => error!("couldn't listen on {addr}: {err}"),
+        }
+        app.add_event::<ClientMessage>()
+            .insert_resource(observers)
+            .insert_resource(RateTiers(SEND_RATES.map(RateTier::new).into()))
+            .add_system(receive_messages)
+            .add_system(send_observations);
+    }
+}
+
+/// One ball as observed; velocity in pixels per second.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct BallObservation {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+}
+
+/// One paddle as observed; `side` indexes `Arena::goals`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct PaddleObservation {
+    pub side: usize,
+    pub position: [f32; 2],
+}
+
+/// The whole game state, in world units with y up.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Observation {
+    /// Seconds since the game started.
+    pub elapsed: f32,
+    pub state: String,
+    /// Points for the first goal's player, then the far side's.
+    pub score: [u32; 2],
+    pub balls: Vec<BallObservation>,
+    pub paddles: Vec<PaddleObservation>,
+}
+
+/// What changed since the last update: `elapsed` always, and each other
+/// field only if it did. A ball or paddle coming or going sends the whole
+/// observation instead.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ObservationDelta {
+    pub elapsed: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<[u32; 2]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub balls: Option<Vec<BallObservation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paddles: Option<Vec<PaddleObservation>>,
+}
+
+impl ObservationDelta {
+    /// What changed from `last` to `next`, or `None` if the balls or paddles
+    /// were counted differently.
+    pub fn between(last: &Observation, next: &Observation) -> Option<Self> {
+        if last.balls.len() != next.balls.len() || last.paddles.len() != next.paddles.len() {
+            return None;
+        }
+        fn changed<T: Clone + PartialEq>(last: &T, next: &T) -> Option<T> {
+            (last != next).then(|| next.clone())
+        }
+        Some(Self {
+            elapsed: next.elapsed,
+            state: changed(&last.state, &next.state),
+            score: changed(&last.score, &next.score),
+            balls: changed(&last.balls, &next.balls),
+            paddles: changed(&last.paddles, &next.paddles),
+        })
+    }
+
+    /// Whether nothing but the time changed.
+    pub fn is_empty(&self) -> bool {
+        self.state.is_none()
+            && self.score.is_none()
+            && self.balls.is_none()
+            && self.paddles.is_none()
+    }
+}
+
+/// What a client receives, tagged `"kind": "full"` or `"kind": "delta"`.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Update {
+    Full(Observation),
+    Delta(ObservationDelta),
+}
+
+/// A connected client, numbered in the order they connected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClientId(u64);
+
+/// A text message from a client.
+pub struct ClientMessage {
+    pub client: ClientId,
+    pub text: String,
+}
+
+/// How fast a client is being sent updates, and whether it's keeping up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Interest {
+    /// Index into [`SEND_RATES`].
+    tier: usize,
+    /// Whether its next update has to be whole, having missed the last.
+    keyframe: bool,
+    caught_up: u32,
+}
+
+impl Interest {
+    fn new() -> Self {
+        Self {
+            keyframe: true,
+            ..default()
+        }
+    }
+
+    /// Moves a rate down with more than [`MAX_BACKLOG`] messages still
+    /// waiting to go, or back up after [`CATCH_UP_SENDS`] with none; `false`
+    /// if it's moved down and should skip this send.
+    fn adjust(&mut self, backlog: usize) -> bool {
+        if backlog > MAX_BACKLOG && self.tier + 1 < SEND_RATES.len() {
+            *self = Self {
+                tier: self.tier + 1,
+                ..Self::new()
+            };
+            return false;
+        }
+        self.caught_up = if backlog == 0 { self.caught_up + 1 } else { 0 };
+        if self.caught_up >= CATCH_UP_SENDS && self.tier > 0 {
+            *self = Self {
+                tier: self.tier - 1,
+                ..Self::new()
+            };
+        }
+        true
+    }
+}
+
+struct Client {
+    id: ClientId,
+    sender: Sender<Arc<Vec<u8>>>,
+    // messages sent that its writer hasn't written out yet
+    backlog: Arc<AtomicUsize>,
+    interest: Interest,
+}
+
+impl Client {
+    /// Queues `frame` for the writer; `false` once it's disconnected.
+    fn send(&self, frame: Arc<Vec<u8>>) -> bool {
+        self.backlog.fetch_add(1, Ordering::Relaxed);
+        self.sender.send(frame).is_ok()
+    }
+}
+
+type Clients = Arc<Mutex<Vec<Client>>>;
+
+#[derive(Resource)]
+pub struct Observers {
+    clients: Clients,
+    inbox: Mutex<Receiver<ClientMessage>>,
+}
+
+impl Observers {
+    /// Sends `text` to `client` alone, if it's still connected.
+    pub fn reply(&self, client: ClientId, text: &str) {
+        let clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.iter().find(|each| each.id == client) {
+            client.send(Arc::new(text_frame(text.as_bytes())));
+        }
+    }
+}
+
+/// One of [`SEND_RATES`]: when its clients are next due, and what they were
+/// last sent to tell the next update's changes from.
+struct RateTier {
+    timer: Timer,
+    last: Option<Observation>,
+    since_keyframe: f32,
+}
+
+impl RateTier {
+    fn new(rate: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(1. / rate, TimerMode::Repeating),
+            last: None,
+            since_keyframe: 0.,
+        }
+    }
+
+    /// This send's changes from the last, or `None` if it's due a whole
+    /// observation.
+    fn delta(&mut self, observation: &Observation) -> Option<ObservationDelta> {
+        self.since_keyframe += self.timer.duration().as_secs_f32();
+        let delta = match self.last.replace(observation.clone()) {
+            Some(last) if self.since_keyframe < KEYFRAME_INTERVAL => {
+                ObservationDelta::between(&last, observation)
+            }
+            _ => None,
+        };
+        if delta.is_none() {
+            self.since_keyframe = 0.;
+        }
+        delta
+    }
+}
+
+#[derive(Resource)]
+struct RateTiers(Vec<RateTier>);
+
+fn accept_clients(listener: TcpListener, clients: Clients, inbox: Sender<ClientMessage>) {
+    for (number, stream) in listener.incoming().flatten().enumerate() {
+        let client = ClientId(number as u64);
+        let (clients, inbox) = (clients.clone(), inbox.clone());
+        // the handshake blocks on the client, so it gets its own thread too
+        thread::spawn(move || {
+            let Some((mut stream, mut reader)) = handshake(stream) else {
+                return;
+            };
+            thread::spawn(move || {
+                while let Ok((opcode, payload)) = read_frame(&mut reader) {
+                    match opcode {
+                        OPCODE_TEXT => {
+                            let text = String::from_utf8_lossy(&payload).into_owned();
+                            if inbox.send(ClientMessage { client, text }).is_err() {
+                                return;
+                            }
+                        }
+                        OPCODE_CLOSE => return,
+                        // pings and the like go unanswered
+                        _ => {}
+                    }
+                }
+            });
+
+            let (sender, receiver) = channel::<Arc<Vec<u8>>>();
+            let backlog = Arc::<AtomicUsize>::default();
+            clients.lock().unwrap().push(Client {
+                id: client,
+                sender,
+                backlog: backlog.clone(),
+                interest: Interest::new(),
+            });
+            for frame in receiver {
+                if stream.write_all(&frame).is_err() {
+                    return;
+                }
+                backlog.fetch_sub(1, Ordering::Relaxed);
+            }
+        });
+    }
+}
+
+/// Reads the client's upgrade request and accepts it, handing back the
+/// stream to write to and the reader its frames continue on; `None` for
+/// anything that isn't a WebSocket upgrade.
+fn handshake(mut stream: TcpStream) -> Option<(TcpStream, BufReader<TcpStream>)> {
+    let mut key = None;
+    let mut reader = BufReader::new(stream.try_clone().ok()?);
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-key") {
+                key = Some(value.trim().to_owned());
+            }
+        }
+    }
+
+    let Some(key) = key else {
+        let _ = stream.write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+        return None;
+    };
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(&key)
+    );
+    stream.write_all(response.as_bytes()).ok()?;
+    Some((stream, reader))
+}
+
+/// One frame from a client, as its opcode and unmasked payload. Clients'
+/// messages are short enough to arrive whole, so fragments aren't pieced
+/// back together.
+fn read_frame(reader: &mut impl Read) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0; 2];
+    reader.read_exact(&mut header)?;
+    let len = match header[1] & 0x7f {
+        126 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            u64::from(u16::from_be_bytes(len))
+        }
+        127 => {
+            let mut len = [0; 8];
+            reader.read_exact(&mut len)?;
+            u64::from_be_bytes(len)
+        }
+        len => u64::from(len),
+    };
+    if len > MAX_MESSAGE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "message too long",
+        ));
+    }
+
+    let mut mask = [0; 4];
+    if header[1] & 0x80 != 0 {
+        reader.read_exact(&mut mask)?;
+    }
+    let mut payload = vec![0; len as usize];
+    reader.read_exact(&mut payload)?;
+    for (byte, mask) in payload.iter_mut().zip(mask.iter().cycle()) {
+        *byte ^= mask;
+    }
+    Ok((header[0] & 0x0f, payload))
+}
+
+/// The `Sec-WebSocket-Accept` value answering a client's `key`.
+fn accept_key(key: &str) -> String {
+    base64::encode(sha1(format!("{key}{HANDSHAKE_GUID}").as_bytes()))
+}
+
+/// A single unmasked, unfragmented text frame, as servers send them.
+fn text_frame(payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x81];
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend((len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend((len as u64).to_be_bytes());
+        }
+    }
+    frame.extend(payload);
+    frame
+}
+
+// only the handshake needs it, so it isn't worth a dependency
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [
+        0x6745_2301,
+        0xefcd_ab89,
+        0x98ba_dcfe,
+        0x1032_5476,
+        0xc3d2_e1f0,
+    ];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().unwrap());
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.into_iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ed9_eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            (a, b, c, d, e) = (next, a, b.rotate_left(30), c, d);
+        }
+        for (word, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *word = word.wrapping_add(add);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+fn receive_messages(observers: Res<Observers>, mut messages: EventWriter<ClientMessage>) {
+    messages.send_batch(observers.inbox.lock().unwrap().try_iter());
+}
+
+fn send_observations(
+    observers: Res<Observers>,
+    mut tiers: ResMut<RateTiers>,
+    query_ball: Query<(&Transform, &Speed), With<Ball>>,
+    query_paddle: Query<(&Transform, &Side), With<Paddle>>,
+    (game_state, state, time): (Res<GameState>, Res<State<AppState>>, Res<Time>),
+) {
+    let mut due = false;
+    for tier in &mut tiers.0 {
+        due |= tier.timer.tick(time.raw_delta()).just_finished();
+    }
+    if !due {
+        return;
+    }
+    let mut clients = observers.clients.lock().unwrap();
+    if clients.is_empty() {
+        return;
+    }
+
+    let observation = Observation {
+        elapsed: time.elapsed_seconds(),
+        state: format!("{:?}", state.0),
+        score: points(game_state.score),
+        balls: query_ball
+            .iter()
+            .map(|(transform, speed)| BallObservation {
+                position: transform.translation.truncate().to_array(),
+                velocity: (speed.dir * speed.speed_multiplier).truncate().to_array(),
+            })
+            .collect(),
+        paddles: query_paddle
+            .iter()
+            .map(|(transform, side)| PaddleObservation {
+                side: side.0,
+                position: transform.translation.truncate().to_array(),
+            })
+            .collect(),
+    };
+    let encode = |update: Update| {
+        serde_json::to_string(&update)
+            .ok()
+            .map(|json| Arc::new(text_frame(json.as_bytes())))
+    };
+    let Some(full) = encode(Update::Full(observation.clone())) else {
+        return;
+    };
+
+    // each due rate's changes, for the clients on it
+    let deltas: Vec<_> = tiers
+        .0
+        .iter_mut()
+        .enumerate()
+        .map(|(index, tier)| {
+            let watched = clients.iter().any(|client| client.interest.tier == index);
+            if !tier.timer.just_finished() || !watched {
+                return None;
+            }
+            Some(match tier.delta(&observation) {
+                Some(delta) if delta.is_empty() => None,
+                Some(delta) => encode(Update::Delta(delta)),
+                None => Some(full.clone()),
+            })
+        })
+        .collect();
+    // a client whose writer has stopped has disconnected
+    clients.retain_mut(|client| {
+        let Some(delta) = &deltas[client.interest.tier] else {
+            return true;
+        };
+        if !client
+            .interest
+            .adjust(client.backlog.load(Ordering::Relaxed))
+        {
+            return true;
+        }
+        let frame = if client.interest.keyframe {
+            client.interest.keyframe = false;
+            &full
+        } else if let Some(delta) = delta {
+            delta
+        } else {
+            return true;
+        };
+        client.send(frame.clone())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_the_rfc_sample_handshake() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn frames_carry_their_length() {
+        assert_eq!(text_frame(b"hi"), [0x81, 2, b'h', b'i']);
+        let long = text_frame(&[0; 300]);
+        assert_eq!(long[..4], [0x81, 126, 1, 44]);
+        assert_eq!(long.len(), 304);
+    }
+
+    #[test]
+    fn unmasks_client_frames() {
+        // the masked "Hello" from RFC 6455
+        let frame = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        let (opcode, payload) = read_frame(&mut &frame[..]).unwrap();
+        assert_eq!(opcode, OPCODE_TEXT);
+        assert_eq!(payload, b"Hello");
+    }
+
+    fn observation(score: [u32; 2]) -> Observation {
+        Observation {
+            elapsed: 1.,
+            state: "Playing".into(),
+            score,
+            balls: vec![BallObservation {
+                position: [0., 0.],
+                velocity: [1., 0.],
+            }],
+            paddles: vec![],
+        }
+    }
+
+    #[test]
+    fn deltas_carry_only_what_changed() {
+        let last = observation([0, 0]);
+        let same = ObservationDelta::between(&last, &last).unwrap();
+        assert!(same.is_empty());
+
+        let scored = ObservationDelta::between(&last, &observation([1, 0])).unwrap();
+        assert_eq!(
+            serde_json::to_string(&Update::Delta(scored)).unwrap(),
+            r#"{"kind":"delta","elapsed":1.0,"score":[1,0]}"#
+        );
+
+        // a ball more and it's the whole observation again
+        let mut split = observation([0, 0]);
+        split.balls.push(split.balls[0].clone());
+        assert_eq!(ObservationDelta::between(&last, &split), None);
+    }
+
+    #[test]
+    fn rates_start_and_settle_on_whole_observations() {
+        let mut tier = RateTier::new(SEND_RATES[1]);
+        assert_eq!(tier.delta(&observation([0, 0])), None);
+        assert!(tier.delta(&observation([0, 0])).unwrap().is_empty());
+        // and another once the interval's up
+        let sends = (KEYFRAME_INTERVAL * SEND_RATES[1]) as usize;
+        assert!((0..=sends).any(|_| tier.delta(&observation([0, 0])).is_none()));
+    }
+
+    #[test]
+    fn clients_that_fall_behind_slow_down_until_they_catch_up() {
+        let mut interest = Interest::new();
+        assert!(!interest.adjust(MAX_BACKLOG + 1));
+        assert_eq!(interest.tier, 1);
+        assert!(interest.keyframe);
+
+        interest.keyframe = false;
+        for _ in 1..CATCH_UP_SENDS {
+            assert!(interest.adjust(0));
+        }
+        assert_eq!(interest.tier, 1);
+        assert!(interest.adjust(0));
+        assert_eq!(interest.tier, 0);
+        assert!(interest.keyframe);
+    }
+}
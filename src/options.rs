@@ -0,0 +1,260 @@
+//! The options screen, off the main menu: the points to win a game, the
+//! games in a match, ball speed and size, paddle width and thickness, lives
+//! for single play, the graze assist, the win meter, whether two players
+//! change ends, the idle pause and whether the first player steers with the
+//! mouse, each stepped with Left/Right. They change the [`Tunables`] the
+//! next match starts from. Key Bindings below them opens the screen for
+//! rebinding keys.
+
+use std::ops::RangeInclusive;
+
+use bevy::prelude::*;
+
+use crate::{
+    despawn_screen,
+    engine::ScheduleSetup,
+    focus::{FocusEvent, Focusable},
+    AppState, Tunables,
+};
+
+pub struct OptionsPlugin;
+
+impl Plugin for OptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Options, spawn_options)
+            .add_systems_during(AppState::Options, adjust_options)
+            .add_systems_on_exit(AppState::Options, despawn_screen::<OptionsScreen>);
+    }
+}
+
+#[derive(Component)]
+struct OptionsScreen;
+
+#[derive(Component)]
+struct BindingsLink;
+
+/// One adjustable line on the options screen.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+enum OptionRow {
+    PointLimit,
+    BestOf,
+    BallSpeed,
+    BallSize,
+    PaddleSize,
+    PaddleHeight,
+    Lives,
+    GrazeAssist,
+    WinMeter,
+    SideSwap,
+    IdlePause,
+    Controls,
+}
+
+impl OptionRow {
+    const ALL: [OptionRow; 12] = [
+        OptionRow::PointLimit,
+        OptionRow::BestOf,
+        OptionRow::BallSpeed,
+        OptionRow::BallSize,
+        OptionRow::PaddleSize,
+        OptionRow::PaddleHeight,
+        OptionRow::Lives,
+        OptionRow::GrazeAssist,
+        OptionRow::WinMeter,
+        OptionRow::SideSwap,
+        OptionRow::IdlePause,
+        OptionRow::Controls,
+    ];
+
+    /// Moves this row's setting `step` notches, within its range.
+    fn step(self, tunables: &mut Tunables, step: i32) {
+        let notches = step as f32;
+        match self {
+            OptionRow::PointLimit => {
+                tunables.point_limit =
+                    stepped(tunables.point_limit, step, Tunables::POINT_LIMIT_RANGE)
+            }
+            // odd, so a match can't end level
+            OptionRow::BestOf => {
+                tunables.best_of = stepped(tunables.best_of, 2 * step, Tunables::BEST_OF_RANGE)
+            }
+            OptionRow::BallSpeed => {
+                tunables.speed = (tunables.speed + 5. * notches).clamp(10., 200.)
+            }
+            OptionRow::BallSize => {
+                tunables.ball_radius = (tunables.ball_radius + notches).clamp(2., 20.)
+            }
+            OptionRow::PaddleSize => {
+                tunables.paddle_scale = (tunables.paddle_scale + 0.1 * notches).clamp(0.5, 2.)
+            }
+            OptionRow::PaddleHeight => {
+                tunables.paddle_height = (tunables.paddle_height + 2. * notches).clamp(4., 40.)
+            }
+            OptionRow::GrazeAssist => tunables.graze_assist = step > 0,
+            OptionRow::WinMeter => tunables.win_meter = step > 0,
+            OptionRow::SideSwap => tunables.side_swap = step > 0,
+            OptionRow::Controls => tunables.mouse = step > 0,
+            OptionRow::Lives => {
+                tunables.lives = stepped(tunables.lives, step, Tunables::LIVES_RANGE)
+            }
+            OptionRow::IdlePause => {
+                tunables.idle_timeout = (tunables.idle_timeout + 10. * notches).clamp(0., 120.)
+            }
+        }
+    }
+
+    fn label(self, tunables: &Tunables) -> String {
+        match self {
+            OptionRow::PointLimit if tunables.point_limit == 0 => "FIRST TO  < endless >".into(),
+            OptionRow::PointLimit => format!("FIRST TO  < {} >", tunables.point_limit),
+            OptionRow::BestOf if tunables.best_of == 1 => "MATCH  < one game >".into(),
+            OptionRow::BestOf => format!("MATCH  < best of {} >", tunables.best_of),
+            OptionRow::BallSpeed => format!("BALL SPEED  < {:.0} >", tunables.speed),
+            OptionRow::BallSize => format!("BALL RADIUS  < {:.0} >", tunables.ball_radius),
+            OptionRow::PaddleSize => format!("PADDLE WIDTH  < {:.1}x >", tunables.paddle_scale),
+            OptionRow::PaddleHeight => {
+                format!("PADDLE HEIGHT  < {:.0} >", tunables.paddle_height)
+            }
+            OptionRow::Lives if tunables.lives == 0 => "LIVES  < off >".into(),
+            OptionRow::Lives => format!("LIVES  < {} >", tunables.lives),
+            OptionRow::GrazeAssist if tunables.graze_assist => "GRAZE ASSIST  < on >".into(),
+            OptionRow::GrazeAssist => "GRAZE ASSIST  < off >".into(),
+            OptionRow::WinMeter if tunables.win_meter => "WIN METER  < on >".into(),
+            OptionRow::WinMeter => "WIN METER  < off >".into(),
+            OptionRow::SideSwap if tunables.side_swap => "CHANGE ENDS  < halfway >".into(),
+            OptionRow::SideSwap => "CHANGE ENDS  < never >".into(),
+            OptionRow::Controls if tunables.mouse => "CONTROLS  < mouse >".into(),
+            OptionRow::Controls => "CONTROLS  < keys >".into(),
+            OptionRow::IdlePause if tunables.idle_timeout == 0. => "IDLE PAUSE  < off >".into(),
+            OptionRow::IdlePause => format!("IDLE PAUSE  < {:.0}s >", tunables.idle_timeout),
+        }
+    }
+}
+
+/// `value` moved `step` notches, kept within `range`.
+fn stepped(value: u32, step: i32, range: RangeInclusive<u32>) -> u32 {
+    (value as i32 + step).clamp(*range.start() as i32, *range.end() as i32) as u32
+}
+
+fn spawn_options(mut commands: Commands, asset_server: Res<AssetServer>, tunables: Res<Tunables>) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                ..default()
+            },
+            OptionsScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "OPTIONS",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 64.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(30.)),
+                    ..default()
+                }),
+            );
+            for row in OptionRow::ALL {
+                parent.spawn((
+                    TextBundle::from_section(
+                        row.label(&tunables),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 28.,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::vertical(Val::Px(6.)),
+                        ..default()
+                    }),
+                    Focusable { adjusts: true },
+                    row,
+                ));
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    "KEY BINDINGS",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 28.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(18.)),
+                    ..default()
+                }),
+                Focusable::default(),
+                BindingsLink,
+            ));
+        });
+}
+
+fn adjust_options(
+    mut events: EventReader<FocusEvent>,
+    mut query: Query<(&OptionRow, &mut Text)>,
+    query_link: Query<(), With<BindingsLink>>,
+    mut tunables: ResMut<Tunables>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    for event in events.iter() {
+        match *event {
+            FocusEvent::Adjusted(entity, step) => {
+                if let Ok((row, mut text)) = query.get_mut(entity) {
+                    row.step(&mut tunables, step);
+                    text.sections[0].value = row.label(&tunables);
+                }
+            }
+            FocusEvent::Back => next_state.set(AppState::Menu),
+            FocusEvent::Activated(entity) => {
+                if query_link.contains(entity) {
+                    next_state.set(AppState::Controls);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_step_within_their_range() {
+        let mut tunables = Tunables::default();
+        OptionRow::BallSpeed.step(&mut tunables, 1);
+        assert_eq!(tunables.speed, 55.);
+        for _ in 0..20 {
+            OptionRow::IdlePause.step(&mut tunables, -1);
+        }
+        assert_eq!(tunables.idle_timeout, 0.);
+        assert_eq!(OptionRow::IdlePause.label(&tunables), "IDLE PAUSE  < off >");
+        OptionRow::BestOf.step(&mut tunables, 2);
+        assert_eq!(tunables.best_of, 5);
+        OptionRow::BallSize.step(&mut tunables, -10);
+        assert_eq!(tunables.ball_size(), Vec2::splat(4.));
+        OptionRow::GrazeAssist.step(&mut tunables, 1);
+        assert_eq!(
+            OptionRow::GrazeAssist.label(&tunables),
+            "GRAZE ASSIST  < on >"
+        );
+        OptionRow::WinMeter.step(&mut tunables, -1);
+        assert_eq!(OptionRow::WinMeter.label(&tunables), "WIN METER  < off >");
+    }
+}
@@ -0,0 +1,79 @@
+//! Ball ownership: each return makes the ball the returning side's until
+//! the next one, and it takes on that side's color, so with several balls
+//! in play it's clear whose each one is. [`Owner`] is what "whoever last
+//! touched it" means elsewhere, such as which side a broken brick scores
+//! for. A ball nobody has returned yet keeps the layout's look.
+
+use bevy::prelude::*;
+
+use crate::{collision::BallHitPaddle, engine::ScheduleSetup, paddle::Side, AppState, Ball};
+
+/// Each side's color, the first goal's first; sides past the last wrap.
+pub const SIDE_COLORS: [Color; 2] = [Color::rgb(0.2, 0.6, 1.), Color::rgb(1., 0.45, 0.2)];
+
+pub struct OwnershipPlugin;
+
+impl Plugin for OwnershipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OwnerMaterials>()
+            .add_systems_during(AppState::Playing, (claim_balls, tint_owned_balls).chain());
+    }
+}
+
+/// The side that last returned a ball.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Owner(pub usize);
+
+/// The color `side`'s balls take.
+pub fn side_color(side: usize) -> Color {
+    SIDE_COLORS[side % SIDE_COLORS.len()]
+}
+
+/// A material per side, made the first time one of its balls needs it.
+#[derive(Resource, Default)]
+struct OwnerMaterials(Vec<Handle<ColorMaterial>>);
+
+fn claim_balls(
+    mut commands: Commands,
+    mut hits: EventReader<BallHitPaddle>,
+    query_ball: Query<(), With<Ball>>,
+    query_side: Query<&Side>,
+) {
+    for hit in hits.iter() {
+        // a ball can have gone out of play since the tick it was returned
+        if !query_ball.contains(hit.ball) {
+            continue;
+        }
+        if let Ok(side) = query_side.get(hit.paddle) {
+            commands.entity(hit.ball).insert(Owner(side.0));
+        }
+    }
+}
+
+fn tint_owned_balls(
+    mut query: Query<(&Owner, &mut Handle<ColorMaterial>), (With<Ball>, Changed<Owner>)>,
+    mut owner_materials: ResMut<OwnerMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (owner, mut material) in &mut query {
+        let side = owner.0 % SIDE_COLORS.len();
+        while owner_materials.0.len() <= side {
+            let color = side_color(owner_materials.0.len());
+            owner_materials
+                .0
+                .push(materials.add(ColorMaterial::from(color)));
+        }
+        *material = owner_materials.0[side].clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sides_past_the_palette_wrap() {
+        assert_eq!(side_color(0), SIDE_COLORS[0]);
+        assert_eq!(side_color(3), SIDE_COLORS[1]);
+    }
+}
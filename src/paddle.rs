@@ -0,0 +1,339 @@
+//! Paddles: spawning them from the chosen archetype, steering them from the
+//! keyboard, a gamepad or the mouse, and keeping grouped paddles in formation with their
+//! player's lead paddle. An arena with a second goal gets a second player on
+//! it, steering with A/D or the second pad, or a bot when there's a
+//! [`Difficulty`].
+//! In lanes mode the steering keys go to one paddle at a time and
+//! [`LANE_KEY`] hands them to the next paddle on the same goal.
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+
+use crate::{
+    archetype::ChosenArchetype,
+    arena::Arena,
+    block::Stance,
+    bot::{Bot, Difficulty},
+    charge::{spawn_meter, Charge},
+    engine,
+    engine::ScheduleSetup,
+    flick::Flick,
+    gamepad::Steering,
+    input::InputBuffer,
+    layout::PaddleTemplate,
+    pause::starting_match,
+    serve::Serving,
+    special::{spawn_energy_bar, Energy},
+    time_scale::{scaled, TimeScale},
+    AppState, Tunables, PLAYER_SIZE,
+};
+
+// standard paddle speed in pixels per second
+pub const PADDLE_SPEED: f32 = 600.;
+
+/// Passes the steering keys to the next paddle in lanes mode. Up and Down
+/// already flick and block.
+pub const LANE_KEY: KeyCode = KeyCode::Tab;
+
+// how far up the field the second paddle of a dual or lanes mode plays
+const MIDFIELD_DISTANCE: f32 = 130.;
+// alpha of a lane paddle that isn't being steered
+const IDLE_LANE_ALPHA: f32 = 0.4;
+
+pub struct PaddlePlugin;
+
+impl Plugin for PaddlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Playing, spawn_paddles.run_if(starting_match))
+            .add_systems_during(
+                AppState::Playing,
+                (switch_lanes, steer_paddles, follow_lead_paddle).chain(),
+            )
+            .add_systems_during(AppState::Playing, scale_paddles);
+    }
+}
+
+/// How many paddles a player steers with the same input.
+#[derive(Resource, Clone, Copy, Default, Debug, PartialEq)]
+pub enum ControlMode {
+    #[default]
+    Single,
+    /// A second paddle higher up moves in the opposite direction.
+    DualMirrored,
+    /// A second paddle higher up moves in lockstep, shifted sideways.
+    DualOffset,
+    /// A goalie and a midfield paddle that move independently, steered one
+    /// at a time.
+    Lanes,
+}
+
+impl ControlMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "single" => Some(Self::Single),
+            "mirrored" => Some(Self::DualMirrored),
+            "offset" => Some(Self::DualOffset),
+            "lanes" => Some(Self::Lanes),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Component, Clone)]
+pub struct Player {
+    pub name: String,
+}
+
+/// Which goal a paddle defends, as an index into `Arena::goals`. The
+/// archetype's skills (charge, block, flick, special) are the first side's.
+#[derive(Component, Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct Side(pub usize);
+
+/// Takes its side's steering keys.
+#[derive(Component)]
+pub struct Steered;
+
+/// One of a side's independently steered paddles in lanes mode, nearest the
+/// goal first.
+#[derive(Component, Clone, Copy)]
+pub struct Lane(pub usize);
+
+/// Anything the ball bounces off like a paddle.
+#[derive(Component)]
+pub struct Paddle;
+
+#[derive(Component, Clone, Copy)]
+pub struct PaddleStats {
+    pub size: Vec2,
+    /// Movement speed relative to a standard paddle.
+    pub speed: f32,
+}
+
+/// A paddle that isn't steered directly but follows its player's lead paddle.
+#[derive(Component)]
+pub struct GroupedPaddle {
+    lead: Entity,
+    offset: Vec3,
+    mirrored: bool,
+}
+
+#[derive(Bundle)]
+pub struct PaddleBundle {
+    pub mesh: MaterialMesh2dBundle<ColorMaterial>,
+    pub paddle: Paddle,
+    pub stats: PaddleStats,
+    pub stance: Stance,
+    pub side: Side,
+}
+
+// spawns the player's paddles with the stats of their archetype
+fn spawn_paddles(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    arena: Res<Arena>,
+    control_mode: Res<ControlMode>,
+    query_template: Query<&PaddleTemplate>,
+    (archetype, asset_server, bot): (
+        Res<ChosenArchetype>,
+        Res<AssetServer>,
+        Option<Res<Difficulty>>,
+    ),
+) {
+    let archetype = &archetype.0;
+    let stats = PaddleStats {
+        size: Vec2::new(archetype.width, PLAYER_SIZE.y),
+        speed: archetype.speed,
+    };
+
+    // at the archetype's own size; scale_paddles stretches it to the match's
+    let paddle_mesh: Handle<Mesh> = meshes.add(engine::rectangle(stats.size));
+    let color = query_template
+        .get_single()
+        .map_or(Color::BLACK, |template| template.color);
+    let paddle_material = materials.add(ColorMaterial::from(color));
+    let paddle_bundle = |translation: Vec3, side: Side| PaddleBundle {
+        mesh: MaterialMesh2dBundle {
+            mesh: paddle_mesh.clone().into(),
+            material: paddle_material.clone(),
+            transform: Transform::from_translation(translation)
+                .with_rotation(arena.paddle_rotation(side.0)),
+            ..default()
+        },
+        paddle: Paddle,
+        stats,
+        stance: Stance::default(),
+        side,
+    };
+
+    let lead = commands
+        .spawn((
+            paddle_bundle(arena.paddle_spawn(), Side(0)),
+            Player {
+                name: archetype.name.clone(),
+            },
+            Charge::default(),
+            Flick::default(),
+            InputBuffer::default(),
+            Energy::default(),
+            archetype.special,
+            Steered,
+        ))
+        .with_children(|parent| spawn_meter(parent, &mut meshes, &mut materials))
+        .id();
+    spawn_energy_bar(&mut commands, &asset_server, lead, archetype.special);
+
+    let midfield = arena.edge(arena.goals[0]).normal().extend(0.) * MIDFIELD_DISTANCE;
+    if *control_mode == ControlMode::Lanes {
+        // each lane has its own material so the idle one can be dimmed
+        commands.entity(lead).insert(Lane(0));
+        let material = materials.add(ColorMaterial::from(color.with_a(IDLE_LANE_ALPHA)));
+        let mut bundle = paddle_bundle(arena.paddle_spawn() + midfield, Side(0));
+        bundle.mesh.material = material;
+        commands.spawn((bundle, Lane(1)));
+    } else if *control_mode != ControlMode::Single {
+        let goal = arena.edge(arena.goals[0]);
+        let mirrored = *control_mode == ControlMode::DualMirrored;
+        let sideways = if mirrored { 0. } else { goal.length() / 4. };
+        let offset = (arena.goal_axis(0) * sideways).extend(0.) + midfield;
+
+        commands.spawn((
+            paddle_bundle(arena.paddle_spawn() + offset, Side(0)),
+            GroupedPaddle {
+                lead,
+                offset,
+                mirrored,
+            },
+        ));
+    }
+
+    // the second player plays the same archetype, moves only
+    for goal in 1..arena.goals.len() {
+        let mut paddle = commands.spawn(paddle_bundle(arena.paddle_spawn_at(goal), Side(goal)));
+        match &bot {
+            Some(difficulty) => paddle.insert((
+                Player {
+                    name: "Bot".to_owned(),
+                },
+                Bot::new(**difficulty, goal as u64),
+            )),
+            None => paddle.insert((
+                Player {
+                    name: format!("Player {}", goal + 1),
+                },
+                Steered,
+            )),
+        };
+    }
+}
+
+// held directions move the paddle by elapsed time, so it covers the same
+// distance per second at any frame rate, and a stick half pushed at half
+// speed; the scaled width keeps it on the goal line. A serving paddle's
+// steering aims the serve instead.
+fn steer_paddles(
+    mut query: Query<
+        (
+            &mut Transform,
+            &Stance,
+            &PaddleStats,
+            &Side,
+            Option<&TimeScale>,
+        ),
+        (With<Steered>, Without<Serving>),
+    >,
+    steering: Steering,
+    arena: Res<Arena>,
+    timer: Res<Time>,
+) {
+    for (mut transform, stance, stats, side, time_scale) in &mut query {
+        let delta = scaled(timer.delta_seconds(), time_scale);
+        let reach = PADDLE_SPEED * delta * stance.move_factor() * stats.speed;
+        let direction = steering.toward(*side, transform.translation, reach);
+        if direction == 0. {
+            continue;
+        }
+
+        transform.translation = arena.slide(
+            side.0,
+            transform.translation,
+            direction * reach,
+            stats.size.x / 2.,
+        );
+    }
+}
+
+// hands the steering keys on to the next lane and dims the paddle left behind
+fn switch_lanes(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    query: Query<(Entity, &Lane, &Handle<ColorMaterial>, Option<&Steered>)>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if !keyboard_input.just_pressed(LANE_KEY) {
+        return;
+    }
+    let count = query.iter().count();
+    let Some(steered) = query
+        .iter()
+        .find_map(|(_, lane, _, steered)| steered.map(|_| lane.0))
+    else {
+        return;
+    };
+
+    let next = (steered + 1) % count;
+    for (entity, lane, material, _) in &query {
+        let active = lane.0 == next;
+        if active {
+            commands.entity(entity).insert(Steered);
+        } else {
+            commands.entity(entity).remove::<Steered>();
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material
+                .color
+                .set_a(if active { 1. } else { IDLE_LANE_ALPHA });
+        }
+    }
+}
+
+// live-tuned widths are relative to the archetype, and collisions use the scaled size
+pub fn scale_paddles(
+    mut query: Query<(&mut Transform, &mut PaddleStats)>,
+    archetype: Res<ChosenArchetype>,
+    tunables: Res<Tunables>,
+) {
+    for (mut transform, mut stats) in &mut query {
+        transform.scale.x = tunables.paddle_scale;
+        transform.scale.y = tunables.paddle_height / PLAYER_SIZE.y;
+        stats.size = Vec2::new(
+            archetype.0.width * tunables.paddle_scale,
+            tunables.paddle_height,
+        );
+    }
+}
+
+// keeps grouped paddles in formation with their lead, without leaving the goal line's span
+fn follow_lead_paddle(
+    mut query_grouped: Query<(&mut Transform, &GroupedPaddle, &PaddleStats), Without<Player>>,
+    query_lead: Query<&Transform, With<Player>>,
+    arena: Res<Arena>,
+) {
+    let axis = arena.goal_axis(0).extend(0.);
+    let center = arena.edge(arena.goals[0]).midpoint().extend(0.);
+    for (mut transform, grouped, stats) in &mut query_grouped {
+        let Ok(lead) = query_lead.get(grouped.lead) else {
+            continue;
+        };
+
+        let lead_along = (lead.translation - center).dot(axis);
+        let sideways = grouped.offset.dot(axis);
+        let along = if grouped.mirrored {
+            -lead_along
+        } else {
+            lead_along
+        } + sideways;
+        // level with the lead up the field, then over to its spot along the goal
+        let level = lead.translation + grouped.offset - axis * sideways;
+        transform.translation = arena.slide(0, level, along - lead_along, stats.size.x / 2.);
+    }
+}
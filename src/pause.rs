@@ -0,0 +1,272 @@
+//! Pausing a match: Back (Esc) during play opens the pause menu over the
+//! frozen arena. Back again or Resume goes straight back to the rally,
+//! Restart puts the match back to its first frame (snapshotted then, serve
+//! rng and all) and Quit ends it at the game-over screen. The game-over
+//! screen plays again the same way, through [`MatchFlow`]. Coming back re-enters
+//! `Playing`, so match setup on entering it runs on [`starting_match`] and
+//! leaves a resumed match alone.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    bindings::KeyBindings,
+    despawn_screen,
+    engine::ScheduleSetup,
+    focus::{FocusEvent, Focusable},
+    prompt::{MenuAction, MenuInput},
+    serve::MatchPhase,
+    snapshot::{capture, restore, GameSnapshot},
+    stats::MatchStats,
+    AppState, Ball,
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Resuming>()
+            .init_resource::<Kickoff>()
+            .add_systems_during(AppState::Playing, pause_match)
+            .add_systems_during(AppState::Playing, kick_off)
+            .add_systems_on_enter(AppState::Paused, spawn_pause_menu)
+            .add_systems_during(AppState::Paused, choose_pause_item)
+            .add_systems_on_exit(AppState::Paused, despawn_screen::<PauseScreen>);
+    }
+}
+
+/// Set on the way back to a match under way, until play has picked up again.
+#[derive(Resource, Default)]
+pub struct Resuming(bool);
+
+/// Run condition for match setup on entering `Playing`: true for a new
+/// match, false when it's a paused one picking up again.
+pub fn starting_match(resuming: Res<Resuming>) -> bool {
+    !resuming.0
+}
+
+/// The match as it stood on its first frame, and whether to go back there.
+#[derive(Resource, Default)]
+pub struct Kickoff {
+    snapshot: Option<GameSnapshot>,
+    restart: bool,
+}
+
+/// Moves the match under way on from a menu: back into play as it stands
+/// or from the top, or over to the game-over screen.
+#[derive(SystemParam)]
+pub struct MatchFlow<'w> {
+    resuming: ResMut<'w, Resuming>,
+    kickoff: ResMut<'w, Kickoff>,
+    next_state: ResMut<'w, NextState<AppState>>,
+    next_phase: ResMut<'w, NextState<MatchPhase>>,
+}
+
+impl MatchFlow<'_> {
+    /// A new match, from the menus.
+    #[cfg(feature = "remote")]
+    pub fn start(&mut self) {
+        self.next_state.set(AppState::Playing);
+    }
+
+    pub fn resume(&mut self) {
+        self.resuming.0 = true;
+        self.next_state.set(AppState::Playing);
+    }
+
+    /// Back to the match's first frame: score, stats, balls and paddles.
+    pub fn restart(&mut self) {
+        self.kickoff.restart = true;
+        // the first frame is mid-rally, even if this one was a serve
+        self.next_phase.set(MatchPhase::Rally);
+        self.resume();
+    }
+
+    pub fn end(&mut self) {
+        self.next_state.set(AppState::GameOver);
+    }
+}
+
+#[derive(Component)]
+struct PauseScreen;
+
+/// The pause menu's entries, top to bottom.
+#[derive(Component, Clone, Copy)]
+enum PauseItem {
+    Resume,
+    Restart,
+    Quit,
+}
+
+impl PauseItem {
+    const ALL: [PauseItem; 3] = [PauseItem::Resume, PauseItem::Restart, PauseItem::Quit];
+
+    fn label(self) -> &'static str {
+        match self {
+            PauseItem::Resume => "RESUME",
+            PauseItem::Restart => "RESTART",
+            PauseItem::Quit => "QUIT",
+        }
+    }
+}
+
+// also where the first frame back in play lands, so it clears the resume
+fn pause_match(
+    mut resuming: ResMut<Resuming>,
+    mut next_state: ResMut<NextState<AppState>>,
+    (input, keys, bindings): (MenuInput, Res<Input<KeyCode>>, Res<KeyBindings>),
+) {
+    if resuming.0 {
+        resuming.0 = false;
+    }
+    let paused = bindings
+        .players
+        .iter()
+        .any(|player| keys.just_pressed(player.pause));
+    if paused || input.just_pressed(MenuAction::Back) {
+        next_state.set(AppState::Paused);
+    }
+}
+
+// exclusive, since snapshots take the whole world
+fn kick_off(world: &mut World) {
+    // once the serve is in play
+    if world.resource::<Kickoff>().snapshot.is_none() {
+        if world.query::<&Ball>().iter(world).next().is_none() {
+            return;
+        }
+        let snapshot = capture(world);
+        world.resource_mut::<Kickoff>().snapshot = Some(snapshot);
+    }
+
+    let mut kickoff = world.resource_mut::<Kickoff>();
+    if !kickoff.restart {
+        return;
+    }
+    kickoff.restart = false;
+    let Some(snapshot) = kickoff.snapshot.take() else {
+        return;
+    };
+    restore(world, &snapshot);
+    world.insert_resource(MatchStats::default());
+    world.resource_mut::<Kickoff>().snapshot = Some(snapshot);
+}
+
+fn spawn_pause_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                // over the HUD
+                z_index: ZIndex::Global(10),
+                ..default()
+            },
+            PauseScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    "PAUSED",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 64.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::bottom(Val::Px(30.)),
+                    ..default()
+                }),
+            );
+            for item in PauseItem::ALL {
+                parent.spawn((
+                    TextBundle::from_section(
+                        item.label(),
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 32.,
+                            color: Color::WHITE,
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::vertical(Val::Px(6.)),
+                        ..default()
+                    }),
+                    Focusable::default(),
+                    item,
+                ));
+            }
+        });
+}
+
+fn choose_pause_item(
+    mut events: EventReader<FocusEvent>,
+    mut flow: MatchFlow,
+    query: Query<&PauseItem>,
+) {
+    for event in events.iter() {
+        let item = match *event {
+            FocusEvent::Activated(entity) => query.get(entity).ok().copied(),
+            FocusEvent::Back => Some(PauseItem::Resume),
+            FocusEvent::Adjusted(..) => None,
+        };
+        match item {
+            Some(PauseItem::Resume) => flow.resume(),
+            Some(PauseItem::Restart) => flow.restart(),
+            Some(PauseItem::Quit) => flow.end(),
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        ball_bundle, lives::Lives, serve::Serve, side_swap::SideSwap, special::SlowMotion,
+        survival::Survival, BallAssets, GameRng, GameState, Speed,
+    };
+
+    #[test]
+    fn restart_goes_back_to_the_first_frame() {
+        let mut world = World::new();
+        world.insert_resource(BallAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        });
+        world.init_resource::<GameState>();
+        world.init_resource::<SlowMotion>();
+        world.init_resource::<Survival>();
+        world.init_resource::<Lives>();
+        world.init_resource::<SideSwap>();
+        world.insert_resource(State(MatchPhase::Rally));
+        world.init_resource::<NextState<MatchPhase>>();
+        world.init_resource::<Serve>();
+        world.init_resource::<MatchStats>();
+        world.init_resource::<Kickoff>();
+        world.insert_resource(GameRng(StdRng::seed_from_u64(1)));
+        let bundle = ball_bundle(world.resource::<BallAssets>(), Vec3::ZERO, Speed::default());
+        world.spawn(bundle);
+
+        kick_off(&mut world);
+        world.resource_mut::<GameState>().score = (2, 3);
+        world.resource_mut::<MatchStats>().longest_rally = 9;
+        world.resource_mut::<Kickoff>().restart = true;
+        kick_off(&mut world);
+
+        assert_eq!(world.resource::<GameState>().score, (0, 0));
+        assert_eq!(world.resource::<MatchStats>().longest_rally, 0);
+        assert!(world.resource::<Kickoff>().snapshot.is_some());
+    }
+}
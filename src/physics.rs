@@ -0,0 +1,559 @@
+//! Pure ball physics math, kept out of ECS systems so it can be tested directly.
+//! Directions are `Speed::dir` style vectors in the XY plane.
+
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    arena::{Arena, Collider, WALL_THICKNESS},
+    engine::overlaps,
+};
+
+/// Bounces one ball can take in a single step; a ball wedged in a corner
+/// stops where it last touched rather than going round forever.
+pub const MAX_SWEEP_BOUNCES: usize = 4;
+
+// nudges tried either side of a blocked spawn before giving up on it
+const SPAWN_NUDGES: usize = 8;
+
+/// Mirrors `dir` off a surface with unit `normal`.
+pub fn reflect(dir: Vec3, normal: Vec3) -> Vec3 {
+    dir - (2. * dir.dot(normal)) * normal
+}
+
+/// Where a paddle sends a ball moving along `dir` that hits its face with
+/// unit `normal`: straight out from the middle, leaning further toward an end
+/// the nearer the ball struck it, up to `max_angle` radians at the very end.
+/// Keeps the ball's speed, so only the hit spot decides the angle.
+pub fn paddle_bounce(
+    dir: Vec3,
+    normal: Vec3,
+    ball: Vec3,
+    paddle: Vec3,
+    paddle_size: Vec2,
+    max_angle: f32,
+) -> Vec3 {
+    let normal2 = normal.truncate();
+    let along = normal2.perp();
+    let half_length = (paddle_size * along.abs()).length() / 2.;
+    let offset = ((ball - paddle).truncate().dot(along) / half_length).clamp(-1., 1.);
+    let angle = offset * max_angle;
+    ((normal2 * angle.cos() + along * angle.sin()) * dir.truncate().length()).extend(dir.z)
+}
+
+/// Whether a ball moving along `dir` is closing in on a surface facing `normal`.
+pub fn heading_into(dir: Vec3, normal: Vec3) -> bool {
+    dir.dot(normal) < 0.
+}
+
+/// The normal of the paddle face the ball is on; paddles have one on either
+/// long side, so an upright paddle's faces point along x.
+pub fn paddle_face(ball: Vec3, paddle: Vec3, paddle_size: Vec2) -> Vec3 {
+    let axis = if paddle_size.x >= paddle_size.y {
+        Vec3::Y
+    } else {
+        Vec3::X
+    };
+    if (ball - paddle).dot(axis) > 0. {
+        axis
+    } else {
+        -axis
+    }
+}
+
+/// The box a paddle of `size` covers once turned by `rotation`, for the
+/// quarter and half turns paddles on upright or far goals get.
+pub fn turned_size(size: Vec2, rotation: Quat) -> Vec2 {
+    (rotation * size.extend(0.)).truncate().abs()
+}
+
+/// Limits the angle between `dir` and `normal` to `max_angle` radians, keeping its length.
+pub fn clamp_angle(dir: Vec3, normal: Vec3, max_angle: f32) -> Vec3 {
+    let (dir2, normal2) = (dir.truncate(), normal.truncate());
+    if dir2 == Vec2::ZERO {
+        return dir;
+    }
+
+    let angle = normal2.angle_between(dir2);
+    if angle.abs() <= max_angle {
+        return dir;
+    }
+
+    let clamped = Vec2::from_angle(max_angle.copysign(angle)).rotate(normal2);
+    (clamped.normalize() * dir2.length()).extend(dir.z)
+}
+
+/// `dir` turned toward `toward` by at most `max_turn` radians, keeping its length.
+pub fn turn_toward(dir: Vec3, toward: Vec3, max_turn: f32) -> Vec3 {
+    let (dir2, toward2) = (dir.truncate(), toward.truncate());
+    if dir2 == Vec2::ZERO || toward2 == Vec2::ZERO {
+        return dir;
+    }
+
+    let turn = dir2.angle_between(toward2).clamp(-max_turn, max_turn);
+    Vec2::from_angle(turn).rotate(dir2).extend(dir.z)
+}
+
+/// The two directions a ball splits into: `angle` radians either side, scaled by `slowdown`.
+pub fn split(dir: Vec3, angle: f32, slowdown: f32) -> [Vec3; 2] {
+    let slowed = dir * slowdown;
+    [
+        Quat::from_rotation_z(angle) * slowed,
+        Quat::from_rotation_z(-angle) * slowed,
+    ]
+}
+
+/// A fresh serve: any sideways drift, always heading up the field from a
+/// goal whose unit normal is `field`.
+pub fn serve_dir(rng: &mut impl Rng, field: Vec2) -> Vec3 {
+    let up_the_field = Vec2::new(rng.gen_range(-10.0..10.0), rng.gen_range(0.0..10.0));
+    // turns +y onto `field`
+    Vec2::new(field.y, -field.x).rotate(up_the_field).extend(0.)
+}
+
+/// How far along `motion`, from 0 to 1, a ball starting at `ball` first
+/// touches `wall`, centered on `center`, while moving into it. A ball
+/// already touching it and heading in meets it straight away.
+pub fn sweep_wall(
+    ball: Vec3,
+    motion: Vec3,
+    center: Vec3,
+    wall: &Collider,
+    ball_size: Vec2,
+) -> Option<f32> {
+    let normal = wall.normal.extend(0.);
+    // only moving into the wall counts, so a ball still touching it after
+    // bouncing off isn't turned back out of the arena
+    if !heading_into(motion, normal) {
+        return None;
+    }
+    let gap = wall.gap(center, ball) - ball_size.y / 2.;
+    if gap <= 0. {
+        return Some(0.);
+    }
+    let toi = gap / -motion.dot(normal);
+    (toi <= 1.).then_some(toi)
+}
+
+/// How far along `motion`, from 0 to 1, a ball starting at `ball` first
+/// touches a box of `size` centered on `center`, and the normal of the face
+/// it meets. A ball already overlapping the box meets it straight away if
+/// it's heading into the face it's on, and not at all otherwise.
+pub fn sweep_box(
+    ball: Vec3,
+    motion: Vec3,
+    center: Vec3,
+    size: Vec2,
+    ball_size: Vec2,
+) -> Option<(f32, Vec3)> {
+    if overlaps(center, size, ball, ball_size) {
+        return paddle_contact(ball, motion, center, size, ball_size).map(|normal| (0., normal));
+    }
+
+    // the ball's center against the box grown by the ball, one slab per axis
+    let half = (size + ball_size) / 2.;
+    let offset = (ball - center).truncate();
+    let motion = motion.truncate();
+    let (mut entry, mut exit) = (f32::NEG_INFINITY, f32::INFINITY);
+    let mut normal = Vec3::ZERO;
+    for (axis, unit) in [(0, Vec3::X), (1, Vec3::Y)] {
+        if motion[axis] == 0. {
+            if offset[axis].abs() >= half[axis] {
+                return None;
+            }
+            continue;
+        }
+        let near = (-half[axis].copysign(motion[axis]) - offset[axis]) / motion[axis];
+        let far = (half[axis].copysign(motion[axis]) - offset[axis]) / motion[axis];
+        if near > entry {
+            entry = near;
+            normal = -unit * motion[axis].signum();
+        }
+        exit = exit.min(far);
+    }
+    (entry < exit && (0. ..=1.).contains(&entry)).then_some((entry, normal))
+}
+
+/// The normal of the paddle face to bounce off if the ball overlaps the paddle
+/// and is moving into that face.
+pub fn paddle_contact(
+    ball: Vec3,
+    dir: Vec3,
+    paddle: Vec3,
+    paddle_size: Vec2,
+    ball_size: Vec2,
+) -> Option<Vec3> {
+    if !overlaps(paddle, paddle_size, ball, ball_size) {
+        return None;
+    }
+    let normal = paddle_face(ball, paddle, paddle_size);
+    heading_into(dir, normal).then_some(normal)
+}
+
+/// Which of the arena's goal lines the ball has reached, as an index into
+/// `Arena::goals`.
+pub fn crossed_goal(arena: &Arena, ball: Vec3, ball_size: Vec2) -> Option<usize> {
+    arena.goal_lines().position(|goal| {
+        goal.signed_distance(ball.truncate()) < (WALL_THICKNESS + ball_size.y) / 2.
+    })
+}
+
+/// Where to move a ball overlapping the paddle so it just touches the side it
+/// sinks into least, if it overlaps at all.
+pub fn push_out_of_paddle(
+    ball: Vec3,
+    paddle: Vec3,
+    paddle_size: Vec2,
+    ball_size: Vec2,
+) -> Option<Vec3> {
+    let offset = (ball - paddle).truncate();
+    let overlap = (paddle_size + ball_size) / 2. - offset.abs();
+    if overlap.x <= 0. || overlap.y <= 0. {
+        return None;
+    }
+
+    let push = if overlap.x < overlap.y {
+        Vec2::new(overlap.x.copysign(offset.x), 0.)
+    } else {
+        Vec2::new(0., overlap.y.copysign(offset.y))
+    };
+    Some(ball + push.extend(0.))
+}
+
+/// Where to move a ball sunk past its contact distance with `wall`,
+/// centered on `center`, back out along the wall's normal, if it has.
+pub fn push_out_of_wall(
+    ball: Vec3,
+    center: Vec3,
+    wall: &Collider,
+    ball_size: Vec2,
+) -> Option<Vec3> {
+    let depth = ball_size.y / 2. - wall.gap(center, ball);
+    (depth > 0.).then(|| ball + (wall.normal * depth).extend(0.))
+}
+
+/// `desired` if it isn't `blocked`, otherwise the nearest free spot a whole
+/// number of `step`s either side of it along `along`. Falls back to `desired`
+/// when every nudge is blocked too.
+pub fn free_spot(desired: Vec3, along: Vec2, step: f32, blocked: impl Fn(Vec3) -> bool) -> Vec3 {
+    if !blocked(desired) {
+        return desired;
+    }
+    (1..=SPAWN_NUDGES)
+        .flat_map(|i| [i as f32, -(i as f32)])
+        .map(|nudge| desired + (along * step * nudge).extend(0.))
+        .find(|&spot| !blocked(spot))
+        .unwrap_or(desired)
+}
+
+/// The arena's ball spawn, slid along the goal line clear of any `(position,
+/// size)` paddle.
+pub fn ball_spawn(arena: &Arena, paddles: &[(Vec3, Vec2)], ball_size: Vec2) -> Vec3 {
+    let goal = arena.edge(arena.goals[0]);
+    clear_of_paddles(
+        arena.ball_spawn(),
+        (goal.end - goal.start).normalize(),
+        paddles,
+        ball_size,
+    )
+}
+
+/// `spot`, slid along `along` clear of any `(position, size)` paddle.
+pub fn clear_of_paddles(
+    spot: Vec3,
+    along: Vec2,
+    paddles: &[(Vec3, Vec2)],
+    ball_size: Vec2,
+) -> Vec3 {
+    let overlaps = |spot| {
+        paddles
+            .iter()
+            .any(|&(paddle, size)| overlaps(paddle, size, spot, ball_size))
+    };
+    free_spot(spot, along, ball_size.x * 2., overlaps)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::arena::Edge;
+
+    const EPSILON: f32 = 1e-3;
+
+    fn dir() -> impl Strategy<Value = Vec3> {
+        (-20f32..20., -20f32..20.).prop_map(|(x, y)| Vec3::new(x, y, 0.))
+    }
+
+    fn normal() -> impl Strategy<Value = Vec3> {
+        (0f32..2. * PI).prop_map(|angle| Vec2::from_angle(angle).extend(0.))
+    }
+
+    #[test]
+    fn reflect_off_floor_flips_vertical() {
+        let reflected = reflect(Vec3::new(3., -4., 0.), Vec3::Y);
+        assert_eq!(reflected, Vec3::new(3., 4., 0.));
+    }
+
+    #[test]
+    fn paddle_face_points_at_ball() {
+        let flat = Vec2::new(100., 10.);
+        assert_eq!(
+            paddle_face(Vec3::new(0., 5., 0.), Vec3::ZERO, flat),
+            Vec3::Y
+        );
+        assert_eq!(
+            paddle_face(Vec3::new(0., -5., 0.), Vec3::ZERO, flat),
+            Vec3::NEG_Y
+        );
+        let upright = Vec2::new(10., 100.);
+        assert_eq!(
+            paddle_face(Vec3::new(5., 40., 0.), Vec3::ZERO, upright),
+            Vec3::X
+        );
+    }
+
+    #[test]
+    fn paddle_ends_send_the_ball_off_at_an_angle() {
+        let (paddle, size) = (Vec3::ZERO, Vec2::new(100., 10.));
+        let incoming = Vec3::new(3., -4., 0.);
+        let middle = paddle_bounce(
+            incoming,
+            Vec3::Y,
+            Vec3::new(0., 8., 0.),
+            paddle,
+            size,
+            PI / 3.,
+        );
+        assert!(middle.abs_diff_eq(Vec3::new(0., 5., 0.), EPSILON));
+
+        let end = paddle_bounce(
+            incoming,
+            Vec3::Y,
+            Vec3::new(-50., 8., 0.),
+            paddle,
+            size,
+            PI / 3.,
+        );
+        assert!((end.angle_between(Vec3::Y) - PI / 3.).abs() < EPSILON);
+        assert!(end.x < 0.);
+        let halfway = paddle_bounce(
+            incoming,
+            Vec3::Y,
+            Vec3::new(25., 8., 0.),
+            paddle,
+            size,
+            PI / 3.,
+        );
+        assert!((halfway.angle_between(Vec3::Y) - PI / 6.).abs() < EPSILON);
+        assert!(halfway.x > 0.);
+
+        // an upright paddle leans along its length, the y axis
+        let upright = paddle_bounce(
+            Vec3::new(-5., 0., 0.),
+            Vec3::X,
+            Vec3::new(8., 50., 0.),
+            paddle,
+            Vec2::new(10., 100.),
+            PI / 3.,
+        );
+        assert!(upright.x > 0. && upright.y.abs() > EPSILON);
+    }
+
+    #[test]
+    fn serves_head_up_the_field() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for field in [Vec2::Y, Vec2::X, Vec2::NEG_X] {
+            for _ in 0..20 {
+                assert!(serve_dir(&mut rng, field).truncate().dot(field) >= 0.);
+            }
+        }
+    }
+
+    #[test]
+    fn clamp_angle_pulls_shallow_shots_in() {
+        let clamped = clamp_angle(Vec3::new(10., 1., 0.), Vec3::Y, PI / 4.);
+        assert!((clamped.x - clamped.y).abs() < EPSILON);
+        assert!(clamped.x > 0.);
+    }
+
+    #[test]
+    fn turn_toward_stops_at_the_max_turn() {
+        let turned = turn_toward(Vec3::new(10., 0., 0.), Vec3::Y, PI / 4.);
+        assert!((turned.x - turned.y).abs() < EPSILON);
+        assert!((turned.length() - 10.).abs() < EPSILON);
+        let small = turn_toward(Vec3::X, Vec3::new(1., 0.1, 0.), PI / 4.);
+        assert!((small.y / small.x - 0.1).abs() < EPSILON);
+    }
+
+    #[test]
+    fn split_diverges_symmetrically() {
+        let [left, right] = split(Vec3::new(0., 10., 0.), PI / 12., 0.5);
+        assert!((left.length() - 5.).abs() < EPSILON);
+        assert!((left.x + right.x).abs() < EPSILON);
+        assert!((left.y - right.y).abs() < EPSILON);
+    }
+
+    #[test]
+    fn paddle_pushes_ball_out_the_shallow_side() {
+        let size = Vec2::new(100., 20.);
+        let ball = Vec2::splat(10.);
+        assert_eq!(
+            push_out_of_paddle(Vec3::new(5., 12., 0.), Vec3::ZERO, size, ball),
+            Some(Vec3::new(5., 15., 0.))
+        );
+        assert_eq!(
+            push_out_of_paddle(Vec3::new(-52., 0., 0.), Vec3::ZERO, size, ball),
+            Some(Vec3::new(-55., 0., 0.))
+        );
+        assert_eq!(
+            push_out_of_paddle(Vec3::new(0., 15., 0.), Vec3::ZERO, size, ball),
+            None
+        );
+    }
+
+    #[test]
+    fn wall_pushes_sunk_ball_back_to_contact() {
+        let wall = Edge {
+            start: Vec2::new(-100., 0.),
+            end: Vec2::new(100., 0.),
+        }
+        .collider();
+        let ball = Vec2::splat(10.);
+        let contact = (WALL_THICKNESS + ball.y) / 2.;
+        let pushed = push_out_of_wall(Vec3::new(3., -4., 0.), Vec3::ZERO, &wall, ball).unwrap();
+        assert!((pushed.y - contact).abs() < EPSILON);
+        assert_eq!(pushed.x, 3.);
+        assert_eq!(
+            push_out_of_wall(Vec3::new(0., contact + 1., 0.), Vec3::ZERO, &wall, ball),
+            None
+        );
+    }
+
+    #[test]
+    fn fast_ball_meets_a_wall_it_would_pass_in_one_step() {
+        // a wall of its own thickness
+        let wall = Collider {
+            size: Vec2::new(200., 20.),
+            normal: Vec2::Y,
+        };
+        let center = Vec3::new(0., -50., 0.);
+        let ball = Vec2::splat(10.);
+        let start = Vec3::new(0., -25., 0.);
+        let sweep = |motion| sweep_wall(start, motion, center, &wall, ball);
+        let toi = sweep(Vec3::new(0., -200., 0.)).unwrap();
+        assert!((toi - 0.05).abs() < EPSILON);
+        assert_eq!(sweep(Vec3::new(0., -5., 0.)), None);
+        assert_eq!(sweep(Vec3::new(0., 200., 0.)), None);
+    }
+
+    #[test]
+    fn fast_ball_meets_the_box_face_it_reaches_first() {
+        let (paddle, size) = (Vec3::ZERO, Vec2::new(10., 100.));
+        let ball = Vec2::splat(10.);
+        let (toi, normal) = sweep_box(
+            Vec3::new(-100., 20., 0.),
+            Vec3::new(400., 0., 0.),
+            paddle,
+            size,
+            ball,
+        )
+        .unwrap();
+        assert!((toi - 0.225).abs() < EPSILON);
+        assert_eq!(normal, Vec3::NEG_X);
+
+        // passing above it, or falling short, misses
+        let above = Vec3::new(-100., 80., 0.);
+        assert_eq!(
+            sweep_box(above, Vec3::new(400., 0., 0.), paddle, size, ball),
+            None
+        );
+        let short = Vec3::new(-100., 20., 0.);
+        assert_eq!(
+            sweep_box(short, Vec3::new(50., 0., 0.), paddle, size, ball),
+            None
+        );
+    }
+
+    #[test]
+    fn free_spot_takes_the_nearest_clear_nudge() {
+        let blocked = |spot: Vec3| spot.x.abs() < 25.;
+        assert_eq!(
+            free_spot(Vec3::ZERO, Vec2::X, 10., blocked),
+            Vec3::new(30., 0., 0.)
+        );
+        assert_eq!(
+            free_spot(Vec3::new(40., 0., 0.), Vec2::X, 10., blocked),
+            Vec3::new(40., 0., 0.)
+        );
+        assert_eq!(free_spot(Vec3::ZERO, Vec2::X, 1., |_| true), Vec3::ZERO);
+    }
+
+    #[test]
+    fn ball_spawn_slides_clear_of_a_paddle() {
+        let arena = Arena::default();
+        let ball = Vec2::splat(10.);
+        let paddle = (arena.ball_spawn(), Vec2::new(100., 20.));
+        let spawn = ball_spawn(&arena, &[paddle], ball);
+        assert!(!overlaps(paddle.0, paddle.1, spawn, ball));
+        assert_eq!(ball_spawn(&arena, &[], ball), arena.ball_spawn());
+    }
+
+    proptest! {
+        #[test]
+        fn reflect_preserves_speed(dir in dir(), normal in normal()) {
+            prop_assert!((reflect(dir, normal).length() - dir.length()).abs() < EPSILON);
+        }
+
+        #[test]
+        fn reflect_twice_is_identity(dir in dir(), normal in normal()) {
+            prop_assert!(reflect(reflect(dir, normal), normal).abs_diff_eq(dir, EPSILON));
+        }
+
+        #[test]
+        fn reflected_ball_leaves_surface(dir in dir(), normal in normal()) {
+            prop_assume!(dir.dot(normal).abs() > EPSILON);
+            prop_assert_ne!(heading_into(dir, normal), heading_into(reflect(dir, normal), normal));
+        }
+
+        #[test]
+        fn paddle_bounce_leaves_the_face_at_speed(
+            dir in dir(),
+            normal in normal(),
+            offset in -80f32..80.,
+        ) {
+            prop_assume!(dir.length() > EPSILON);
+            // only axis-aligned paddles exist, but the face can point anywhere
+            let ball = (normal.truncate().perp() * offset).extend(0.) + normal * 8.;
+            let bounced = paddle_bounce(dir, normal, ball, Vec3::ZERO, Vec2::splat(100.), PI / 3.);
+            prop_assert!((bounced.length() - dir.length()).abs() < EPSILON);
+            prop_assert!(bounced.angle_between(normal) <= PI / 3. + EPSILON);
+        }
+
+        #[test]
+        fn clamped_angle_within_bounds(dir in dir(), normal in normal(), max in 0f32..PI) {
+            prop_assume!(dir.length() > EPSILON);
+            let clamped = clamp_angle(dir, normal, max);
+            prop_assert!(clamped.angle_between(normal) <= max + EPSILON);
+            prop_assert!((clamped.length() - dir.length()).abs() < EPSILON);
+        }
+
+        #[test]
+        fn clamp_keeps_angles_already_inside(dir in dir(), normal in normal()) {
+            prop_assume!(dir.length() > EPSILON);
+            let max = dir.angle_between(normal) + 0.01;
+            prop_assert_eq!(clamp_angle(dir, normal, max), dir);
+        }
+
+        #[test]
+        fn split_halves_share_speed(dir in dir(), angle in 0f32..PI / 2., slowdown in 0f32..1.) {
+            let [left, right] = split(dir, angle, slowdown);
+            prop_assert!((left.length() - right.length()).abs() < EPSILON);
+            prop_assert!((left.length() - dir.length() * slowdown).abs() < EPSILON);
+        }
+    }
+}
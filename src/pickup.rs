@@ -0,0 +1,145 @@
+//! Power-up pickups. A fresh set goes out mid-field at the start of every
+//! point; the first ball through one collects it and it stays gone until the
+//! next point. What a pickup does is up to the module listening for it.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena,
+    engine::ScheduleSetup,
+    event_log::GameplayEvent,
+    physics::free_spot,
+    prefab::{spawn_prefab, PrefabLibrary, PrefabOverrides, Prefabs},
+    AppState, Ball, GameState, Tunables,
+};
+
+// matches the "power-up" prefab's hexagon
+const PICKUP_RADIUS: f32 = 14.;
+
+pub struct PickupPlugin;
+
+impl Plugin for PickupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PointStarted>()
+            .add_event::<PickupCollected>()
+            .add_systems_during(AppState::Playing, (new_point, collect_pickups).chain());
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pickup {
+    Rewind,
+    TimeDilation,
+}
+
+impl Pickup {
+    pub const ALL: [Pickup; 2] = [Pickup::Rewind, Pickup::TimeDilation];
+
+    fn color(self) -> Color {
+        match self {
+            Pickup::Rewind => Color::CYAN,
+            Pickup::TimeDilation => Color::GOLD,
+        }
+    }
+}
+
+/// Sent after every goal, when the score goes back (a restart, or a rewind
+/// past a goal), and once when play first starts. Points from anything but a
+/// goal don't start a new one.
+pub struct PointStarted;
+
+pub struct PickupCollected {
+    pub pickup: Pickup,
+}
+
+fn new_point(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    (mut points, mut goals): (EventWriter<PointStarted>, EventReader<GameplayEvent>),
+    mut score: Local<Option<(u32, u32)>>,
+    (query, query_ball): (Query<Entity, With<Pickup>>, Query<&Transform, With<Ball>>),
+    (game_state, arena, prefabs, libraries, tunables): (
+        Res<GameState>,
+        Res<Arena>,
+        Res<Prefabs>,
+        Res<Assets<PrefabLibrary>>,
+        Res<Tunables>,
+    ),
+) {
+    // wait for the prefabs rather than skip the point's pickups
+    let Some(library) = libraries.get(&prefabs.0) else {
+        return;
+    };
+    let scored = goals
+        .iter()
+        .any(|event| matches!(event, GameplayEvent::Goal { .. }));
+    let went_back = score.map_or(true, |(near, far)| {
+        game_state.score.0 < near || game_state.score.1 < far
+    });
+    *score = Some(game_state.score);
+    if !scored && !went_back {
+        return;
+    }
+    points.send(PointStarted);
+
+    for entity in &query {
+        commands.entity(entity).despawn();
+    }
+
+    // spread the set across the middle of the field, stepping up or down the
+    // field out from under any ball so it isn't collected the moment it appears
+    let center = arena.vertices.iter().sum::<Vec2>() / arena.vertices.len() as f32;
+    let goal = arena.edge(arena.goals[0]);
+    let spacing = goal.length() / (Pickup::ALL.len() + 1) as f32;
+    let reach = PICKUP_RADIUS + tunables.ball_size().x / 2.;
+    let under_ball = |spot: Vec3| {
+        query_ball
+            .iter()
+            .any(|ball| ball.translation.truncate().distance(spot.truncate()) < reach)
+    };
+    for (i, pickup) in Pickup::ALL.into_iter().enumerate() {
+        let offset = (i as f32 - (Pickup::ALL.len() - 1) as f32 / 2.) * spacing;
+        let translation = free_spot(
+            Vec3::new(center.x + offset, center.y, 0.),
+            goal.normal(),
+            2. * reach,
+            under_ball,
+        );
+        spawn_prefab(
+            &mut commands,
+            &mut meshes,
+            &mut materials,
+            library,
+            "power-up",
+            PrefabOverrides {
+                translation,
+                color: Some(pickup.color()),
+                pickup: Some(pickup),
+                ..default()
+            },
+        );
+    }
+}
+
+fn collect_pickups(
+    mut commands: Commands,
+    mut collected: EventWriter<PickupCollected>,
+    query_pickup: Query<(Entity, &Transform, &Pickup)>,
+    query_ball: Query<&Transform, With<Ball>>,
+    tunables: Res<Tunables>,
+) {
+    let ball_radius = tunables.ball_size().x / 2.;
+    for (entity, transform, &pickup) in &query_pickup {
+        let touched = query_ball.iter().any(|ball| {
+            ball.translation
+                .truncate()
+                .distance(transform.translation.truncate())
+                < PICKUP_RADIUS + ball_radius
+        });
+        if touched {
+            commands.entity(entity).despawn();
+            collected.send(PickupCollected { pickup });
+        }
+    }
+}
@@ -0,0 +1,574 @@
+//! Power-ups that drift through the arena during a rally, a new one every
+//! few seconds, bouncing off every edge until a ball runs through one or its
+//! time is up. Whoever last returned that ball gets the effect: a wider or a
+//! narrower paddle, or a sticky one that catches the ball for a moment before
+//! letting it go. Slow motion and an extra ball split off the one that
+//! collected it are for everybody. The timed effects are listed down the left
+//! of the HUD while they last, and a new point clears them all.
+
+use std::{
+    f32::consts::{PI, TAU},
+    ops::RangeInclusive,
+};
+
+use bevy::{prelude::*, transform::TransformSystem};
+use rand::Rng;
+
+use crate::{
+    arena::{Arena, WALL_THICKNESS},
+    engine::{ScheduleSetup, Stage},
+    event_log::GameplayEvent,
+    ownership::Owner,
+    paddle::{scale_paddles, Paddle, PaddleStats},
+    physics::{reflect, split},
+    pickup::PointStarted,
+    prefab::{spawn_prefab, PrefabLibrary, PrefabOverrides, Prefabs},
+    serve::MatchPhase,
+    spawn_ball,
+    special::SlowMotion,
+    AppState, Ball, BallAssets, GameRng, LastPaddleHit, Speed, Tunables,
+};
+
+// matches the "power-up" prefab's hexagon
+const POWER_UP_RADIUS: f32 = 14.;
+const SPAWN_INTERVAL: f32 = 8.;
+const MAX_POWER_UPS: usize = 2;
+// seconds a power-up drifts before it's gone
+const LIFETIME: f32 = 12.;
+// pixels per second
+const DRIFT_SPEED: RangeInclusive<f32> = 40.0..=90.0;
+const EFFECT_DURATION: f32 = 8.;
+const SLOW_DURATION: f32 = 3.;
+const ENLARGE_FACTOR: f32 = 1.5;
+const SHRINK_FACTOR: f32 = 0.6;
+// how long a sticky paddle holds the ball before it goes on its way
+const STICK_HOLD: f32 = 0.6;
+const MULTI_BALL_ANGLE: f32 = PI / 6.;
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveEffects>()
+            .insert_resource(PowerUpSpawner(Timer::from_seconds(
+                SPAWN_INTERVAL,
+                TimerMode::Repeating,
+            )))
+            .add_event::<PowerUpCollected>()
+            .add_systems_at(Stage::Startup, spawn_effect_icons)
+            .add_systems_during(
+                AppState::Playing,
+                (
+                    spawn_power_ups,
+                    drift_power_ups,
+                    collect_power_ups,
+                    apply_power_ups,
+                    stick_balls,
+                )
+                    .chain()
+                    .distributive_run_if(in_state(MatchPhase::Rally)),
+            )
+            .add_systems_during(
+                AppState::Playing,
+                (
+                    clear_power_ups,
+                    expire_effects,
+                    resize_paddles.after(scale_paddles),
+                    update_effect_icons,
+                ),
+            )
+            .add_systems_at(
+                Stage::PostUpdate,
+                hold_stuck_balls
+                    .before(TransformSystem::TransformPropagate)
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    Enlarge,
+    Shrink,
+    SlowMo,
+    MultiBall,
+    Sticky,
+}
+
+impl PowerUp {
+    const ALL: [PowerUp; 5] = [
+        PowerUp::Enlarge,
+        PowerUp::Shrink,
+        PowerUp::SlowMo,
+        PowerUp::MultiBall,
+        PowerUp::Sticky,
+    ];
+
+    fn color(self) -> Color {
+        match self {
+            PowerUp::Enlarge => Color::LIME_GREEN,
+            PowerUp::Shrink => Color::CRIMSON,
+            PowerUp::SlowMo => Color::PURPLE,
+            PowerUp::MultiBall => Color::ORANGE,
+            PowerUp::Sticky => Color::PINK,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PowerUp::Enlarge => "ENLARGE",
+            PowerUp::Shrink => "SHRINK",
+            PowerUp::SlowMo => "SLOW-MO",
+            PowerUp::MultiBall => "MULTI-BALL",
+            PowerUp::Sticky => "STICKY",
+        }
+    }
+
+    /// Seconds the effect lasts; none for the extra ball, which stays.
+    fn duration(self) -> Option<f32> {
+        match self {
+            PowerUp::MultiBall => None,
+            PowerUp::SlowMo => Some(SLOW_DURATION),
+            _ => Some(EFFECT_DURATION),
+        }
+    }
+
+    /// Whether the effect goes on the paddle that last returned the ball.
+    fn on_paddle(self) -> bool {
+        matches!(self, PowerUp::Enlarge | PowerUp::Shrink | PowerUp::Sticky)
+    }
+}
+
+#[derive(Component)]
+struct Drift {
+    velocity: Vec2,
+    remaining: f32,
+}
+
+struct PowerUpCollected {
+    power_up: PowerUp,
+    ball: Entity,
+}
+
+#[derive(Resource)]
+struct PowerUpSpawner(Timer);
+
+/// A timed effect still running.
+struct Effect {
+    power_up: PowerUp,
+    /// The paddle it's on, for the paddle effects.
+    paddle: Option<Entity>,
+    remaining: f32,
+}
+
+#[derive(Resource, Default)]
+struct ActiveEffects(Vec<Effect>);
+
+impl ActiveEffects {
+    fn on(&self, paddle: Entity) -> impl Iterator<Item = PowerUp> + '_ {
+        self.0
+            .iter()
+            .filter(move |effect| effect.paddle == Some(paddle))
+            .map(|effect| effect.power_up)
+    }
+
+    /// How much wider than usual `paddle` is, with every size effect on it.
+    fn size_factor(&self, paddle: Entity) -> f32 {
+        self.on(paddle).fold(1., |factor, power_up| match power_up {
+            PowerUp::Enlarge => factor * ENLARGE_FACTOR,
+            PowerUp::Shrink => factor * SHRINK_FACTOR,
+            _ => factor,
+        })
+    }
+
+    fn is_sticky(&self, paddle: Entity) -> bool {
+        self.on(paddle).any(|power_up| power_up == PowerUp::Sticky)
+    }
+
+    /// The longest any `power_up` effect has left, if one's running.
+    fn remaining(&self, power_up: PowerUp) -> Option<f32> {
+        self.0
+            .iter()
+            .filter(|effect| effect.power_up == power_up)
+            .map(|effect| effect.remaining)
+            .reduce(f32::max)
+    }
+}
+
+/// A ball held on a sticky paddle, at `offset` from it.
+#[derive(Component)]
+struct Stuck {
+    paddle: Entity,
+    offset: Vec3,
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct EffectIcon(PowerUp);
+
+/// Where a power-up at `position` moving at `velocity` is `delta` seconds
+/// on, and how it's moving then, turned back off any edge it has reached.
+fn drifted(arena: &Arena, position: Vec2, velocity: Vec2, delta: f32) -> (Vec2, Vec2) {
+    let mut velocity = velocity;
+    for i in 0..arena.vertices.len() {
+        let edge = arena.edge(i);
+        let touching = edge.signed_distance(position) < POWER_UP_RADIUS + WALL_THICKNESS / 2.;
+        if touching && velocity.dot(edge.normal()) < 0. {
+            velocity = reflect(velocity.extend(0.), edge.normal().extend(0.)).truncate();
+        }
+    }
+    (position + velocity * delta, velocity)
+}
+
+fn spawn_power_ups(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut spawner: ResMut<PowerUpSpawner>,
+    query: Query<(), With<PowerUp>>,
+    (arena, prefabs, libraries, timer, mut rng): (
+        Res<Arena>,
+        Res<Prefabs>,
+        Res<Assets<PrefabLibrary>>,
+        Res<Time>,
+        ResMut<GameRng>,
+    ),
+) {
+    if !spawner.0.tick(timer.delta()).just_finished() || query.iter().len() >= MAX_POWER_UPS {
+        return;
+    }
+    let (Some(library), Some((start, end))) = (libraries.get(&prefabs.0), arena.center_line())
+    else {
+        return;
+    };
+
+    // somewhere across the middle, off in any direction
+    let power_up = PowerUp::ALL[rng.0.gen_range(0..PowerUp::ALL.len())];
+    let translation = start.lerp(end, rng.0.gen_range(0.2..0.8)).extend(0.);
+    let velocity = Vec2::from_angle(rng.0.gen_range(0.0..TAU)) * rng.0.gen_range(DRIFT_SPEED);
+    if let Some(entity) = spawn_prefab(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        library,
+        "power-up",
+        PrefabOverrides {
+            translation,
+            color: Some(power_up.color()),
+            power_up: Some(power_up),
+            ..default()
+        },
+    ) {
+        commands.entity(entity).insert(Drift {
+            velocity,
+            remaining: LIFETIME,
+        });
+    }
+}
+
+fn drift_power_ups(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut Drift)>,
+    arena: Res<Arena>,
+    timer: Res<Time>,
+) {
+    let delta = timer.delta_seconds();
+    for (entity, mut transform, mut drift) in &mut query {
+        drift.remaining -= delta;
+        if drift.remaining <= 0. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let (position, velocity) = drifted(
+            &arena,
+            transform.translation.truncate(),
+            drift.velocity,
+            delta,
+        );
+        transform.translation = position.extend(transform.translation.z);
+        drift.velocity = velocity;
+    }
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    mut collected: EventWriter<PowerUpCollected>,
+    query_power_up: Query<(Entity, &Transform, &PowerUp)>,
+    query_ball: Query<(Entity, &Transform), With<Ball>>,
+    tunables: Res<Tunables>,
+) {
+    let reach = POWER_UP_RADIUS + tunables.ball_size().x / 2.;
+    for (entity, transform, &power_up) in &query_power_up {
+        let at = transform.translation.truncate();
+        let Some((ball, _)) = query_ball
+            .iter()
+            .find(|(_, ball)| ball.translation.truncate().distance(at) < reach)
+        else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+        collected.send(PowerUpCollected { power_up, ball });
+    }
+}
+
+fn apply_power_ups(
+    mut commands: Commands,
+    mut collected: EventReader<PowerUpCollected>,
+    mut effects: ResMut<ActiveEffects>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut query_ball: Query<
+        (
+            &Transform,
+            &mut Speed,
+            Option<&LastPaddleHit>,
+            Option<&Owner>,
+        ),
+        With<Ball>,
+    >,
+    ball_assets: Res<BallAssets>,
+) {
+    for &PowerUpCollected { power_up, ball } in collected.iter() {
+        let Ok((transform, mut speed, last_hit, owner)) = query_ball.get_mut(ball) else {
+            continue;
+        };
+        let paddle = last_hit.map(|hit| hit.paddle);
+        // off a serve nobody has returned yet, the paddle effects go to waste
+        if power_up.on_paddle() && paddle.is_none() {
+            continue;
+        }
+
+        match power_up {
+            PowerUp::SlowMo => slow_motion.slow_for(SLOW_DURATION),
+            PowerUp::MultiBall => {
+                let [kept, split_off] = split(speed.dir, MULTI_BALL_ANGLE, 1.);
+                speed.dir = kept;
+                let split_ball = spawn_ball(
+                    &mut commands,
+                    &ball_assets,
+                    transform.translation,
+                    split_off,
+                );
+                if let Some(&owner) = owner {
+                    commands.entity(split_ball).insert(owner);
+                }
+            }
+            PowerUp::Enlarge | PowerUp::Shrink | PowerUp::Sticky => {}
+        }
+        if let Some(remaining) = power_up.duration() {
+            effects.0.push(Effect {
+                power_up,
+                paddle: paddle.filter(|_| power_up.on_paddle()),
+                remaining,
+            });
+        }
+    }
+}
+
+// hits only say where the ball and paddle were, so they go to the closest ones
+fn stick_balls(
+    mut commands: Commands,
+    mut events: EventReader<GameplayEvent>,
+    effects: Res<ActiveEffects>,
+    query_ball: Query<(Entity, &Transform), (With<Ball>, Without<Stuck>)>,
+    query_paddle: Query<(Entity, &Transform), With<Paddle>>,
+) {
+    for event in events.iter() {
+        let GameplayEvent::PaddleHit { ball, paddle, .. } = *event else {
+            continue;
+        };
+        let Some((ball, ball_transform)) = query_ball.iter().min_by(|a, b| {
+            let distance = |transform: &Transform| transform.translation.distance(ball);
+            distance(a.1).total_cmp(&distance(b.1))
+        }) else {
+            continue;
+        };
+        let Some((paddle, paddle_transform)) = query_paddle.iter().min_by(|a, b| {
+            let distance = |transform: &Transform| transform.translation.distance(paddle);
+            distance(a.1).total_cmp(&distance(b.1))
+        }) else {
+            continue;
+        };
+
+        if effects.is_sticky(paddle) {
+            commands.entity(ball).insert(Stuck {
+                paddle,
+                offset: ball_transform.translation - paddle_transform.translation,
+                remaining: STICK_HOLD,
+            });
+        }
+    }
+}
+
+// after everything that moves balls and paddles, so a stuck ball rides along
+// with its paddle and goes on from there with the direction it bounced off in
+fn hold_stuck_balls(
+    mut commands: Commands,
+    mut query_ball: Query<(Entity, &mut Transform, &mut Stuck), With<Ball>>,
+    query_paddle: Query<&Transform, (With<Paddle>, Without<Ball>)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut transform, mut stuck) in &mut query_ball {
+        stuck.remaining -= timer.delta_seconds();
+        match query_paddle.get(stuck.paddle) {
+            Ok(paddle) if stuck.remaining > 0. => {
+                transform.translation = paddle.translation + stuck.offset;
+            }
+            _ => {
+                commands.entity(entity).remove::<Stuck>();
+            }
+        }
+    }
+}
+
+fn expire_effects(mut effects: ResMut<ActiveEffects>, timer: Res<Time>) {
+    for effect in &mut effects.0 {
+        effect.remaining -= timer.delta_seconds();
+    }
+    effects.0.retain(|effect| effect.remaining > 0.);
+}
+
+// a new point starts on a clear field, with nobody's effects left running
+fn clear_power_ups(
+    mut commands: Commands,
+    mut points: EventReader<PointStarted>,
+    mut effects: ResMut<ActiveEffects>,
+    mut spawner: ResMut<PowerUpSpawner>,
+    query_power_up: Query<Entity, With<PowerUp>>,
+    query_stuck: Query<Entity, With<Stuck>>,
+) {
+    if points.is_empty() {
+        return;
+    }
+    points.clear();
+
+    for entity in &query_power_up {
+        commands.entity(entity).despawn();
+    }
+    for entity in &query_stuck {
+        commands.entity(entity).remove::<Stuck>();
+    }
+    effects.0.clear();
+    spawner.0.reset();
+}
+
+// on top of the width scale_paddles has just set from the archetype and tunables
+fn resize_paddles(
+    mut query: Query<(Entity, &mut Transform, &mut PaddleStats)>,
+    effects: Res<ActiveEffects>,
+) {
+    for (entity, mut transform, mut stats) in &mut query {
+        let factor = effects.size_factor(entity);
+        transform.scale.x *= factor;
+        stats.size.x *= factor;
+    }
+}
+
+// a hidden row per timed effect, shown while one is running
+fn spawn_effect_icons(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+        font_size: 16.,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    top: Val::Percent(40.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for power_up in PowerUp::ALL {
+                if power_up.duration().is_none() {
+                    continue;
+                }
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                margin: UiRect::vertical(Val::Px(2.)),
+                                ..default()
+                            },
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        EffectIcon(power_up),
+                    ))
+                    .with_children(|row| {
+                        row.spawn(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(12.), Val::Px(12.)),
+                                margin: UiRect::right(Val::Px(6.)),
+                                ..default()
+                            },
+                            background_color: power_up.color().into(),
+                            ..default()
+                        });
+                        row.spawn((
+                            TextBundle::from_section(power_up.label(), style.clone()),
+                            EffectIcon(power_up),
+                        ));
+                    });
+            }
+        });
+}
+
+fn update_effect_icons(
+    effects: Res<ActiveEffects>,
+    mut query_row: Query<(&EffectIcon, &mut Visibility), Without<Text>>,
+    mut query_text: Query<(&EffectIcon, &mut Text)>,
+) {
+    for (icon, mut visibility) in &mut query_row {
+        *visibility = match effects.remaining(icon.0) {
+            Some(_) => Visibility::Inherited,
+            None => Visibility::Hidden,
+        };
+    }
+    for (icon, mut text) in &mut query_text {
+        if let Some(remaining) = effects.remaining(icon.0) {
+            text.sections[0].value = format!("{} {}", icon.0.label(), remaining.ceil());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn power_ups_drift_back_off_the_walls() {
+        let arena = Arena::square(600.);
+        let (position, velocity) = drifted(&arena, Vec2::new(290., 0.), Vec2::new(60., 10.), 0.5);
+        assert_eq!(velocity, Vec2::new(-60., 10.));
+        assert!(position.x < 290.);
+        // out in the open it keeps going
+        let (_, velocity) = drifted(&arena, Vec2::ZERO, Vec2::new(60., 10.), 0.5);
+        assert_eq!(velocity, Vec2::new(60., 10.));
+    }
+
+    #[test]
+    fn size_effects_stack_on_their_paddle() {
+        let (paddle, other) = (Entity::from_raw(1), Entity::from_raw(2));
+        let effect = |power_up| Effect {
+            power_up,
+            paddle: Some(paddle),
+            remaining: 1.,
+        };
+        let effects = ActiveEffects(vec![
+            effect(PowerUp::Enlarge),
+            effect(PowerUp::Enlarge),
+            effect(PowerUp::Sticky),
+        ]);
+        assert_eq!(effects.size_factor(paddle), ENLARGE_FACTOR * ENLARGE_FACTOR);
+        assert_eq!(effects.size_factor(other), 1.);
+        assert!(effects.is_sticky(paddle) && !effects.is_sticky(other));
+    }
+}
@@ -0,0 +1,215 @@
+//! Training mode save-states. F5 snapshots the rally as it stands and F6
+//! jumps back to it, so a tricky incoming ball can be replayed as many times as
+//! it takes. F2 steps through the seeded scenarios in
+//! `assets/practice.scenarios.ron`, setting up each one's balls exactly and
+//! saving it so F6 replays it too.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    ecs::system::SystemState,
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    arena::Arena,
+    callout::spawn_callout,
+    engine::ScheduleSetup,
+    hotkey::HotkeyGuard,
+    snapshot::{capture, restore, GameSnapshot},
+    AppState, Speed, Tunables,
+};
+
+pub const SAVE_STATE_KEY: KeyCode = KeyCode::F5;
+pub const LOAD_STATE_KEY: KeyCode = KeyCode::F6;
+pub const SCENARIO_KEY: KeyCode = KeyCode::F2;
+
+const SCENARIOS_PATH: &str = "practice.scenarios.ron";
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ScenarioList>()
+            .init_asset_loader::<ScenarioLoader>()
+            .init_resource::<SaveState>()
+            .init_resource::<Scenarios>()
+            .add_systems_during(AppState::Playing, save_states);
+    }
+}
+
+#[derive(Resource, Default)]
+struct SaveState(Option<GameSnapshot>);
+
+/// Positions and directions are relative to the first goal: x along it from
+/// its middle, y up the field.
+#[derive(Deserialize, Clone, Copy)]
+struct ScenarioBall {
+    position: Vec2,
+    dir: Vec2,
+}
+
+#[derive(Deserialize)]
+struct Scenario {
+    name: String,
+    seed: u64,
+    balls: Vec<ScenarioBall>,
+}
+
+impl Scenario {
+    /// The balls laid out in `arena`, moving at `speed`.
+    fn balls(&self, arena: &Arena, speed: f32) -> Vec<(Vec3, Speed)> {
+        let goal = arena.edge(arena.goals[0]);
+        let (along, up) = ((goal.end - goal.start).normalize(), goal.normal());
+        let to_world = |point: Vec2| along * point.x + up * point.y;
+        self.balls
+            .iter()
+            .map(|ball| {
+                (
+                    (goal.midpoint() + to_world(ball.position)).extend(0.),
+                    Speed {
+                        dir: to_world(ball.dir).extend(0.),
+                        speed_multiplier: speed,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "6b1d9e4f-2a7c-4c3e-8f15-d2a9e0b7c481"]
+struct ScenarioList {
+    scenarios: Vec<Scenario>,
+}
+
+#[derive(Resource)]
+struct Scenarios {
+    list: Handle<ScenarioList>,
+    /// The scenario the next press sets up.
+    next: usize,
+}
+
+impl FromWorld for Scenarios {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            list: world.resource::<AssetServer>().load(SCENARIOS_PATH),
+            next: 0,
+        }
+    }
+}
+
+fn save_states(world: &mut World) {
+    let keys = world.resource::<Input<KeyCode>>();
+    let guard = world.resource::<HotkeyGuard>();
+    let (save, load, scenario) = (
+        guard.just_pressed(keys, SAVE_STATE_KEY),
+        guard.just_pressed(keys, LOAD_STATE_KEY),
+        guard.just_pressed(keys, SCENARIO_KEY),
+    );
+
+    let text = if scenario {
+        let Some(name) = stage_scenario(world) else {
+            return;
+        };
+        name
+    } else if save {
+        let snapshot = capture(world);
+        world.resource_mut::<SaveState>().0 = Some(snapshot);
+        "SAVED".to_owned()
+    } else if load {
+        let Some(snapshot) = world.resource_mut::<SaveState>().0.take() else {
+            return;
+        };
+        restore(world, &snapshot);
+        world.resource_mut::<SaveState>().0 = Some(snapshot);
+        "RESTORED".to_owned()
+    } else {
+        return;
+    };
+
+    let mut state: SystemState<(Commands, Res<AssetServer>)> = SystemState::new(world);
+    let (mut commands, asset_server) = state.get_mut(world);
+    spawn_callout(&mut commands, &asset_server, &text, Vec3::ZERO);
+    state.apply(world);
+}
+
+// sets up the next scenario on top of the current paddles and score, and saves
+// it; returns its name
+fn stage_scenario(world: &mut World) -> Option<String> {
+    let (balls, seed, name) = {
+        let scenarios = world.resource::<Scenarios>();
+        let list = world
+            .resource::<Assets<ScenarioList>>()
+            .get(&scenarios.list)?;
+        let scenario = list
+            .scenarios
+            .get(scenarios.next % list.scenarios.len().max(1))?;
+        let speed = world.resource::<Tunables>().speed;
+        (
+            scenario.balls(world.resource::<Arena>(), speed),
+            scenario.seed,
+            scenario.name.clone(),
+        )
+    };
+    world.resource_mut::<Scenarios>().next += 1;
+
+    let snapshot = capture(world).staged(balls, seed);
+    restore(world, &snapshot);
+    world.resource_mut::<SaveState>().0 = Some(snapshot);
+    Some(name.to_uppercase())
+}
+
+#[derive(Default)]
+struct ScenarioLoader;
+
+impl AssetLoader for ScenarioLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let list: ScenarioList = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scenarios.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{arena::WALL_THICKNESS, BALL_SIZE};
+
+    // every ball has to start in play, clear of the walls, in every arena
+    #[test]
+    fn scenarios_fit_every_arena() {
+        let list: ScenarioList =
+            ron::from_str(include_str!("../assets/practice.scenarios.ron")).unwrap();
+        assert!(!list.scenarios.is_empty());
+
+        let clearance = (WALL_THICKNESS + BALL_SIZE.y) / 2.;
+        for name in ["square", "hex", "triangle", "classic"] {
+            let arena = Arena::from_name(name).unwrap();
+            for scenario in &list.scenarios {
+                for (translation, _) in scenario.balls(&arena, 1.) {
+                    let point = translation.truncate();
+                    for edge in arena.walls().chain(arena.goal_lines()) {
+                        assert!(
+                            edge.signed_distance(point) > clearance,
+                            "{}: {name} ball at {point}",
+                            scenario.name
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
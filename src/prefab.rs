@@ -0,0 +1,199 @@
+//! Prefabs: named entity templates defined in `assets/entities.prefabs.ron`.
+//! Each one pairs a look (shape and color) with the gameplay components its
+//! kind needs, and spawners pass overrides for the per-instance details, so a
+//! new variant is a new entry in the file rather than new spawn code.
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    sprite::MaterialMesh2dBundle,
+    utils::{BoxedFuture, HashMap},
+};
+use serde::Deserialize;
+
+use crate::{
+    block::Stance,
+    engine,
+    engine::{ScheduleSetup, Stage},
+    paddle::Paddle,
+    paddle::PaddleStats,
+    pickup::Pickup,
+    power_up::PowerUp,
+    Ball, Speed,
+};
+
+pub const PREFABS_PATH: &str = "entities.prefabs.ron";
+
+pub struct PrefabPlugin;
+
+impl Plugin for PrefabPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PrefabLibrary>()
+            .init_asset_loader::<PrefabLoader>()
+            .add_systems_at(Stage::Startup, load_prefabs);
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum PrefabKind {
+    Ball,
+    Paddle,
+    Brick,
+    PowerUp,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum PrefabShape {
+    Circle { radius: f32 },
+    Box { width: f32, height: f32 },
+    Hexagon { radius: f32 },
+}
+
+impl PrefabShape {
+    fn mesh(self) -> Mesh {
+        match self {
+            PrefabShape::Circle { radius } => engine::circle(radius),
+            PrefabShape::Box { width, height } => engine::rectangle(Vec2::new(width, height)),
+            PrefabShape::Hexagon { radius } => engine::polygon(radius, 6),
+        }
+    }
+
+    pub fn size(self) -> Vec2 {
+        match self {
+            PrefabShape::Circle { radius } | PrefabShape::Hexagon { radius } => {
+                Vec2::splat(radius * 2.)
+            }
+            PrefabShape::Box { width, height } => Vec2::new(width, height),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Prefab {
+    pub kind: PrefabKind,
+    pub shape: PrefabShape,
+    pub color: Color,
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "0f6e2b8a-3c4d-4e91-9b7a-5d2c8e1f4a63"]
+pub struct PrefabLibrary {
+    pub prefabs: HashMap<String, Prefab>,
+}
+
+#[derive(Resource)]
+pub struct Prefabs(pub Handle<PrefabLibrary>);
+
+/// Per-instance changes to a prefab; anything left `None` keeps the template's.
+#[derive(Default, Clone, Copy)]
+pub struct PrefabOverrides {
+    pub translation: Vec3,
+    pub color: Option<Color>,
+    /// Starting direction for balls.
+    pub dir: Option<Vec3>,
+    /// Which power-up a power-up prefab grants.
+    pub pickup: Option<Pickup>,
+    /// Which drifting power-up a power-up prefab is instead, if any.
+    pub power_up: Option<PowerUp>,
+}
+
+/// A block in the field that a ball knocks out on contact; spawned by
+/// layouts that want obstacles and by the breakout mutator.
+#[derive(Component)]
+pub struct Brick {
+    pub size: Vec2,
+}
+
+fn load_prefabs(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Prefabs(asset_server.load(PREFABS_PATH)));
+}
+
+/// Spawns prefab `name` with `overrides`, or does nothing if there's no such prefab.
+pub fn spawn_prefab(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<ColorMaterial>,
+    library: &PrefabLibrary,
+    name: &str,
+    overrides: PrefabOverrides,
+) -> Option<Entity> {
+    let Some(prefab) = library.prefabs.get(name) else {
+        warn!("no prefab named {name:?}");
+        return None;
+    };
+
+    let mut entity = commands.spawn(MaterialMesh2dBundle {
+        mesh: meshes.add(prefab.shape.mesh()).into(),
+        material: materials.add(ColorMaterial::from(overrides.color.unwrap_or(prefab.color))),
+        transform: Transform::from_translation(overrides.translation),
+        ..default()
+    });
+
+    match prefab.kind {
+        PrefabKind::Ball => entity.insert((
+            Ball,
+            Speed {
+                dir: overrides.dir.unwrap_or_default(),
+                ..default()
+            },
+        )),
+        PrefabKind::Paddle => entity.insert((
+            Paddle,
+            PaddleStats {
+                size: prefab.shape.size(),
+                speed: 1.,
+            },
+            Stance::default(),
+        )),
+        PrefabKind::Brick => entity.insert(Brick {
+            size: prefab.shape.size(),
+        }),
+        PrefabKind::PowerUp => match overrides.power_up {
+            Some(power_up) => entity.insert(power_up),
+            None => entity.insert(overrides.pickup.unwrap_or(Pickup::Rewind)),
+        },
+    };
+    Some(entity.id())
+}
+
+#[derive(Default)]
+struct PrefabLoader;
+
+impl AssetLoader for PrefabLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let library: PrefabLibrary = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(library));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["prefabs.ron"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shipped_prefabs_parse() {
+        let path = format!("{}/assets/{PREFABS_PATH}", env!("CARGO_MANIFEST_DIR"));
+        let library: PrefabLibrary = ron::de::from_bytes(&std::fs::read(path).unwrap()).unwrap();
+
+        for (name, kind) in [
+            ("ball", PrefabKind::Ball),
+            ("paddle", PrefabKind::Paddle),
+            ("brick", PrefabKind::Brick),
+            ("power-up", PrefabKind::PowerUp),
+        ] {
+            assert_eq!(library.prefabs[name].kind, kind, "{name}");
+        }
+    }
+}
@@ -0,0 +1,219 @@
+//! Menu input and the prompts that name it. Menus take the keyboard or any
+//! gamepad, and prompts are written for whichever was used last: key names,
+//! or the face buttons as that pad family labels them.
+
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*};
+
+use crate::engine::{ScheduleSetup, Stage};
+
+pub struct PromptPlugin;
+
+impl Plugin for PromptPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastDevice>()
+            .add_systems_at(Stage::PreUpdate, track_device.after(InputSystem));
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadStyle {
+    Xbox,
+    PlayStation,
+    Switch,
+}
+
+impl PadStyle {
+    /// Guesses the family from the name the driver reports; unknown pads get
+    /// Xbox labels, the layout most others copy.
+    fn from_name(name: &str) -> Self {
+        let name = name.to_lowercase();
+        if [
+            "playstation",
+            "dualshock",
+            "dualsense",
+            "sony",
+            "ps4",
+            "ps5",
+        ]
+        .iter()
+        .any(|hint| name.contains(hint))
+        {
+            PadStyle::PlayStation
+        } else if ["nintendo", "switch", "pro controller", "joy-con"]
+            .iter()
+            .any(|hint| name.contains(hint))
+        {
+            PadStyle::Switch
+        } else {
+            PadStyle::Xbox
+        }
+    }
+
+    fn face(self, button: GamepadButtonType) -> &'static str {
+        use GamepadButtonType::*;
+        match (self, button) {
+            (PadStyle::Xbox, South) | (PadStyle::Switch, East) => "A",
+            (PadStyle::Xbox, East) | (PadStyle::Switch, South) => "B",
+            (PadStyle::Xbox, West) | (PadStyle::Switch, North) => "X",
+            (PadStyle::Xbox, North) | (PadStyle::Switch, West) => "Y",
+            (PadStyle::PlayStation, South) => "✕",
+            (PadStyle::PlayStation, East) => "○",
+            (PadStyle::PlayStation, West) => "□",
+            (PadStyle::PlayStation, North) => "△",
+            _ => "?",
+        }
+    }
+}
+
+/// The device the player touched most recently.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LastDevice {
+    #[default]
+    Keyboard,
+    Gamepad(PadStyle),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    Left,
+    Right,
+    Up,
+    Down,
+    Toggle,
+    Confirm,
+    Back,
+}
+
+impl MenuAction {
+    fn key(self) -> KeyCode {
+        match self {
+            MenuAction::Left => KeyCode::Left,
+            MenuAction::Right => KeyCode::Right,
+            MenuAction::Up => KeyCode::Up,
+            MenuAction::Down => KeyCode::Down,
+            MenuAction::Toggle => KeyCode::Space,
+            MenuAction::Confirm => KeyCode::Return,
+            MenuAction::Back => KeyCode::Escape,
+        }
+    }
+
+    fn button(self) -> GamepadButtonType {
+        match self {
+            MenuAction::Left => GamepadButtonType::DPadLeft,
+            MenuAction::Right => GamepadButtonType::DPadRight,
+            MenuAction::Up => GamepadButtonType::DPadUp,
+            MenuAction::Down => GamepadButtonType::DPadDown,
+            MenuAction::Toggle => GamepadButtonType::West,
+            MenuAction::Confirm => GamepadButtonType::South,
+            MenuAction::Back => GamepadButtonType::East,
+        }
+    }
+
+    /// What to call this action's input on `device`.
+    pub fn glyph(self, device: LastDevice) -> &'static str {
+        match (device, self) {
+            (LastDevice::Keyboard, MenuAction::Left) => "Left",
+            (LastDevice::Keyboard, MenuAction::Right) => "Right",
+            (LastDevice::Keyboard, MenuAction::Up) => "Up",
+            (LastDevice::Keyboard, MenuAction::Down) => "Down",
+            (LastDevice::Keyboard, MenuAction::Toggle) => "Space",
+            (LastDevice::Keyboard, MenuAction::Confirm) => "Enter",
+            (LastDevice::Keyboard, MenuAction::Back) => "Esc",
+            (LastDevice::Gamepad(_), MenuAction::Left) => "D-pad Left",
+            (LastDevice::Gamepad(_), MenuAction::Right) => "D-pad Right",
+            (LastDevice::Gamepad(_), MenuAction::Up) => "D-pad Up",
+            (LastDevice::Gamepad(_), MenuAction::Down) => "D-pad Down",
+            (LastDevice::Gamepad(style), action) => style.face(action.button()),
+        }
+    }
+}
+
+/// "press any key", worded for `device`.
+pub fn any_input_prompt(device: LastDevice) -> &'static str {
+    match device {
+        LastDevice::Keyboard => "press any key",
+        LastDevice::Gamepad(_) => "press any button",
+    }
+}
+
+/// Menu presses from the keyboard and every gamepad, and prompts to match.
+#[derive(SystemParam)]
+pub struct MenuInput<'w> {
+    keys: Res<'w, Input<KeyCode>>,
+    buttons: Res<'w, Input<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+    device: Res<'w, LastDevice>,
+}
+
+impl MenuInput<'_> {
+    pub fn just_pressed(&self, action: MenuAction) -> bool {
+        self.keys.just_pressed(action.key())
+            || self.gamepads.iter().any(|gamepad| {
+                self.buttons
+                    .just_pressed(GamepadButton::new(gamepad, action.button()))
+            })
+    }
+
+    pub fn glyph(&self, action: MenuAction) -> &'static str {
+        action.glyph(*self.device)
+    }
+}
+
+fn track_device(
+    mut device: ResMut<LastDevice>,
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+) {
+    let latest = if let Some(button) = buttons.get_just_pressed().next() {
+        let name = gamepads.name(button.gamepad).unwrap_or_default();
+        LastDevice::Gamepad(PadStyle::from_name(name))
+    } else if keys.get_just_pressed().next().is_some() {
+        LastDevice::Keyboard
+    } else {
+        return;
+    };
+    // only touch the resource on a switch, so prompts can rebuild on change
+    if *device != latest {
+        *device = latest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pad_families_from_names() {
+        assert_eq!(
+            PadStyle::from_name("Sony Interactive Entertainment Wireless Controller"),
+            PadStyle::PlayStation
+        );
+        assert_eq!(
+            PadStyle::from_name("Nintendo Switch Pro Controller"),
+            PadStyle::Switch
+        );
+        assert_eq!(
+            PadStyle::from_name("Xbox Wireless Controller"),
+            PadStyle::Xbox
+        );
+        assert_eq!(PadStyle::from_name("Generic USB Joystick"), PadStyle::Xbox);
+    }
+
+    #[test]
+    fn confirm_is_the_bottom_button_except_on_switch() {
+        assert_eq!(
+            MenuAction::Confirm.glyph(LastDevice::Gamepad(PadStyle::Xbox)),
+            "A"
+        );
+        assert_eq!(
+            MenuAction::Confirm.glyph(LastDevice::Gamepad(PadStyle::PlayStation)),
+            "✕"
+        );
+        assert_eq!(
+            MenuAction::Confirm.glyph(LastDevice::Gamepad(PadStyle::Switch)),
+            "B"
+        );
+        assert_eq!(MenuAction::Confirm.glyph(LastDevice::Keyboard), "Enter");
+    }
+}
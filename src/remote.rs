@@ -0,0 +1,349 @@
+//! Remote control for QA automation and tournament tools, built with the
+//! `remote` feature on top of the observation server. Each text message a
+//! client sends is one JSON command, answered with a JSON reply to that
+//! client alone:
+//!
+//! - `{"command": "start", "point_limit": 5, "best_of": 3}` starts a match,
+//!   or starts the one under way over, with any of [`MatchConfig`]'s
+//!   settings changed first. Replies `{"ok": true}`.
+//! - `{"command": "steer", "side": 0, "steer": -1}` holds a side's steering
+//!   at that fraction of paddle speed, from -1 (full left) to 1 (full right),
+//!   until the next `steer` or the next match. Replies `{"ok": true}`.
+//! - `{"command": "result"}` replies with the [`MatchResult`] so far.
+//! - `{"command": "quit"}` closes the game.
+//!
+//! A command that doesn't parse, a `start` before the game has loaded, or
+//! one with a setting the options menu or the tuning panel wouldn't allow,
+//! gets `{"error": "..."}`. Run with `--headless` to drive the game without a
+//! window.
+
+use std::{fmt::Display, ops::RangeInclusive};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    archetype::ChosenArchetype,
+    arena::Arena,
+    block::Stance,
+    engine::ScheduleSetup,
+    observe::{ClientMessage, Observers},
+    paddle::{PaddleStats, Side, Steered, PADDLE_SPEED},
+    pause::MatchFlow,
+    scoreboard::{games_won, match_winner, points},
+    serve::Serving,
+    stats::MatchStats,
+    time_scale::{scaled, TimeScale},
+    AppState, BallAssets, GameState, Tunables,
+};
+
+pub struct RemotePlugin;
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RemoteSteering>()
+            .add_system(run_commands)
+            .add_systems_during(AppState::Playing, steer_paddles);
+    }
+}
+
+/// Settings a `start` command can change before the match begins; the rest
+/// stay as they are.
+#[derive(Deserialize, Default, Debug, PartialEq)]
+#[serde(default)]
+pub struct MatchConfig {
+    pub point_limit: Option<u32>,
+    pub speed: Option<f32>,
+    pub paddle_scale: Option<f32>,
+    pub ball_scale: Option<f32>,
+    pub ball_radius: Option<f32>,
+    pub paddle_height: Option<f32>,
+    pub gravity: Option<f32>,
+    pub best_of: Option<u32>,
+    pub lives: Option<u32>,
+}
+
+impl MatchConfig {
+    /// The first setting outside what the options menu and the tuning panel
+    /// allow, if any.
+    fn out_of_range(&self) -> Option<String> {
+        let counts = [
+            ("point_limit", self.point_limit, Tunables::POINT_LIMIT_RANGE),
+            ("best_of", self.best_of, Tunables::BEST_OF_RANGE),
+            ("lives", self.lives, Tunables::LIVES_RANGE),
+        ]
+        .into_iter()
+        .find_map(|(name, value, range)| outside(name, value, range));
+        let floats = [
+            ("speed", self.speed, Tunables::SPEED_RANGE),
+            (
+                "paddle_scale",
+                self.paddle_scale,
+                Tunables::PADDLE_SCALE_RANGE,
+            ),
+            (
+                "paddle_height",
+                self.paddle_height,
+                Tunables::PADDLE_HEIGHT_RANGE,
+            ),
+            ("ball_radius", self.ball_radius, Tunables::BALL_RADIUS_RANGE),
+            ("ball_scale", self.ball_scale, Tunables::BALL_SCALE_RANGE),
+            ("gravity", self.gravity, Tunables::GRAVITY_RANGE),
+        ]
+        .into_iter()
+        .find_map(|(name, value, range)| outside(name, value, range));
+        // an even number of games could end level
+        let even = self
+            .best_of
+            .filter(|best_of| best_of % 2 == 0)
+            .map(|best_of| format!("best_of must be odd, not {best_of}"));
+        counts.or(floats).or(even)
+    }
+
+    fn apply(&self, tunables: &mut Tunables) {
+        tunables.point_limit = self.point_limit.unwrap_or(tunables.point_limit);
+        tunables.speed = self.speed.unwrap_or(tunables.speed);
+        tunables.paddle_scale = self.paddle_scale.unwrap_or(tunables.paddle_scale);
+        tunables.ball_scale = self.ball_scale.unwrap_or(tunables.ball_scale);
+        tunables.ball_radius = self.ball_radius.unwrap_or(tunables.ball_radius);
+        tunables.paddle_height = self.paddle_height.unwrap_or(tunables.paddle_height);
+        tunables.gravity = self.gravity.unwrap_or(tunables.gravity);
+        tunables.best_of = self.best_of.unwrap_or(tunables.best_of);
+        tunables.lives = self.lives.unwrap_or(tunables.lives);
+    }
+}
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    Start(MatchConfig),
+    Steer { side: usize, steer: f32 },
+    Result,
+    Quit,
+}
+
+/// The reply to `result`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MatchResult {
+    pub state: String,
+    /// Points for the first goal's player, then the far side's, this game.
+    pub score: [u32; 2],
+    /// Games won, in the same order.
+    pub games: [u32; 2],
+    /// The side that won the match, once one has.
+    pub winner: Option<usize>,
+    pub longest_rally: u32,
+}
+
+#[derive(Serialize)]
+struct Ack {
+    ok: bool,
+}
+
+#[derive(Serialize)]
+struct Failure {
+    error: String,
+}
+
+/// Why `value`, if it's set, can't go to `name`.
+fn outside<T: PartialOrd + Display>(
+    name: &str,
+    value: Option<T>,
+    range: RangeInclusive<T>,
+) -> Option<String> {
+    let value = value?;
+    (!range.contains(&value)).then(|| {
+        format!(
+            "{name} must be between {} and {}, not {value}",
+            range.start(),
+            range.end()
+        )
+    })
+}
+
+/// Each side's steering from `steer` commands, indexed like `Arena::goals`.
+#[derive(Resource, Default)]
+struct RemoteSteering(Vec<f32>);
+
+fn run_commands(
+    mut commands: Commands,
+    mut messages: EventReader<ClientMessage>,
+    mut flow: MatchFlow,
+    mut exit: EventWriter<AppExit>,
+    (observers, mut tunables, mut steering): (
+        Res<Observers>,
+        ResMut<Tunables>,
+        ResMut<RemoteSteering>,
+    ),
+    (state, game_state, stats): (Res<State<AppState>>, Res<GameState>, Res<MatchStats>),
+    (archetype, ball_assets): (Option<Res<ChosenArchetype>>, Option<Res<BallAssets>>),
+) {
+    for message in messages.iter() {
+        let command = match serde_json::from_str::<Command>(&message.text) {
+            Ok(command) => command,
+            Err(err) => {
+                let failure = Failure {
+                    error: err.to_string(),
+                };
+                observers.reply(message.client, &serde_json::to_string(&failure).unwrap());
+                continue;
+            }
+        };
+
+        let reply = match command {
+            // the serve needs the ball look, which comes with the layout
+            // scene, and a match shouldn't start on half-loaded assets
+            Command::Start(_) if ball_assets.is_none() || state.0 == AppState::Loading => {
+                serde_json::to_string(&Failure {
+                    error: "still loading".to_owned(),
+                })
+            }
+            Command::Start(config) => {
+                if let Some(error) = config.out_of_range() {
+                    let failure = Failure { error };
+                    observers.reply(message.client, &serde_json::to_string(&failure).unwrap());
+                    continue;
+                }
+                config.apply(&mut tunables);
+                steering.0.clear();
+                match state.0 {
+                    AppState::Playing
+                    | AppState::Paused
+                    | AppState::Intermission
+                    | AppState::GameOver => flow.restart(),
+                    _ => {
+                        // skipping character select leaves the default archetype
+                        if archetype.is_none() {
+                            commands.init_resource::<ChosenArchetype>();
+                        }
+                        flow.start();
+                    }
+                }
+                serde_json::to_string(&Ack { ok: true })
+            }
+            Command::Steer { side, steer } => {
+                if steering.0.len() <= side {
+                    steering.0.resize(side + 1, 0.);
+                }
+                steering.0[side] = steer.clamp(-1., 1.);
+                serde_json::to_string(&Ack { ok: true })
+            }
+            Command::Result => serde_json::to_string(&MatchResult {
+                state: format!("{:?}", state.0),
+                score: points(game_state.score),
+                games: games_won(&game_state.games),
+                winner: match_winner(&game_state.games, tunables.best_of),
+                longest_rally: stats.longest_rally,
+            }),
+            Command::Quit => {
+                exit.send(AppExit);
+                serde_json::to_string(&Ack { ok: true })
+            }
+        };
+        observers.reply(message.client, &reply.unwrap());
+    }
+}
+
+// like the steering keys, with a held fraction of a key in place of one
+fn steer_paddles(
+    mut query: Query<
+        (
+            &mut Transform,
+            &Stance,
+            &PaddleStats,
+            &Side,
+            Option<&TimeScale>,
+        ),
+        (With<Steered>, Without<Serving>),
+    >,
+    steering: Res<RemoteSteering>,
+    arena: Res<Arena>,
+    timer: Res<Time>,
+) {
+    for (mut transform, stance, stats, side, time_scale) in &mut query {
+        let Some(&steer) = steering.0.get(side.0) else {
+            continue;
+        };
+        let delta = scaled(timer.delta_seconds(), time_scale);
+        let step = steer * PADDLE_SPEED * delta * stance.move_factor();
+        transform.translation = arena.slide(
+            side.0,
+            transform.translation,
+            step * stats.speed,
+            stats.size.x / 2.,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        let start: Command =
+            serde_json::from_str(r#"{"command": "start", "point_limit": 5}"#).unwrap();
+        assert_eq!(
+            start,
+            Command::Start(MatchConfig {
+                point_limit: Some(5),
+                ..default()
+            })
+        );
+        let steer: Command =
+            serde_json::from_str(r#"{"command": "steer", "side": 1, "steer": -0.5}"#).unwrap();
+        assert_eq!(
+            steer,
+            Command::Steer {
+                side: 1,
+                steer: -0.5
+            }
+        );
+        assert!(serde_json::from_str::<Command>(r#"{"command": "jump"}"#).is_err());
+    }
+
+    #[test]
+    fn config_changes_only_what_it_names() {
+        let mut tunables = Tunables::default();
+        MatchConfig {
+            speed: Some(80.),
+            ..default()
+        }
+        .apply(&mut tunables);
+        assert_eq!(tunables.speed, 80.);
+        assert_eq!(tunables.point_limit, Tunables::default().point_limit);
+    }
+
+    #[test]
+    fn settings_out_of_range_are_turned_down() {
+        let Command::Start(config) =
+            serde_json::from_str(r#"{"command": "start", "speed": 0}"#).unwrap()
+        else {
+            panic!("not a start command");
+        };
+        assert!(config.out_of_range().unwrap().starts_with("speed"));
+
+        let nan = MatchConfig {
+            ball_radius: Some(f32::NAN),
+            ..default()
+        };
+        assert!(nan.out_of_range().is_some());
+        for (best_of, point_limit) in [(0, 5), (4, 5), (11, 5), (3, 1_000_000)] {
+            let config = MatchConfig {
+                best_of: Some(best_of),
+                point_limit: Some(point_limit),
+                ..default()
+            };
+            assert!(config.out_of_range().is_some(), "{best_of}, {point_limit}");
+        }
+        let fine = MatchConfig {
+            speed: Some(80.),
+            paddle_height: Some(20.),
+            best_of: Some(5),
+            point_limit: Some(0),
+            lives: Some(3),
+            ..default()
+        };
+        assert_eq!(fine.out_of_range(), None);
+    }
+}
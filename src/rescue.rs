@@ -0,0 +1,137 @@
+//! Stuck-ball rescue. A ball that goes [`Tunables::rescue_after`] seconds
+//! without crossing the center line or meeting a paddle is most likely caught
+//! bouncing wall to wall or round a corner, so it's turned part of the way
+//! toward the nearest paddle, with a faint glow where it happened. One that's
+//! still stuck gets another turn after the same wait.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena, engine::ScheduleSetup, event_log::GameplayEvent, paddle::Paddle,
+    physics::turn_toward, serve::MatchPhase, AppState, Ball, Speed, Tunables, BALL_SIZE,
+};
+
+// how far one rescue turns the ball
+const RESCUE_TURN: f32 = 30. * PI / 180.;
+const CUE_DURATION: f32 = 0.4;
+const CUE_ALPHA: f32 = 0.3;
+// the glow starts at twice the ball and spreads to this many times that
+const CUE_SIZE: Vec2 = Vec2::new(BALL_SIZE.x * 2., BALL_SIZE.y * 2.);
+const CUE_GROWTH: f32 = 2.5;
+
+pub struct RescuePlugin;
+
+impl Plugin for RescuePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(
+            AppState::Playing,
+            watch_balls.run_if(in_state(MatchPhase::Rally)),
+        )
+        .add_systems_during(AppState::Playing, fade_cues);
+    }
+}
+
+/// How long a ball has gone without getting anywhere, and which half of the
+/// field it was last in.
+#[derive(Component)]
+struct Watchdog {
+    quiet: f32,
+    far_half: bool,
+}
+
+#[derive(Component)]
+struct RescueCue {
+    age: f32,
+}
+
+fn watch_balls(
+    mut commands: Commands,
+    mut query_ball: Query<(Entity, &Transform, &mut Speed, Option<&mut Watchdog>), With<Ball>>,
+    query_paddle: Query<&Transform, (With<Paddle>, Without<Ball>)>,
+    mut events: EventReader<GameplayEvent>,
+    (arena, tunables, timer): (Res<Arena>, Res<Tunables>, Res<Time>),
+) {
+    // hits only say where the ball was, so they go to the closest one
+    let touched: Vec<Entity> = events
+        .iter()
+        .filter_map(|event| match event {
+            GameplayEvent::PaddleHit { ball, .. } => Some(*ball),
+            _ => None,
+        })
+        .filter_map(|hit| {
+            query_ball
+                .iter()
+                .min_by(|a, b| {
+                    let distance = |transform: &Transform| transform.translation.distance(hit);
+                    distance(a.1).total_cmp(&distance(b.1))
+                })
+                .map(|(entity, ..)| entity)
+        })
+        .collect();
+    let field = arena.edge(arena.goals[0]).normal();
+
+    for (entity, transform, mut speed, watchdog) in &mut query_ball {
+        // arenas are centered on the origin, so the center line runs through it
+        let far_half = transform.translation.truncate().dot(field) > 0.;
+        let Some(mut watchdog) = watchdog else {
+            commands.entity(entity).insert(Watchdog {
+                quiet: 0.,
+                far_half,
+            });
+            continue;
+        };
+        if far_half != watchdog.far_half || touched.contains(&entity) {
+            watchdog.quiet = 0.;
+            watchdog.far_half = far_half;
+            continue;
+        }
+
+        watchdog.quiet += timer.delta_seconds();
+        if tunables.rescue_after <= 0. || watchdog.quiet < tunables.rescue_after {
+            continue;
+        }
+        watchdog.quiet = 0.;
+        let ball = transform.translation;
+        let Some(paddle) = query_paddle.iter().min_by(|a, b| {
+            a.translation
+                .distance(ball)
+                .total_cmp(&b.translation.distance(ball))
+        }) else {
+            continue;
+        };
+        speed.dir = turn_toward(speed.dir, paddle.translation - ball, RESCUE_TURN);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1., 1., 1., CUE_ALPHA),
+                    custom_size: Some(CUE_SIZE),
+                    ..default()
+                },
+                // under the ball but over the court markings, still in the
+                // camera's view, which ends just below 0
+                transform: Transform::from_translation(ball.truncate().extend(-0.02)),
+                ..default()
+            },
+            RescueCue { age: 0. },
+        ));
+    }
+}
+
+fn fade_cues(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut RescueCue, &mut Transform, &mut Sprite)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut cue, mut transform, mut sprite) in &mut query {
+        cue.age += timer.delta_seconds();
+        let t = cue.age / CUE_DURATION;
+        if t >= 1. {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.scale = Vec3::splat(1. + (CUE_GROWTH - 1.) * t);
+        sprite.color.set_a(CUE_ALPHA * (1. - t));
+    }
+}
@@ -0,0 +1,118 @@
+//! Rewind power-up. Collecting the pickup banks a rewind for the rest of the
+//! point; pressing the rewind key plays the last two seconds back in reverse
+//! before play resumes from there. A game snapshot is kept for every frame of
+//! that window.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    engine::{ScheduleSetup, Stage},
+    pickup::{Pickup, PickupCollected, PointStarted},
+    snapshot::{capture, restore, GameSnapshot},
+    AppState,
+};
+
+pub const REWIND_KEY: KeyCode = KeyCode::R;
+
+// how far back a rewind goes
+const REWIND_SECONDS: f32 = 2.;
+// how long the reversed playback takes on screen
+const PLAYBACK_SECONDS: f32 = 0.5;
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rewind>()
+            .add_systems_during(
+                AppState::Playing,
+                (new_point, bank_rewind, rewind_input).chain(),
+            )
+            .add_systems_at(
+                Stage::Last,
+                record_or_play_back.run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+struct Rewind {
+    /// Snapshots from the current point, oldest first, with the time taken.
+    history: VecDeque<(f32, GameSnapshot)>,
+    held: bool,
+    playback: Option<Playback>,
+}
+
+struct Playback {
+    frames: Vec<GameSnapshot>,
+    elapsed: f32,
+}
+
+// a goal ends the point: the history can't reach back past it and an unspent
+// rewind is lost
+fn new_point(mut points: EventReader<PointStarted>, mut rewind: ResMut<Rewind>) {
+    if points.iter().count() > 0 && rewind.playback.is_none() {
+        rewind.history.clear();
+        rewind.held = false;
+    }
+}
+
+fn bank_rewind(mut collected: EventReader<PickupCollected>, mut rewind: ResMut<Rewind>) {
+    if collected.iter().any(|event| event.pickup == Pickup::Rewind) {
+        rewind.held = true;
+    }
+}
+
+fn rewind_input(mut rewind: ResMut<Rewind>, keyboard_input: Res<Input<KeyCode>>) {
+    if !rewind.held || rewind.playback.is_some() || !keyboard_input.just_pressed(REWIND_KEY) {
+        return;
+    }
+
+    rewind.held = false;
+    let frames = rewind
+        .history
+        .drain(..)
+        .map(|(_, snapshot)| snapshot)
+        .collect();
+    rewind.playback = Some(Playback {
+        frames,
+        elapsed: 0.,
+    });
+}
+
+// runs after everything else so a restored frame is what gets drawn
+fn record_or_play_back(world: &mut World) {
+    let now = world.resource::<Time>().elapsed_seconds();
+    let delta = world.resource::<Time>().delta_seconds();
+
+    let Some(mut playback) = world.resource_mut::<Rewind>().playback.take() else {
+        let snapshot = capture(world);
+        let mut rewind = world.resource_mut::<Rewind>();
+        rewind.history.push_back((now, snapshot));
+        while rewind
+            .history
+            .front()
+            .map_or(false, |(taken, _)| now - taken > REWIND_SECONDS)
+        {
+            rewind.history.pop_front();
+        }
+        return;
+    };
+
+    if playback.frames.is_empty() {
+        return;
+    }
+
+    // walk the frames newest to oldest over the playback time
+    playback.elapsed += delta;
+    let progress = (playback.elapsed / PLAYBACK_SECONDS).min(1.);
+    let last = playback.frames.len() - 1;
+    let index = last - (progress * last as f32).round() as usize;
+    restore(world, &playback.frames[index]);
+
+    if progress < 1. {
+        world.resource_mut::<Rewind>().playback = Some(playback);
+    }
+}
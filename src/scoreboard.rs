@@ -0,0 +1,178 @@
+//! The scoreboard at the top of the HUD: points for the first goal's player
+//! on the left and for the far side on the right (a second player, a bot, or
+//! in single play the walls, who score on every miss), with the longest
+//! rally so far underneath, and the games each side has won when the match
+//! is longer than one. The win meter, when it's on, sits between the two.
+
+use bevy::prelude::*;
+
+use crate::{
+    engine::ScheduleSetup,
+    paddle::{Player, Side},
+    pause::starting_match,
+    stats::MatchStats,
+    win_meter::spawn_meter,
+    AppState, GameState, Tunables,
+};
+
+pub struct ScoreboardPlugin;
+
+impl Plugin for ScoreboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::Playing, spawn_scoreboard.run_if(starting_match))
+            .add_systems_during(AppState::Playing, update_scoreboard);
+    }
+}
+
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct BestRallyText;
+
+/// The score as shown, the first goal's player first. `score` counts goals
+/// let in, so each side's points are the other goal's count.
+pub fn score_line(score: (u32, u32)) -> String {
+    let [first, far] = points(score);
+    format!("{first}  :  {far}")
+}
+
+/// Each side's points, the first goal's player first.
+pub fn points(score: (u32, u32)) -> [u32; 2] {
+    [score.1, score.0]
+}
+
+/// The side (0 for the first goal's player) that has reached `limit`
+/// points, if either has. A `limit` of 0 never ends.
+pub fn winner(score: (u32, u32), limit: u32) -> Option<usize> {
+    if limit == 0 {
+        return None;
+    }
+    points(score).iter().position(|&points| points >= limit)
+}
+
+/// Games each side has won out of the finished `games`, the first goal's
+/// player first.
+pub fn games_won(games: &[(u32, u32)]) -> [u32; 2] {
+    let mut won = [0; 2];
+    for &score in games {
+        let [first, far] = points(score);
+        won[usize::from(far > first)] += 1;
+    }
+    won
+}
+
+/// The side that has won more than half of a best-of-`best_of` match, if
+/// either has.
+pub fn match_winner(games: &[(u32, u32)], best_of: u32) -> Option<usize> {
+    games_won(games).iter().position(|&won| won > best_of / 2)
+}
+
+/// Who plays each side, the first goal's player first; in single play the
+/// far side is the walls.
+pub fn side_names<'a>(players: impl IntoIterator<Item = (&'a Player, &'a Side)>) -> [String; 2] {
+    let mut names = [String::new(), "Walls".to_owned()];
+    for (player, side) in players {
+        if let Some(name) = names.get_mut(side.0) {
+            name.clone_from(&player.name);
+        }
+    }
+    names
+}
+
+fn spawn_scoreboard(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    score_line((0, 0)),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 36.,
+                        color: Color::WHITE,
+                    },
+                ),
+                ScoreText,
+            ));
+            spawn_meter(parent);
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font,
+                        font_size: 12.,
+                        color: Color::GRAY,
+                    },
+                ),
+                BestRallyText,
+            ));
+        });
+}
+
+fn update_scoreboard(
+    mut query_score: Query<&mut Text, (With<ScoreText>, Without<BestRallyText>)>,
+    mut query_rally: Query<&mut Text, With<BestRallyText>>,
+    game_state: Res<GameState>,
+    stats: Res<MatchStats>,
+    tunables: Res<Tunables>,
+) {
+    if game_state.is_changed() {
+        for mut text in &mut query_score {
+            text.sections[0].value = score_line(game_state.score);
+        }
+    }
+    if stats.is_changed() || game_state.is_changed() {
+        let rally = format!("BEST RALLY {}", stats.longest_rally);
+        let line = if tunables.best_of > 1 {
+            let [first, far] = games_won(&game_state.games);
+            format!("GAMES {first} : {far}   {rally}")
+        } else {
+            rally
+        };
+        for mut text in &mut query_rally {
+            text.sections[0].value.clone_from(&line);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_miss_is_a_point_for_the_far_side() {
+        assert_eq!(score_line((1, 0)), "0  :  1");
+    }
+
+    #[test]
+    fn the_first_side_to_the_limit_wins() {
+        assert_eq!(winner((10, 4), 11), None);
+        assert_eq!(winner((11, 4), 11), Some(1));
+        assert_eq!(winner((2, 11), 11), Some(0));
+        assert_eq!(winner((40, 0), 0), None);
+    }
+
+    #[test]
+    fn more_than_half_the_games_takes_the_match() {
+        let games = [(3, 11), (11, 9), (4, 11)];
+        assert_eq!(games_won(&games), [2, 1]);
+        assert_eq!(match_winner(&games, 5), None);
+        assert_eq!(match_winner(&games, 3), Some(0));
+        assert_eq!(match_winner(&games[..1], 1), Some(0));
+    }
+}
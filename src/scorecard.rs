@@ -0,0 +1,252 @@
+//! Shareable scorecards: a PNG of the finished match (who played, the final
+//! score, the longest rally, the date and the serve seed, so the same match
+//! can be played again with `--seed`), drawn off screen on the CPU and saved
+//! under [`SCORECARD_DIR`] from the game-over screen.
+
+use std::{
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use ab_glyph::{point, Font as _, FontArc, PxScale, ScaleFont};
+use bevy::prelude::*;
+use image::{Rgba, RgbaImage};
+
+use crate::{
+    engine::ScheduleSetup,
+    fonts::FontFallback,
+    paddle::{Player, Side},
+    scoreboard::{score_line, side_names},
+    stats::MatchStats,
+    toast::Toast,
+    AppState, GameState, MatchSeed,
+};
+
+pub const SCORECARD_DIR: &str = "scorecards";
+
+// the usual link-preview size, so it posts without cropping
+const CARD_WIDTH: u32 = 1200;
+const CARD_HEIGHT: u32 = 630;
+const BACKGROUND: [u8; 3] = [26, 26, 26];
+const FOREGROUND: [u8; 3] = [255, 255, 255];
+const DIM: [u8; 3] = [140, 140, 140];
+const ACCENT: [u8; 3] = [230, 40, 40];
+
+pub struct ScorecardPlugin;
+
+impl Plugin for ScorecardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SaveScorecard>()
+            .add_systems_during(AppState::GameOver, save_scorecard);
+    }
+}
+
+/// Asks for the finished match's scorecard to be written out.
+pub struct SaveScorecard;
+
+struct Scorecard {
+    /// The first goal's player, then the far side's.
+    names: [String; 2],
+    score: (u32, u32),
+    longest_rally: u32,
+    date: String,
+    seed: u64,
+}
+
+impl Scorecard {
+    fn file_name(&self) -> String {
+        format!("pong-{}-{}.png", self.date, self.seed)
+    }
+
+    fn render(&self, font: &FontArc) -> RgbaImage {
+        let mut card = RgbaImage::from_pixel(CARD_WIDTH, CARD_HEIGHT, opaque(BACKGROUND));
+        // a ball and a paddle along the bottom, like the title screen
+        fill_rect(&mut card, (560, 560), (80, 12), FOREGROUND);
+        fill_rect(&mut card, (640, 500), (20, 20), ACCENT);
+
+        let center = CARD_WIDTH as f32 / 2.;
+        let names = format!("{}  vs  {}", self.names[0], self.names[1]);
+        draw_text(&mut card, font, "PONG", 56., (center, 40.), DIM);
+        draw_text(&mut card, font, &names, 44., (center, 120.), FOREGROUND);
+        draw_text(
+            &mut card,
+            font,
+            &score_line(self.score),
+            150.,
+            (center, 180.),
+            FOREGROUND,
+        );
+        let details = format!(
+            "BEST RALLY {}   {}   SEED {}",
+            self.longest_rally, self.date, self.seed
+        );
+        draw_text(&mut card, font, &details, 30., (center, 400.), DIM);
+        card
+    }
+}
+
+fn opaque([r, g, b]: [u8; 3]) -> Rgba<u8> {
+    Rgba([r, g, b, 255])
+}
+
+fn fill_rect(
+    card: &mut RgbaImage,
+    (left, top): (u32, u32),
+    (width, height): (u32, u32),
+    color: [u8; 3],
+) {
+    for y in top..(top + height).min(card.height()) {
+        for x in left..(left + width).min(card.width()) {
+            card.put_pixel(x, y, opaque(color));
+        }
+    }
+}
+
+/// Draws one line of `text` centered on `center_x`, its top at `top`.
+fn draw_text(
+    card: &mut RgbaImage,
+    font: &FontArc,
+    text: &str,
+    size: f32,
+    (center_x, top): (f32, f32),
+    color: [u8; 3],
+) {
+    let scaled = font.as_scaled(PxScale::from(size));
+    let mut glyphs = Vec::new();
+    let mut x = 0.;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            x += scaled.kern(previous, id);
+        }
+        glyphs.push((id, x));
+        x += scaled.h_advance(id);
+        previous = Some(id);
+    }
+
+    let origin = point(center_x - x / 2., top + scaled.ascent());
+    for (id, x) in glyphs {
+        let glyph = id.with_scale_and_position(size, point(origin.x + x, origin.y));
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|gx, gy, coverage| {
+            let (x, y) = (
+                bounds.min.x as i32 + gx as i32,
+                bounds.min.y as i32 + gy as i32,
+            );
+            if x < 0 || y < 0 || x >= card.width() as i32 || y >= card.height() as i32 {
+                return;
+            }
+            let pixel = card.get_pixel_mut(x as u32, y as u32);
+            for (under, over) in pixel.0.iter_mut().zip(color) {
+                *under = (*under as f32 + (over as f32 - *under as f32) * coverage) as u8;
+            }
+        });
+    }
+}
+
+/// The calendar date `days` after 1970-01-01, as (year, month, day).
+fn civil_date(days: i64) -> (i64, u32, u32) {
+    // shifted to start in March, so the leap day falls at the end of the year
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+pub fn today() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (year, month, day) = civil_date((seconds / 86_400) as i64);
+    format!("{year}-{month:02}-{day:02}")
+}
+
+fn save_scorecard(
+    mut events: EventReader<SaveScorecard>,
+    mut toasts: EventWriter<Toast>,
+    query: Query<(&Player, &Side)>,
+    (game_state, stats, seed): (Res<GameState>, Res<MatchStats>, Res<MatchSeed>),
+    (fonts, fallback, asset_server): (Res<Assets<Font>>, Res<FontFallback>, Res<AssetServer>),
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    let card = Scorecard {
+        names: side_names(&query),
+        score: game_state.score,
+        longest_rally: stats.longest_rally,
+        date: today(),
+        seed: seed.0,
+    };
+    // one that can spell both names
+    let handle = fallback
+        .font_for(&card.names.join(" "), &fonts)
+        .unwrap_or_else(|| asset_server.load("fonts/DejaVuSans-Bold.ttf"));
+    let Some(font) = fonts.get(&handle) else {
+        return;
+    };
+
+    let path = PathBuf::from(SCORECARD_DIR).join(card.file_name());
+    let saved = fs::create_dir_all(SCORECARD_DIR)
+        .map_err(image::ImageError::IoError)
+        .and_then(|()| card.render(&font.font).save(&path));
+    match saved {
+        Ok(()) => toasts.send(Toast(format!("Saved {}", path.display()))),
+        Err(err) => {
+            error!("couldn't write {}: {err}", path.display());
+            toasts.send(Toast("Couldn't save the scorecard".to_owned()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_days_into_calendar_dates() {
+        assert_eq!(civil_date(0), (1970, 1, 1));
+        assert_eq!(civil_date(11_016), (2000, 2, 29));
+        assert_eq!(civil_date(20_740), (2026, 10, 14));
+    }
+
+    #[test]
+    fn the_card_shows_the_score() {
+        let font = FontArc::try_from_vec(
+            fs::read("assets/fonts/DejaVuSans-Bold.ttf").expect("font asset"),
+        )
+        .unwrap();
+        let card = Scorecard {
+            names: ["Classic".to_owned(), "Bot".to_owned()],
+            score: (3, 11),
+            longest_rally: 24,
+            date: "2026-10-14".to_owned(),
+            seed: 7,
+        };
+        let image = card.render(&font);
+        assert_eq!(image.dimensions(), (CARD_WIDTH, CARD_HEIGHT));
+        // the score line is drawn in white across the middle
+        let lit = (180..330)
+            .flat_map(|y| (0..CARD_WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| image.get_pixel(x, y).0[..3] == FOREGROUND)
+            .count();
+        assert!(lit > 1000, "{lit}");
+        assert_eq!(card.file_name(), "pong-2026-10-14-7.png");
+    }
+}
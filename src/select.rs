@@ -0,0 +1,284 @@
+//! Pre-match select, worked through the menu focus: Left/Right on the paddle
+//! row browse the archetypes and Confirm there starts the match, each
+//! mutator below toggles on Confirm, and Back returns to the title. A
+//! gamepad works too, with prompts naming its buttons.
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::{
+    archetype::{ArchetypeList, ChosenArchetype, ARCHETYPES_PATH},
+    engine::ScheduleSetup,
+    focus::{FocusEvent, Focusable},
+    mutator::{ActiveMutators, MutatorList, MUTATORS_PATH},
+    prompt::{MenuAction, MenuInput},
+    share::MatchCode,
+    AppState,
+};
+
+pub struct SelectPlugin;
+
+impl Plugin for SelectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_on_enter(AppState::CharacterSelect, spawn_select_screen)
+            .add_systems_during(AppState::CharacterSelect, choose_archetype)
+            .add_systems_during(AppState::CharacterSelect, choose_mutators)
+            .add_systems_during(AppState::CharacterSelect, show_hint)
+            .add_systems_on_exit(AppState::CharacterSelect, despawn_select_screen);
+    }
+}
+
+#[derive(Resource)]
+struct Selection {
+    archetypes: Handle<ArchetypeList>,
+    index: usize,
+    mutators: Handle<MutatorList>,
+    mutators_on: HashSet<usize>,
+}
+
+#[derive(Component)]
+struct SelectScreen;
+
+#[derive(Component)]
+struct SelectText;
+
+/// The column the mutator lines go in once the list has loaded.
+#[derive(Component)]
+struct MutatorColumn;
+
+#[derive(Component)]
+struct MutatorLine(usize);
+
+#[derive(Component)]
+struct HintText;
+
+fn spawn_select_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    code: Option<Res<MatchCode>>,
+) {
+    // a shared code's mutators come picked
+    let mutators_on = code.map_or_else(HashSet::default, |code| {
+        (0..u32::BITS as usize)
+            .filter(|&i| code.mutators & 1 << i != 0)
+            .collect()
+    });
+    commands.insert_resource(Selection {
+        archetypes: asset_server.load(ARCHETYPES_PATH),
+        index: 0,
+        mutators: asset_server.load(MUTATORS_PATH),
+        mutators_on,
+    });
+
+    let style = TextStyle {
+        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+        font_size: 28.,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+            SelectScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "CHOOSE YOUR PADDLE",
+                style.clone(),
+            ));
+            parent.spawn((
+                TextBundle::from_section("loading...", style.clone())
+                    .with_text_alignment(TextAlignment::Center),
+                SelectText,
+                Focusable { adjusts: true },
+            ));
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::top(Val::Px(30.)),
+                        ..default()
+                    },
+                    ..default()
+                },
+                MutatorColumn,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.,
+                        ..style
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(30.)),
+                    ..default()
+                }),
+                HintText,
+            ));
+        });
+}
+
+fn choose_mutators(
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    mut events: EventReader<FocusEvent>,
+    mut query_line: Query<(Entity, &MutatorLine, &mut Text)>,
+    query_column: Query<Entity, With<MutatorColumn>>,
+    (lists, asset_server): (Res<Assets<MutatorList>>, Res<AssetServer>),
+) {
+    let Some(list) = lists.get(&selection.mutators) else {
+        return;
+    };
+
+    if query_line.is_empty() {
+        let style = TextStyle {
+            font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+            font_size: 18.,
+            color: Color::WHITE,
+        };
+        for column in &query_column {
+            commands.entity(column).with_children(|parent| {
+                parent.spawn(TextBundle::from_section("MUTATORS", style.clone()));
+                for i in 0..list.mutators.len() {
+                    parent.spawn((
+                        TextBundle::from_section("", style.clone()),
+                        MutatorLine(i),
+                        Focusable::default(),
+                    ));
+                }
+            });
+        }
+        return;
+    }
+
+    for event in events.iter() {
+        if let FocusEvent::Activated(entity) = *event {
+            if let Ok((_, line, _)) = query_line.get(entity) {
+                if !selection.mutators_on.remove(&line.0) {
+                    selection.mutators_on.insert(line.0);
+                }
+            }
+        }
+    }
+
+    for (_, line, mut text) in &mut query_line {
+        let mutator = &list.mutators[line.0];
+        text.sections[0].value = format!(
+            "[{}] {}: {}",
+            if selection.mutators_on.contains(&line.0) {
+                "x"
+            } else {
+                " "
+            },
+            mutator.name,
+            mutator.description,
+        );
+    }
+}
+
+fn choose_archetype(
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    mut events: EventReader<FocusEvent>,
+    mut query_text: Query<(Entity, &mut Text), With<SelectText>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    (lists, mutator_lists, code): (
+        Res<Assets<ArchetypeList>>,
+        Res<Assets<MutatorList>>,
+        Option<ResMut<MatchCode>>,
+    ),
+    input: MenuInput,
+) {
+    let Some(list) = lists.get(&selection.archetypes) else {
+        return;
+    };
+    let count = list.archetypes.len();
+    let Ok((row, mut text)) = query_text.get_single_mut() else {
+        return;
+    };
+    if count == 0 {
+        return;
+    }
+
+    let mut start = false;
+    for event in events.iter() {
+        match *event {
+            FocusEvent::Adjusted(entity, step) if entity == row => {
+                selection.index = (selection.index as i32 + step).rem_euclid(count as i32) as usize;
+            }
+            FocusEvent::Activated(entity) if entity == row => start = true,
+            FocusEvent::Back => next_state.set(AppState::Menu),
+            _ => {}
+        }
+    }
+
+    let archetype = &list.archetypes[selection.index % count];
+
+    if start {
+        commands.insert_resource(ChosenArchetype(archetype.clone()));
+        if let Some(mutator_list) = mutator_lists.get(&selection.mutators) {
+            let mut picked: Vec<_> = selection
+                .mutators_on
+                .iter()
+                .copied()
+                .filter(|&i| i < mutator_list.mutators.len())
+                .collect();
+            picked.sort_unstable();
+            if let Some(mut code) = code {
+                code.mutators = picked
+                    .iter()
+                    .filter_map(|&i| 1u32.checked_shl(i as u32))
+                    .sum();
+            }
+            commands.insert_resource(ActiveMutators(
+                picked
+                    .into_iter()
+                    .map(|i| mutator_list.mutators[i].clone())
+                    .collect(),
+            ));
+        }
+        next_state.set(AppState::Playing);
+        return;
+    }
+
+    text.sections[0].value = format!(
+        "{} < {} > {}\nwidth {}  speed {:.1}x  special {}",
+        input.glyph(MenuAction::Left),
+        archetype.name,
+        input.glyph(MenuAction::Right),
+        archetype.width,
+        archetype.speed,
+        archetype.special.label(),
+    );
+}
+
+fn show_hint(mut query: Query<&mut Text, With<HintText>>, input: MenuInput) {
+    for mut text in &mut query {
+        text.sections[0].value = format!(
+            "{}/{} move, {} on your paddle to start or on a mutator to toggle it, {} back",
+            input.glyph(MenuAction::Up),
+            input.glyph(MenuAction::Down),
+            input.glyph(MenuAction::Confirm),
+            input.glyph(MenuAction::Back),
+        );
+    }
+}
+
+fn despawn_select_screen(mut commands: Commands, query: Query<Entity, With<SelectScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<Selection>();
+}
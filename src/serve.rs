@@ -0,0 +1,346 @@
+//! Serving after a point. Rather than the ball flying straight back in from
+//! the serve spot, the side that let the goal in holds it on its paddle
+//! through a 3-2-1 countdown, leaning the launch with its steering keys
+//! (which stop moving that paddle meanwhile), and it goes when the count
+//! runs out. A bot serves straight up the field.
+//!
+//! That's the [`ServePattern::Paddle`] serve every match has unless a
+//! mutator picks another pattern; the others put the ball (or two) back in
+//! the field and send it straight off.
+
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::{
+    arena::Arena,
+    bindings::KeyBindings,
+    engine::ScheduleSetup,
+    gamepad::Steering,
+    paddle::{PaddleStats, Side, Steered},
+    physics::{clear_of_paddles, serve_dir},
+    AppState, Ball, Speed, Tunables,
+};
+
+pub const SERVE_COUNTDOWN: f32 = 3.;
+// how far either way of straight up the field a serve can lean
+const MAX_AIM: f32 = 60. * PI / 180.;
+// radians per second while a steering key is held
+const AIM_RATE: f32 = PI / 2.;
+// about as fast as an average random serve
+const SERVE_POWER: f32 = 8.;
+// between the paddle face and the held ball
+const SERVE_GAP: f32 = 2.;
+const AIM_MARKER_LENGTH: f32 = 40.;
+// how far in from the walls a corner serve starts
+const CORNER_INSET: f32 = 60.;
+// between the two balls of a dual serve
+const DUAL_GAP: f32 = 80.;
+
+pub struct ServePlugin;
+
+impl Plugin for ServePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<MatchPhase>()
+            .init_resource::<Serve>()
+            .add_systems_on_enter(MatchPhase::Serve, spawn_serve_cues)
+            .add_systems_during(
+                AppState::Playing,
+                hold_serve.run_if(in_state(MatchPhase::Serve)),
+            )
+            .add_systems_on_exit(MatchPhase::Serve, end_serve);
+    }
+}
+
+/// Where a match under way is, within `Playing`. The ball only moves,
+/// bounces and scores in a `Rally`.
+#[derive(States, Clone, Copy, Default, Debug, Hash, PartialEq, Eq)]
+pub enum MatchPhase {
+    #[default]
+    Rally,
+    /// A paddle holds the ball, waiting out the countdown.
+    Serve,
+}
+
+/// Where serves come from, as a mutator sets it.
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+pub enum ServePattern {
+    /// The first ball from in front of the first goal, then each one held on
+    /// the paddle of the side that let the last one in.
+    #[default]
+    Paddle,
+    /// From the middle of the field.
+    Center,
+    /// From each end of the center line in turn.
+    AlternatingCorners,
+    /// From anywhere along the center line.
+    Random,
+    /// Two balls at once either side of the middle, one up the field from
+    /// each of the first two goals.
+    Dual,
+}
+
+/// Where each ball of the match's `serves`th serve (counting from 0) starts
+/// and which way it goes, for a `pattern` serve after a goal at `goal`. The
+/// spots are slid clear of the `(position, size)` paddles.
+pub fn serve_spots(
+    pattern: ServePattern,
+    arena: &Arena,
+    goal: usize,
+    serves: u32,
+    paddles: &[(Vec3, Vec2)],
+    ball_size: Vec2,
+    rng: &mut impl Rng,
+) -> Vec<(Vec3, Vec3)> {
+    let spawn = arena.ball_spawn().truncate();
+    let (start, end) = arena.center_line().unwrap_or((spawn, spawn));
+    let across = (end - start).normalize_or_zero();
+    let inset = CORNER_INSET.min(start.distance(end) / 2.);
+    let middle = start.lerp(end, 0.5);
+    let spots = match pattern {
+        ServePattern::Paddle => vec![(spawn, goal)],
+        ServePattern::Center => vec![(middle, goal)],
+        ServePattern::AlternatingCorners if serves % 2 == 0 => {
+            vec![(start + across * inset, goal)]
+        }
+        ServePattern::AlternatingCorners => vec![(end - across * inset, goal)],
+        ServePattern::Random => {
+            let reach = start.distance(end) / 2. - inset;
+            vec![(middle + across * rng.gen_range(-reach..=reach), goal)]
+        }
+        ServePattern::Dual => vec![
+            (middle - across * DUAL_GAP / 2., 0),
+            (middle + across * DUAL_GAP / 2., 1 % arena.goals.len()),
+        ],
+    };
+
+    // the center line runs the same way as the first goal's
+    let goal_line = arena.edge(arena.goals[0]);
+    let along = (goal_line.end - goal_line.start).normalize();
+    spots
+        .into_iter()
+        .map(|(spot, goal)| {
+            (
+                clear_of_paddles(spot.extend(0.), along, paddles, ball_size),
+                serve_dir(rng, arena.edge(arena.goals[goal]).normal()),
+            )
+        })
+        .collect()
+}
+
+/// The serve being lined up.
+#[derive(Resource, Clone, Default, Debug, PartialEq)]
+pub struct Serve {
+    countdown: f32,
+    /// Radians off straight up the field, toward the goal's right.
+    aim: f32,
+}
+
+impl Serve {
+    /// Serves on the next frame rather than waiting out the countdown.
+    pub fn release(&mut self) {
+        self.countdown = 0.;
+    }
+}
+
+/// The paddle holding the ball for the serve.
+#[derive(Component)]
+pub struct Serving;
+
+#[derive(Component)]
+struct ServeCue;
+
+#[derive(Component)]
+struct CountdownText;
+
+#[derive(Component)]
+struct AimMarker;
+
+/// Whether the rally plays on. A goal handing the serve over only changes
+/// the phase once the frame's ticks are done, so the ticks left in the
+/// frame have to stop as soon as it's asked for, or they'd score the ball
+/// still past the line again.
+pub fn rally_goes_on(next_phase: Res<NextState<MatchPhase>>) -> bool {
+    next_phase.0.is_none()
+}
+
+/// Hands the serve to `paddle`; the ball waits on it from the next frame.
+pub fn start_serve(
+    commands: &mut Commands,
+    next_phase: &mut NextState<MatchPhase>,
+    paddle: Entity,
+) {
+    commands.entity(paddle).insert(Serving);
+    commands.insert_resource(Serve {
+        countdown: SERVE_COUNTDOWN,
+        aim: 0.,
+    });
+    next_phase.set(MatchPhase::Serve);
+}
+
+/// The launch direction for a serve aimed `aim` radians off `field`, the
+/// goal's unit normal, toward `axis`.
+fn launch_dir(field: Vec2, axis: Vec2, aim: f32) -> Vec3 {
+    ((field * aim.cos() + axis * aim.sin()) * SERVE_POWER).extend(0.)
+}
+
+/// The "3", "2", "1" to show with `countdown` seconds left.
+fn countdown_label(countdown: f32) -> String {
+    (countdown.ceil().max(1.) as u32).to_string()
+}
+
+fn spawn_serve_cues(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                countdown_label(SERVE_COUNTDOWN),
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 96.,
+                    color: Color::rgba(1., 1., 1., 0.8),
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            // over the playfield, at the center of the arena
+            transform: Transform::from_xyz(0., 0., 10.),
+            ..default()
+        },
+        ServeCue,
+        CountdownText,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1., 1., 1., 0.5),
+                custom_size: Some(Vec2::new(2., AIM_MARKER_LENGTH)),
+                ..default()
+            },
+            ..default()
+        },
+        ServeCue,
+        AimMarker,
+    ));
+}
+
+// keeps the ball on the server's face and the marker pointing the way it'll go
+fn hold_serve(
+    mut next_phase: ResMut<NextState<MatchPhase>>,
+    mut serve: ResMut<Serve>,
+    query_server: Query<(&Transform, &PaddleStats, &Side, Option<&Steered>), With<Serving>>,
+    mut query_ball: Query<(&mut Transform, &mut Speed), (With<Ball>, Without<Serving>)>,
+    mut query_text: Query<&mut Text, With<CountdownText>>,
+    mut query_marker: Query<&mut Transform, (With<AimMarker>, Without<Ball>, Without<Serving>)>,
+    (steering, keys, bindings, arena, tunables, timer): (
+        Steering,
+        Res<Input<KeyCode>>,
+        Res<KeyBindings>,
+        Res<Arena>,
+        Res<Tunables>,
+        Res<Time>,
+    ),
+) {
+    let Ok((paddle, stats, side, steered)) = query_server.get_single() else {
+        // the server's gone, so play on without one
+        next_phase.set(MatchPhase::Rally);
+        return;
+    };
+    let delta = timer.delta_seconds();
+    if steered.is_some() {
+        // a cursor past the paddle's end leans the serve all the way
+        let direction = steering.toward(*side, paddle.translation, stats.size.x / 2.);
+        serve.aim = (serve.aim + direction * AIM_RATE * delta).clamp(-MAX_AIM, MAX_AIM);
+        if keys.just_pressed(bindings.player(side.0).serve) {
+            serve.release();
+        }
+    }
+
+    let field = arena.edge(arena.goals[side.0]).normal();
+    let dir = launch_dir(field, arena.goal_axis(side.0), serve.aim);
+    let reach = stats.size.y / 2. + tunables.ball_size().y / 2. + SERVE_GAP;
+    let held = paddle.translation + (field * reach).extend(0.);
+    serve.countdown -= delta;
+    let released = serve.countdown <= 0.;
+
+    for (mut transform, mut speed) in &mut query_ball {
+        transform.translation = held;
+        speed.dir = if released { dir } else { Vec3::ZERO };
+    }
+    for mut text in &mut query_text {
+        text.sections[0].value = countdown_label(serve.countdown);
+    }
+    for mut transform in &mut query_marker {
+        let toward = dir.normalize();
+        transform.translation = (held + toward * AIM_MARKER_LENGTH).truncate().extend(5.);
+        transform.rotation = Quat::from_rotation_arc(Vec3::Y, toward);
+    }
+    if released {
+        next_phase.set(MatchPhase::Rally);
+    }
+}
+
+fn end_serve(
+    mut commands: Commands,
+    query_cues: Query<Entity, With<ServeCue>>,
+    query_server: Query<Entity, With<Serving>>,
+) {
+    for entity in &query_cues {
+        commands.entity(entity).despawn();
+    }
+    for entity in &query_server {
+        commands.entity(entity).remove::<Serving>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::BALL_SIZE;
+
+    #[test]
+    fn serves_lean_toward_the_aim() {
+        let straight = launch_dir(Vec2::Y, Vec2::X, 0.);
+        assert_eq!(straight, Vec3::new(0., SERVE_POWER, 0.));
+        let leaning = launch_dir(Vec2::Y, Vec2::X, MAX_AIM);
+        assert!(leaning.x > 0. && leaning.y > 0.);
+        assert!((leaning.length() - SERVE_POWER).abs() < 1e-4);
+    }
+
+    #[test]
+    fn patterns_serve_from_the_center_line() {
+        let arena = Arena::square(600.).with_opposite_goal();
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut serve =
+            |pattern, serves| serve_spots(pattern, &arena, 0, serves, &[], BALL_SIZE, &mut rng);
+
+        let [(center, _)] = serve(ServePattern::Center, 0)[..] else {
+            panic!("one ball");
+        };
+        assert!(center.abs_diff_eq(Vec3::ZERO, 0.01));
+        let (first, second) = (
+            serve(ServePattern::AlternatingCorners, 0)[0].0,
+            serve(ServePattern::AlternatingCorners, 1)[0].0,
+        );
+        assert!((first.x + second.x).abs() < 0.01 && first.x.abs() > 200.);
+        for _ in 0..20 {
+            let (spot, _) = serve(ServePattern::Random, 0)[0];
+            assert!(spot.y.abs() < 0.01 && spot.x.abs() <= 300. - CORNER_INSET);
+        }
+
+        let dual = serve(ServePattern::Dual, 0);
+        assert_eq!(dual.len(), 2);
+        // one toward each goal
+        assert!(dual[0].1.y >= 0. && dual[1].1.y <= 0.);
+    }
+
+    #[test]
+    fn counts_down_from_three() {
+        assert_eq!(countdown_label(3.), "3");
+        assert_eq!(countdown_label(2.4), "3");
+        assert_eq!(countdown_label(1.), "1");
+        assert_eq!(countdown_label(0.), "1");
+    }
+}
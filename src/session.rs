@@ -0,0 +1,148 @@
+//! The session so far: every match finished since the game started adds to
+//! it, and quitting from a menu after playing puts a short summary up
+//! (matches played, the player's points and the best rally of them all)
+//! before the game closes. Any key closes it straight away.
+
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{engine::ScheduleSetup, scoreboard::points, stats::MatchStats, AppState, GameState};
+
+// seconds the summary stays up
+const SUMMARY_TIME: f32 = 3.;
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Session>()
+            .add_event::<EndSession>()
+            .add_systems_on_enter(AppState::GameOver, record_match)
+            .add_system(end_session);
+    }
+}
+
+/// Sent in place of [`AppExit`] by the menus' Quit, to show the summary first.
+pub struct EndSession;
+
+#[derive(Resource, Default, Debug, PartialEq)]
+struct Session {
+    matches: u32,
+    /// The first goal's player's, over every match.
+    points: u32,
+    best_rally: u32,
+}
+
+impl Session {
+    fn record(&mut self, score: (u32, u32), longest_rally: u32) {
+        self.matches += 1;
+        self.points += points(score)[0];
+        self.best_rally = self.best_rally.max(longest_rally);
+    }
+
+    fn lines(&self) -> [String; 3] {
+        [
+            format!("MATCHES {}", self.matches),
+            format!("POINTS {}", self.points),
+            format!("BEST RALLY {}", self.best_rally),
+        ]
+    }
+}
+
+/// How long until the summary closes the game.
+#[derive(Component)]
+struct SessionSummary(Timer);
+
+// a match quit from the pause menu still counts; it ends here too
+fn record_match(mut session: ResMut<Session>, game_state: Res<GameState>, stats: Res<MatchStats>) {
+    session.record(game_state.score, stats.longest_rally);
+}
+
+fn end_session(
+    mut commands: Commands,
+    mut ends: EventReader<EndSession>,
+    mut exit: EventWriter<AppExit>,
+    mut query: Query<&mut SessionSummary>,
+    (session, asset_server, keyboard_input, timer): (
+        Res<Session>,
+        Res<AssetServer>,
+        Res<Input<KeyCode>>,
+        Res<Time>,
+    ),
+) {
+    if let Ok(mut summary) = query.get_single_mut() {
+        summary.0.tick(timer.raw_delta());
+        if summary.0.finished() || keyboard_input.get_just_pressed().next().is_some() {
+            exit.send(AppExit);
+        }
+        return;
+    }
+    if ends.iter().next().is_none() {
+        return;
+    }
+    // nothing to sum up before the first match
+    if session.matches == 0 {
+        exit.send(AppExit);
+        return;
+    }
+
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+        )
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(8.)),
+            ..default()
+        })
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgb(0.1, 0.1, 0.1).into(),
+                // over the game-over screen
+                z_index: ZIndex::Global(20),
+                ..default()
+            },
+            SessionSummary(Timer::from_seconds(SUMMARY_TIME, TimerMode::Once)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("SESSION".to_owned(), 64.));
+            for line in session.lines() {
+                parent.spawn(text(line, 32.));
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_add_up_their_matches() {
+        let mut session = Session::default();
+        session.record((3, 11), 7);
+        session.record((11, 4), 12);
+        assert_eq!(
+            session,
+            Session {
+                matches: 2,
+                points: 15,
+                best_rally: 12,
+            }
+        );
+        assert_eq!(session.lines()[1], "POINTS 15");
+    }
+}
@@ -0,0 +1,173 @@
+//! Match codes, for playing someone else's setup on equal terms. A code packs
+//! the serve seed, the arena, the opponent (a second player or the bot at
+//! its difficulty), the paddle mode and the mutators picked into 24
+//! characters of Crockford base32, with a check byte against typos. The
+//! game-over screen shows the code of the match just played, and `--code`
+//! sets a match up from one. Mutators go by their place in
+//! `rules.mutators.ron`, so a code means the same match wherever the rules
+//! file is the same; only the first 32 fit.
+
+use bevy::prelude::*;
+
+use crate::{bot::Difficulty, paddle::ControlMode};
+
+// Crockford's base32, without the letters that read as digits
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const ARENAS: [&str; 4] = ["square", "classic", "hex", "triangle"];
+const PADDLES: [ControlMode; 4] = [
+    ControlMode::Single,
+    ControlMode::DualMirrored,
+    ControlMode::DualOffset,
+    ControlMode::Lanes,
+];
+const BOTS: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+// seed, arena, mode, mutators and the check byte
+const CODE_BYTES: usize = 15;
+const GROUP_LENGTH: usize = 4;
+
+/// Everything a match code pins down.
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct MatchCode {
+    pub seed: u64,
+    /// One of the names `Arena::from_name` takes. Only the four arenas the
+    /// game comes with fit in a code; one of your own is sent as the square.
+    pub arena: String,
+    /// A second player on the far goal, when there's no bot.
+    pub two_player: bool,
+    pub bot: Option<Difficulty>,
+    pub paddles: ControlMode,
+    /// Bit `i` for the `i`th mutator in the rules file.
+    pub mutators: u32,
+}
+
+impl MatchCode {
+    /// The code, in dash-separated groups of four.
+    pub fn encode(&self) -> String {
+        let arena = ARENAS
+            .iter()
+            .position(|&name| name == self.arena)
+            .unwrap_or(0);
+        let bot = self
+            .bot
+            .and_then(|bot| BOTS.iter().position(|&level| level == bot))
+            .map_or(0, |level| level + 1);
+        let paddles = PADDLES
+            .iter()
+            .position(|&mode| mode == self.paddles)
+            .unwrap_or(0);
+        let mode = bot | (self.two_player as usize) << 2 | paddles << 3;
+
+        let mut bytes = Vec::with_capacity(CODE_BYTES);
+        bytes.extend(self.seed.to_be_bytes());
+        bytes.extend([arena as u8, mode as u8]);
+        bytes.extend(self.mutators.to_be_bytes());
+        bytes.push(check(&bytes));
+
+        let characters = to_base32(&bytes);
+        characters
+            .chunks(GROUP_LENGTH)
+            .map(|group| String::from_utf8_lossy(group).into_owned())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// The match `code` stands for, if it's one; case, dashes and spaces
+    /// don't matter, and O, I and L read as 0, 1 and 1.
+    pub fn decode(code: &str) -> Option<Self> {
+        let bytes = from_base32(code)?;
+        if bytes.len() != CODE_BYTES {
+            return None;
+        }
+        let (body, sum) = bytes.split_at(CODE_BYTES - 1);
+        if check(body) != sum[0] {
+            return None;
+        }
+
+        let mode = body[9] as usize;
+        let bot = match mode & 0b11 {
+            0 => None,
+            level => Some(BOTS[level - 1]),
+        };
+        Some(Self {
+            seed: u64::from_be_bytes(body[..8].try_into().ok()?),
+            arena: (*ARENAS.get(body[8] as usize)?).to_owned(),
+            two_player: mode & 0b100 != 0,
+            bot,
+            paddles: *PADDLES.get(mode >> 3)?,
+            mutators: u32::from_be_bytes(body[10..].try_into().ok()?),
+        })
+    }
+}
+
+fn check(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0x5a, |sum: u8, &byte| sum.rotate_left(3) ^ byte)
+}
+
+fn to_base32(bytes: &[u8]) -> Vec<u8> {
+    let mut characters = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for &byte in bytes {
+        buffer = buffer << 8 | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            characters.push(ALPHABET[(buffer >> bits) as usize & 31]);
+        }
+    }
+    if bits > 0 {
+        characters.push(ALPHABET[(buffer << (5 - bits)) as usize & 31]);
+    }
+    characters
+}
+
+fn from_base32(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in text.chars().filter(|c| !matches!(c, '-' | ' ')) {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let value = ALPHABET.iter().position(|&letter| letter as char == c)?;
+        buffer = buffer << 5 | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_survive_sloppy_typing_but_not_typos() {
+        let code = MatchCode {
+            seed: 0x0123_4567_89ab_cdef,
+            arena: "hex".to_owned(),
+            two_player: false,
+            bot: Some(Difficulty::Hard),
+            paddles: ControlMode::Lanes,
+            mutators: 0b1010,
+        };
+        let text = code.encode();
+        assert_eq!(text.len(), 29, "{text}");
+        assert_eq!(MatchCode::decode(&text), Some(code.clone()));
+        let sloppy = text.to_lowercase().replace('-', " ").replace('0', "o");
+        assert_eq!(MatchCode::decode(&sloppy), Some(code));
+
+        let mut typo: Vec<char> = text.chars().collect();
+        typo[3] = if typo[3] == 'Z' { 'Y' } else { 'Z' };
+        assert_eq!(
+            MatchCode::decode(&typo.into_iter().collect::<String>()),
+            None
+        );
+        assert_eq!(MatchCode::decode("ABCD"), None);
+    }
+}
@@ -0,0 +1,161 @@
+//! Shot chart: small multiples of the shots that ended the last few points,
+//! one mini field per point, oldest top left. Each path runs from the return
+//! that started the shot through its bounces to a red mark where it crossed
+//! the goal line, so where goals keep going in is easy to spot.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::stats::{MatchStats, HEATMAP_EXTENT, SHOT_HISTORY};
+
+const COLUMNS: usize = 4;
+const ROWS: usize = SHOT_HISTORY / COLUMNS;
+// pixels per mini field
+const CELL: usize = 40;
+const WIDTH: usize = COLUMNS * CELL;
+const HEIGHT: usize = ROWS * CELL;
+const BACKGROUND: [u8; 4] = [40, 40, 40, 200];
+const PATH: [u8; 4] = [255, 255, 255, 255];
+const GOAL: [u8; 4] = [255, 40, 40, 255];
+
+pub struct ShotChartPlugin;
+
+impl Plugin for ShotChartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(paint_charts);
+    }
+}
+
+#[derive(Component)]
+struct ShotChart;
+
+/// Adds a shot chart under `parent`, redrawn whenever a point ends.
+pub fn spawn_shot_chart(parent: &mut ChildBuilder, images: &mut Assets<Image>) {
+    let image = Image::new_fill(
+        Extent3d {
+            width: WIDTH as u32,
+            height: HEIGHT as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &BACKGROUND,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    parent.spawn((
+        ImageBundle {
+            style: Style {
+                size: Size::new(Val::Px(WIDTH as f32), Val::Px(HEIGHT as f32)),
+                margin: UiRect::top(Val::Px(6.)),
+                ..default()
+            },
+            image: images.add(image).into(),
+            ..default()
+        },
+        ShotChart,
+    ));
+}
+
+/// Top left pixel of mini field `cell`.
+fn origin(cell: usize) -> (usize, usize) {
+    (cell % COLUMNS * CELL, cell / COLUMNS * CELL)
+}
+
+/// The pixel of mini field `cell` that `point` falls on.
+fn pixel(cell: usize, point: Vec2) -> (usize, usize) {
+    let scale = CELL as f32 / (2. * HEATMAP_EXTENT);
+    let to_cell = |value: f32| ((value * scale) as usize).min(CELL - 1);
+    let (x0, y0) = origin(cell);
+    // image rows run top to bottom
+    (
+        x0 + to_cell(point.x + HEATMAP_EXTENT),
+        y0 + to_cell(HEATMAP_EXTENT - point.y),
+    )
+}
+
+fn put(data: &mut [u8], (x, y): (usize, usize), color: [u8; 4]) {
+    let start = (y * WIDTH + x) * 4;
+    data[start..start + 4].copy_from_slice(&color);
+}
+
+fn draw_shots<'a>(data: &mut [u8], shots: impl IntoIterator<Item = &'a Vec<Vec2>>) {
+    for pixel in data.chunks_mut(4) {
+        pixel.copy_from_slice(&BACKGROUND);
+    }
+
+    for (cell, shot) in shots.into_iter().enumerate().take(COLUMNS * ROWS) {
+        for segment in shot.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            // a step per pixel along the longer axis
+            let steps = ((end - start).abs().max_element() * CELL as f32 / (2. * HEATMAP_EXTENT))
+                .ceil()
+                .max(1.) as usize;
+            for step in 0..=steps {
+                let point = start.lerp(end, step as f32 / steps as f32);
+                put(data, pixel(cell, point), PATH);
+            }
+        }
+
+        // a 3x3 mark, kept inside the cell
+        if let Some(&goal) = shot.last() {
+            let (x, y) = pixel(cell, goal);
+            let (x0, y0) = origin(cell);
+            for y in y.saturating_sub(1).max(y0)..=(y + 1).min(y0 + CELL - 1) {
+                for x in x.saturating_sub(1).max(x0)..=(x + 1).min(x0 + CELL - 1) {
+                    put(data, (x, y), GOAL);
+                }
+            }
+        }
+    }
+}
+
+fn paint_charts(
+    mut images: ResMut<Assets<Image>>,
+    query: Query<(&UiImage, &ComputedVisibility), With<ShotChart>>,
+    stats: Res<MatchStats>,
+    mut drawn: Local<Option<u32>>,
+) {
+    // the chart only changes when a point ends
+    for (image, visibility) in &query {
+        if !visibility.is_visible() || *drawn == Some(stats.misses) {
+            continue;
+        }
+        let Some(image) = images.get_mut(&image.texture) else {
+            continue;
+        };
+        draw_shots(&mut image.data, &stats.shots);
+        *drawn = Some(stats.misses);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color_at(data: &[u8], (x, y): (usize, usize)) -> [u8; 4] {
+        let start = (y * WIDTH + x) * 4;
+        data[start..start + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn shots_are_drawn_into_their_own_cells() {
+        let mut data = vec![0; WIDTH * HEIGHT * 4];
+        let shot = vec![
+            Vec2::new(0., -280.),
+            Vec2::new(200., 0.),
+            Vec2::new(100., -290.),
+        ];
+        draw_shots(&mut data, [&shot, &shot]);
+
+        assert_eq!(color_at(&data, pixel(0, shot[2])), GOAL);
+        assert_eq!(color_at(&data, pixel(1, shot[2])), GOAL);
+        assert_eq!(color_at(&data, pixel(0, shot[1])), PATH);
+        assert_eq!(color_at(&data, pixel(2, shot[2])), BACKGROUND);
+        assert_eq!(
+            color_at(&data, pixel(0, Vec2::new(-300., 300.))),
+            BACKGROUND
+        );
+    }
+}
@@ -0,0 +1,159 @@
+//! Changing ends, an option for arenas that favor one of them: with
+//! [`Tunables::side_swap`] on, two players swap goals once the match is half
+//! played, after half its games or, in a one-game match, when either side is
+//! halfway to the point limit. Everything that follows a person moves with
+//! them: their keys, pad and mouse, their name and their points. The field
+//! and whatever belongs to a goal, the first goal's skills among them, stay
+//! where they are, so each player spends about half the match on each end.
+//! A bot has no end to prefer, so matches against one play on as they start.
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena,
+    bindings::KeyBindings,
+    bot::Difficulty,
+    engine::ScheduleSetup,
+    gamepad::PadSeats,
+    paddle::{Player, Side},
+    pause::starting_match,
+    scoreboard::points,
+    toast::Toast,
+    AppState, GameState, Tunables,
+};
+
+pub struct SideSwapPlugin;
+
+impl Plugin for SideSwapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SideSwap>()
+            .add_systems_on_enter(AppState::Playing, reset_ends.run_if(starting_match))
+            .add_systems_on_enter(AppState::Menu, reset_ends)
+            .add_systems_during(
+                AppState::Playing,
+                swap_at_halfway.run_if(resource_changed::<GameState>()),
+            )
+            .add_system(reanchor_inputs.run_if(resource_changed::<SideSwap>()));
+    }
+}
+
+/// Whether the players have changed ends this match.
+#[derive(Resource, Default, Clone, Copy)]
+pub struct SideSwap {
+    pub swapped: bool,
+}
+
+impl SideSwap {
+    /// Which player, counted the way they started, is on `side`.
+    pub fn player(&self, side: Side) -> usize {
+        match side.0 {
+            0 | 1 if self.swapped => 1 - side.0,
+            side => side,
+        }
+    }
+}
+
+/// Whether a match standing at `score` after `games` is half played: half
+/// of `best_of` games finished, or in a one-game match a side halfway to
+/// `limit`. An endless game never is.
+fn halfway(score: (u32, u32), games: usize, limit: u32, best_of: u32) -> bool {
+    if best_of > 1 {
+        return games as u32 >= best_of / 2;
+    }
+    limit > 0 && points(score).into_iter().max() >= Some((limit + 1) / 2)
+}
+
+fn reset_ends(mut swap: ResMut<SideSwap>) {
+    if swap.swapped {
+        swap.swapped = false;
+    }
+}
+
+// the points move with the players, so the scoreboard reads the same way
+// round as their ends
+fn swap_at_halfway(
+    mut swap: ResMut<SideSwap>,
+    mut game_state: ResMut<GameState>,
+    mut query: Query<(&mut Player, &Side)>,
+    mut toasts: EventWriter<Toast>,
+    (arena, tunables, bot): (Res<Arena>, Res<Tunables>, Option<Res<Difficulty>>),
+) {
+    if !tunables.side_swap
+        || swap.swapped
+        || arena.goals.len() != 2
+        || bot.is_some()
+        || !halfway(
+            game_state.score,
+            game_state.games.len(),
+            tunables.point_limit,
+            tunables.best_of,
+        )
+    {
+        return;
+    }
+    swap.swapped = true;
+
+    let GameState { score, games } = &mut *game_state;
+    *score = (score.1, score.0);
+    for game in games {
+        *game = (game.1, game.0);
+    }
+    let mut names = [String::new(), String::new()];
+    for (player, side) in &query {
+        if let Some(name) = 1usize
+            .checked_sub(side.0)
+            .and_then(|end| names.get_mut(end))
+        {
+            name.clone_from(&player.name);
+        }
+    }
+    for (mut player, side) in &mut query {
+        if let Some(name) = names.get_mut(side.0) {
+            player.name = std::mem::take(name);
+        }
+    }
+    toasts.send(Toast("Sides swapped".to_owned()));
+}
+
+// keys and pads belong to people, so they follow whichever end `SideSwap`
+// has them at, a rewind or a new match putting them back too
+fn reanchor_inputs(
+    mut bindings: ResMut<KeyBindings>,
+    mut seats: ResMut<PadSeats>,
+    mut applied: Local<bool>,
+    swap: Res<SideSwap>,
+) {
+    if *applied == swap.swapped {
+        return;
+    }
+    *applied = swap.swapped;
+    if bindings.players.len() >= 2 {
+        bindings.players.swap(0, 1);
+    }
+    if seats.0.len() < 2 {
+        seats.0.resize(2, None);
+    }
+    seats.0.swap(0, 1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ends_change_at_halfway() {
+        assert!(!halfway((5, 3), 0, 11, 1));
+        assert!(halfway((6, 3), 0, 11, 1));
+        assert!(halfway((0, 2), 0, 3, 1));
+        assert!(!halfway((40, 0), 0, 0, 1));
+        assert!(!halfway((0, 0), 1, 11, 5));
+        assert!(halfway((0, 0), 2, 11, 5));
+        assert!(halfway((0, 0), 1, 11, 3));
+
+        let swap = SideSwap { swapped: true };
+        assert_eq!(swap.player(Side(0)), 1);
+        assert_eq!(swap.player(Side(1)), 0);
+        assert_eq!(swap.player(Side(2)), 2);
+        assert_eq!(SideSwap::default().player(Side(1)), 1);
+    }
+}
@@ -0,0 +1,236 @@
+//! Headless ball simulation. Steps the same contact rules the game systems use
+//! (walls, a paddle, goal lines, splits) over plain data, with a seeded rng
+//! driving serves and a wandering paddle, so long runs can be replayed and
+//! checked without a window.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    arena::Arena,
+    paddle::PADDLE_SPEED,
+    physics::{
+        ball_spawn, crossed_goal, paddle_bounce, push_out_of_paddle, push_out_of_wall, reflect,
+        serve_dir, split, sweep_box, sweep_wall, turned_size, MAX_SWEEP_BOUNCES,
+    },
+    Speed, BALL_SIZE, DEFAULT_SPEED, MAX_BALLS, MAX_BOUNCE_ANGLE, PLAYER_SIZE, SPLIT_ANGLE,
+    SPLIT_SLOWDOWN, SPLIT_SPEED,
+};
+
+#[derive(Clone)]
+pub struct SimConfig {
+    pub arena: Arena,
+    pub paddle_size: Vec2,
+    pub ball_size: Vec2,
+    /// Seconds per step.
+    pub dt: f32,
+    /// Balls served at the start.
+    pub balls: usize,
+    /// Upper limit on balls in play; splits stop once it's reached.
+    pub max_balls: usize,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            arena: Arena::default(),
+            paddle_size: PLAYER_SIZE,
+            ball_size: BALL_SIZE,
+            dt: 1. / 60.,
+            balls: 1,
+            max_balls: MAX_BALLS,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SimBall {
+    pub translation: Vec3,
+    pub speed: Speed,
+}
+
+pub struct Simulation {
+    config: SimConfig,
+    rng: StdRng,
+    balls: Vec<SimBall>,
+    paddle: Vec3,
+    score: u32,
+    steps: u64,
+}
+
+impl Simulation {
+    pub fn new(config: SimConfig, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let field = config.arena.edge(config.arena.goals[0]).normal();
+        let balls = (0..config.balls)
+            .map(|_| SimBall {
+                translation: config.arena.ball_spawn(),
+                speed: Speed {
+                    dir: serve_dir(&mut rng, field),
+                    speed_multiplier: DEFAULT_SPEED,
+                },
+            })
+            .collect();
+
+        Self {
+            paddle: config.arena.paddle_spawn(),
+            config,
+            rng,
+            balls,
+            score: 0,
+            steps: 0,
+        }
+    }
+
+    pub fn config(&self) -> &SimConfig {
+        &self.config
+    }
+
+    pub fn balls(&self) -> &[SimBall] {
+        &self.balls
+    }
+
+    pub fn paddle(&self) -> Vec3 {
+        self.paddle
+    }
+
+    /// Goals conceded so far.
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Advances one frame with the paddle wandering at random.
+    pub fn step(&mut self) {
+        let action = self.rng.gen_range(-1.0..=1.0);
+        self.step_with(action);
+    }
+
+    /// Advances one frame in the same order as the game: paddle, a swept
+    /// move that bounces off whatever it meets, depenetrate, goals. `action`
+    /// moves the paddle at that fraction of its speed, from -1 (full left)
+    /// to 1 (full right).
+    pub fn step_with(&mut self, action: f32) {
+        let dt = self.config.dt;
+        let arena = &self.config.arena;
+
+        let nudge = action.clamp(-1., 1.) * PADDLE_SPEED * dt;
+        self.paddle = arena.slide(0, self.paddle, nudge, self.config.paddle_size.x / 2.);
+        let paddle_size = turned_size(self.config.paddle_size, arena.paddle_rotation(0));
+        let ball_size = self.config.ball_size;
+
+        let ball_count = self.balls.len();
+        let mut split_offs = Vec::new();
+        for ball in &mut self.balls {
+            let speed = &mut ball.speed;
+            let mut motion = speed.dir * speed.speed_multiplier * dt;
+            speed.speed_multiplier = DEFAULT_SPEED;
+
+            for _ in 0..MAX_SWEEP_BOUNCES {
+                let walls = arena.walls().filter_map(|edge| {
+                    let (center, wall) = (edge.midpoint().extend(0.), edge.collider());
+                    let toi = sweep_wall(ball.translation, motion, center, &wall, ball_size)?;
+                    Some((toi, wall.normal.extend(0.), false))
+                });
+                let paddle = sweep_box(
+                    ball.translation,
+                    motion,
+                    self.paddle,
+                    paddle_size,
+                    ball_size,
+                )
+                .map(|(toi, normal)| (toi, normal, true));
+                let Some((toi, normal, off_paddle)) =
+                    walls.chain(paddle).min_by(|a, b| a.0.total_cmp(&b.0))
+                else {
+                    ball.translation += motion;
+                    break;
+                };
+                ball.translation += motion * toi;
+                let left = motion.length() * (1. - toi);
+
+                if !off_paddle {
+                    speed.dir = reflect(speed.dir, normal);
+                    speed.speed_multiplier *= 2.;
+                    motion = speed.dir.normalize_or_zero() * left;
+                    continue;
+                }
+
+                speed.dir = paddle_bounce(
+                    speed.dir,
+                    normal,
+                    ball.translation,
+                    self.paddle,
+                    paddle_size,
+                    MAX_BOUNCE_ANGLE,
+                );
+                speed.speed_multiplier *= 2.;
+
+                let in_play = ball_count + split_offs.len();
+                if speed.dir.length() * DEFAULT_SPEED > SPLIT_SPEED
+                    && in_play < self.config.max_balls
+                {
+                    let [kept, split_off] = split(speed.dir, SPLIT_ANGLE, SPLIT_SLOWDOWN);
+                    speed.dir = kept;
+                    split_offs.push(SimBall {
+                        translation: ball.translation,
+                        speed: Speed {
+                            dir: split_off,
+                            speed_multiplier: DEFAULT_SPEED,
+                        },
+                    });
+                }
+                motion = speed.dir.normalize_or_zero() * left;
+            }
+        }
+        self.balls.extend(split_offs);
+
+        for ball in &mut self.balls {
+            if let Some(pushed) =
+                push_out_of_paddle(ball.translation, self.paddle, paddle_size, ball_size)
+            {
+                ball.translation = pushed;
+            }
+            for edge in arena.walls() {
+                let center = edge.midpoint().extend(0.);
+                if let Some(pushed) =
+                    push_out_of_wall(ball.translation, center, &edge.collider(), ball_size)
+                {
+                    ball.translation = pushed;
+                }
+            }
+        }
+
+        // extra balls from a split just leave play, the last one goes back to the start
+        let mut index = 0;
+        while index < self.balls.len() {
+            if crossed_goal(arena, self.balls[index].translation, ball_size).is_none() {
+                index += 1;
+                continue;
+            }
+
+            self.score += 1;
+            if self.balls.len() > 1 {
+                self.balls.swap_remove(index);
+            } else {
+                self.balls[index].translation =
+                    ball_spawn(arena, &[(self.paddle, paddle_size)], ball_size);
+                index += 1;
+            }
+        }
+
+        self.steps += 1;
+    }
+}
+
+/// Runs `n` steps of a fresh simulation seeded with `seed`.
+pub fn simulate_steps(config: SimConfig, seed: u64, n: u64) -> Simulation {
+    let mut sim = Simulation::new(config, seed);
+    for _ in 0..n {
+        sim.step();
+    }
+    sim
+}
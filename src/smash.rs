@@ -0,0 +1,15 @@
+//! Perfectly timed smashes: tapping the smash key just before the ball meets
+//! the paddle fires it back with a burst of speed. The paddle-hit response
+//! checks the player's input buffer for a recent enough tap.
+
+use bevy::prelude::*;
+
+pub const SMASH_KEY: KeyCode = KeyCode::RShift;
+
+/// How long before contact a tap still counts.
+pub const SMASH_WINDOW: f32 = 0.12;
+const SMASH_BOOST: f32 = 1.5;
+
+pub fn smashed_dir(dir: Vec3) -> Vec3 {
+    dir * SMASH_BOOST
+}
@@ -0,0 +1,380 @@
+//! Whole-game snapshots. [`capture`] copies everything that decides how play
+//! continues (balls and the paddle each last bounced off, paddles and their
+//! meters, the score and lives, which end each player is at, a serve being
+//! lined up, running timers and the serve rng) and [`restore`] puts it back,
+//! so save/resume, rollback, replays and rewinds can share one
+//! implementation.
+//!
+//! Bricks and power-ups aren't in a snapshot: bricks knocked out since the
+//! capture stay gone, and power-ups drifting or in effect carry on as they
+//! are.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    ball_bundle,
+    block::Stance,
+    charge::Charge,
+    input::InputBuffer,
+    lives::Lives,
+    ownership::Owner,
+    paddle::{Paddle, Player},
+    serve::{MatchPhase, Serve, Serving},
+    side_swap::SideSwap,
+    special::{Curve, Energy, SlowMotion},
+    spin::Spin,
+    survival::Survival,
+    time_scale::TimeScale,
+    Ball, BallAssets, GameRng, GameState, LastPaddleHit, Speed,
+};
+
+#[derive(Clone)]
+pub struct GameSnapshot {
+    balls: Vec<BallSnapshot>,
+    paddles: Vec<PaddleSnapshot>,
+    game_state: GameState,
+    slow_motion: SlowMotion,
+    survival: Survival,
+    lives: Lives,
+    side_swap: SideSwap,
+    phase: MatchPhase,
+    serve: Serve,
+    rng: GameRng,
+}
+
+#[derive(Clone)]
+struct BallSnapshot {
+    translation: Vec3,
+    speed: Speed,
+    curve: Option<Curve>,
+    spin: Option<Spin>,
+    time_scale: Option<TimeScale>,
+    owner: Option<Owner>,
+    last_hit: Option<LastPaddleHit>,
+}
+
+// paddles live for the whole match, so they're matched back up by entity
+#[derive(Clone)]
+struct PaddleSnapshot {
+    entity: Entity,
+    transform: Transform,
+    stance: Stance,
+    charge: Option<Charge>,
+    energy: Option<Energy>,
+    buffer: Option<InputBuffer>,
+    time_scale: Option<TimeScale>,
+    player: Option<Player>,
+    serving: bool,
+}
+
+impl GameSnapshot {
+    /// This snapshot with `balls` in play instead and the serve rng seeded
+    /// from `seed`, for setting up an exact situation to practice.
+    pub fn staged(mut self, balls: impl IntoIterator<Item = (Vec3, Speed)>, seed: u64) -> Self {
+        self.balls = balls
+            .into_iter()
+            .map(|(translation, speed)| BallSnapshot {
+                translation,
+                speed,
+                curve: None,
+                spin: None,
+                time_scale: None,
+                owner: None,
+                last_hit: None,
+            })
+            .collect();
+        self.rng = GameRng(StdRng::seed_from_u64(seed));
+        self
+    }
+}
+
+pub fn capture(world: &mut World) -> GameSnapshot {
+    let balls = world
+        .query_filtered::<(
+            &Transform,
+            &Speed,
+            Option<&Curve>,
+            Option<&Spin>,
+            Option<&TimeScale>,
+            Option<&Owner>,
+            Option<&LastPaddleHit>,
+        ), With<Ball>>()
+        .iter(world)
+        .map(
+            |(transform, speed, curve, spin, time_scale, owner, last_hit)| BallSnapshot {
+                translation: transform.translation,
+                speed: *speed,
+                curve: curve.cloned(),
+                spin: spin.copied(),
+                time_scale: time_scale.copied(),
+                owner: owner.copied(),
+                last_hit: last_hit.copied(),
+            },
+        )
+        .collect();
+
+    let paddles = world
+        .query_filtered::<(
+            Entity,
+            &Transform,
+            &Stance,
+            Option<&Charge>,
+            Option<&Energy>,
+            Option<&InputBuffer>,
+            Option<&TimeScale>,
+            Option<&Player>,
+            Option<&Serving>,
+        ), With<Paddle>>()
+        .iter(world)
+        .map(
+            |(entity, transform, stance, charge, energy, buffer, time_scale, player, serving)| {
+                PaddleSnapshot {
+                    entity,
+                    transform: *transform,
+                    stance: *stance,
+                    charge: charge.cloned(),
+                    energy: energy.cloned(),
+                    buffer: buffer.cloned(),
+                    time_scale: time_scale.copied(),
+                    player: player.cloned(),
+                    serving: serving.is_some(),
+                }
+            },
+        )
+        .collect();
+
+    GameSnapshot {
+        balls,
+        paddles,
+        game_state: world.resource::<GameState>().clone(),
+        slow_motion: world.resource::<SlowMotion>().clone(),
+        survival: world.resource::<Survival>().clone(),
+        lives: world.resource::<Lives>().clone(),
+        side_swap: *world.resource::<SideSwap>(),
+        phase: world.resource::<State<MatchPhase>>().0,
+        serve: world.resource::<Serve>().clone(),
+        rng: world.resource::<GameRng>().clone(),
+    }
+}
+
+/// Rewinds `world` to `snapshot`. Balls are respawned since splits and goals
+/// change how many there are; paddles despawned since the capture are skipped.
+pub fn restore(world: &mut World, snapshot: &GameSnapshot) {
+    let stale: Vec<Entity> = world
+        .query_filtered::<Entity, With<Ball>>()
+        .iter(world)
+        .collect();
+    for entity in stale {
+        world.despawn(entity);
+    }
+
+    let bundles: Vec<_> = {
+        let assets = world.resource::<BallAssets>();
+        snapshot
+            .balls
+            .iter()
+            .map(|ball| {
+                let bundle = ball_bundle(assets, ball.translation, ball.speed);
+                let extras = (
+                    ball.curve.clone(),
+                    ball.spin,
+                    ball.time_scale,
+                    ball.owner,
+                    ball.last_hit,
+                );
+                (bundle, extras)
+            })
+            .collect()
+    };
+    for (bundle, (curve, spin, time_scale, owner, last_hit)) in bundles {
+        let mut entity = world.spawn(bundle);
+        if let Some(curve) = curve {
+            entity.insert(curve);
+        }
+        if let Some(spin) = spin {
+            entity.insert(spin);
+        }
+        if let Some(time_scale) = time_scale {
+            entity.insert(time_scale);
+        }
+        if let Some(owner) = owner {
+            entity.insert(owner);
+        }
+        if let Some(last_hit) = last_hit {
+            entity.insert(last_hit);
+        }
+    }
+
+    for paddle in &snapshot.paddles {
+        let Some(mut entity) = world.get_entity_mut(paddle.entity) else {
+            continue;
+        };
+        entity.insert((paddle.transform, paddle.stance));
+        if let Some(charge) = &paddle.charge {
+            entity.insert(charge.clone());
+        }
+        if let Some(energy) = &paddle.energy {
+            entity.insert(energy.clone());
+        }
+        if let Some(buffer) = &paddle.buffer {
+            entity.insert(buffer.clone());
+        }
+        // a haste the rewind goes back before comes off
+        match paddle.time_scale {
+            Some(time_scale) => entity.insert(time_scale),
+            None => entity.remove::<TimeScale>(),
+        };
+        if let Some(player) = &paddle.player {
+            entity.insert(player.clone());
+        }
+        if paddle.serving {
+            entity.insert(Serving);
+        } else {
+            entity.remove::<Serving>();
+        }
+    }
+
+    // through a transition, so the serve cues come and go with the phase
+    let phase = world.resource::<State<MatchPhase>>().0;
+    world.resource_mut::<NextState<MatchPhase>>().0 =
+        (phase != snapshot.phase).then_some(snapshot.phase);
+    world.insert_resource(snapshot.serve.clone());
+
+    world.insert_resource(snapshot.game_state.clone());
+    world.insert_resource(snapshot.slow_motion.clone());
+    world.insert_resource(snapshot.survival.clone());
+    world.insert_resource(snapshot.lives.clone());
+    world.insert_resource(snapshot.side_swap);
+    world.insert_resource(snapshot.rng.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::serve::start_serve;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.insert_resource(BallAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        });
+        world.init_resource::<GameState>();
+        world.init_resource::<SlowMotion>();
+        world.init_resource::<Survival>();
+        world.init_resource::<Lives>();
+        world.init_resource::<SideSwap>();
+        world.insert_resource(State(MatchPhase::Rally));
+        world.init_resource::<NextState<MatchPhase>>();
+        world.init_resource::<Serve>();
+        world.insert_resource(GameRng(rand::SeedableRng::seed_from_u64(3)));
+        world
+    }
+
+    fn ball_positions(world: &mut World) -> Vec<Vec3> {
+        world
+            .query_filtered::<&Transform, With<Ball>>()
+            .iter(world)
+            .map(|transform| transform.translation)
+            .collect()
+    }
+
+    #[test]
+    fn restore_undoes_play_since_capture() {
+        let mut world = world();
+        let assets = world.resource::<BallAssets>();
+        let bundle = ball_bundle(assets, Vec3::new(1., 2., 0.), Speed::default());
+        world.spawn(bundle);
+        let paddle = world
+            .spawn((
+                Paddle,
+                Transform::default(),
+                Stance::Normal,
+                Energy::default(),
+            ))
+            .id();
+
+        let snapshot = capture(&mut world);
+        let next_roll: f32 = world.resource::<GameRng>().clone().0.gen();
+
+        // play on: the ball moves, a second one appears, the paddle braces, a point is scored
+        for mut transform in world
+            .query_filtered::<&mut Transform, With<Ball>>()
+            .iter_mut(&mut world)
+        {
+            transform.translation = Vec3::new(50., 50., 0.);
+        }
+        let bundle = ball_bundle(world.resource(), Vec3::ZERO, Speed::default());
+        world.spawn(bundle);
+        world.entity_mut(paddle).insert(Stance::Blocking);
+        world.resource_mut::<GameState>().score.0 += 1;
+        world.resource_mut::<GameRng>().0.gen::<f32>();
+
+        restore(&mut world, &snapshot);
+
+        assert_eq!(ball_positions(&mut world), vec![Vec3::new(1., 2., 0.)]);
+        assert!(*world.get::<Stance>(paddle).unwrap() == Stance::Normal);
+        assert_eq!(world.resource::<GameState>().score, (0, 0));
+        assert_eq!(world.resource_mut::<GameRng>().0.gen::<f32>(), next_roll);
+    }
+
+    #[test]
+    fn restore_goes_back_to_a_serve_being_lined_up() {
+        let mut world = world();
+        let paddle = world
+            .spawn((Paddle, Transform::default(), Stance::Normal))
+            .id();
+        let assets = world.resource::<BallAssets>();
+        let bundle = ball_bundle(assets, Vec3::ZERO, Speed::default());
+        let hit = LastPaddleHit { paddle, age: 0.5 };
+        world.spawn((bundle, hit));
+
+        let mut schedule = Schedule::new();
+        schedule.add_system(
+            move |mut commands: Commands, mut next_phase: ResMut<NextState<MatchPhase>>| {
+                start_serve(&mut commands, &mut next_phase, paddle);
+            },
+        );
+        schedule.run(&mut world);
+        world.resource_mut::<State<MatchPhase>>().0 = MatchPhase::Serve;
+        world.resource_mut::<NextState<MatchPhase>>().0 = None;
+        let serve = world.resource::<Serve>().clone();
+
+        let snapshot = capture(&mut world);
+
+        // the serve goes and the rally gets under way
+        world.resource_mut::<Serve>().release();
+        world.entity_mut(paddle).remove::<Serving>();
+        world.resource_mut::<State<MatchPhase>>().0 = MatchPhase::Rally;
+        for entity in world
+            .query_filtered::<Entity, With<Ball>>()
+            .iter(&world)
+            .collect::<Vec<_>>()
+        {
+            world.entity_mut(entity).remove::<LastPaddleHit>();
+        }
+
+        restore(&mut world, &snapshot);
+
+        assert!(world.get::<Serving>(paddle).is_some());
+        assert_eq!(*world.resource::<Serve>(), serve);
+        assert_eq!(
+            world.resource::<NextState<MatchPhase>>().0,
+            Some(MatchPhase::Serve)
+        );
+        let hits: Vec<_> = world
+            .query::<&LastPaddleHit>()
+            .iter(&world)
+            .map(|hit| (hit.paddle, hit.age))
+            .collect();
+        assert_eq!(hits, vec![(paddle, 0.5)]);
+
+        // once the serve's back, a second restore leaves the phase be
+        world.resource_mut::<State<MatchPhase>>().0 = MatchPhase::Serve;
+        restore(&mut world, &snapshot);
+        assert_eq!(world.resource::<NextState<MatchPhase>>().0, None);
+    }
+}
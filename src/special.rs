@@ -0,0 +1,300 @@
+//! Energy and special moves. Every successful return charges the player's
+//! energy meter; once it's full the special key spends it on the special the
+//! player picked for the match.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    arena::Arena,
+    bindings::KeyBindings,
+    engine::ScheduleSetup,
+    input::{Action, InputBuffer, InputSet},
+    paddle::{PaddleStats, Player, Side},
+    AppState, Ball, Speed,
+};
+
+pub const SPECIAL_KEY: KeyCode = KeyCode::RControl;
+
+const MAX_ENERGY: f32 = 100.;
+const ENERGY_PER_RETURN: f32 = 25.;
+// a press this early still fires once the meter fills up
+const SPECIAL_BUFFER: f32 = 0.15;
+
+const TIME_SLOW_DURATION: f32 = 3.;
+pub const TIME_SLOW_SCALE: f32 = 0.4;
+const DASH_DISTANCE: f32 = 150.;
+// sideways acceleration of a curve shot, in `Speed::dir` units per second
+const CURVE_STRENGTH: f32 = 12.;
+const CURVE_DURATION: f32 = 1.;
+
+pub struct SpecialPlugin;
+
+impl Plugin for SpecialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SlowMotion>()
+            .add_event::<SpecialActivated>()
+            .add_systems_during(
+                AppState::Playing,
+                (
+                    activate_special.after(InputSet),
+                    time_slow.after(activate_special),
+                    paddle_dash.after(activate_special),
+                    curve_shot.after(activate_special),
+                    curve_balls,
+                    update_energy_bars,
+                ),
+            );
+    }
+}
+
+#[derive(Component, Deserialize, Clone, Copy, Default, PartialEq, Debug)]
+pub enum Special {
+    /// Slows every ball down for a few seconds.
+    #[default]
+    TimeSlow,
+    /// Jumps the paddle sideways in the held direction.
+    Dash,
+    /// Bends the player's last return.
+    CurveShot,
+}
+
+impl Special {
+    pub fn label(self) -> &'static str {
+        match self {
+            Special::TimeSlow => "TIME SLOW",
+            Special::Dash => "DASH",
+            Special::CurveShot => "CURVE",
+        }
+    }
+}
+
+#[derive(Component, Default, Clone)]
+pub struct Energy {
+    value: f32,
+}
+
+impl Energy {
+    pub fn gain_return(&mut self) {
+        self.value = (self.value + ENERGY_PER_RETURN).min(MAX_ENERGY);
+    }
+}
+
+/// Global slowdown of ball movement left running by the time-slow special.
+#[derive(Resource, Default, Clone)]
+pub struct SlowMotion {
+    remaining: f32,
+}
+
+impl SlowMotion {
+    /// Slows the balls for at least `seconds` more.
+    pub fn slow_for(&mut self, seconds: f32) {
+        self.remaining = self.remaining.max(seconds);
+    }
+
+    pub fn scale(&self) -> f32 {
+        if self.remaining > 0. {
+            TIME_SLOW_SCALE
+        } else {
+            1.
+        }
+    }
+}
+
+pub struct SpecialActivated {
+    pub player: Entity,
+    pub special: Special,
+}
+
+#[derive(Component, Clone)]
+pub struct Curve {
+    accel: Vec3,
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct EnergyFill {
+    player: Entity,
+}
+
+/// Adds an energy meter for `player` to the bottom left of the screen.
+pub fn spawn_energy_bar(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    player: Entity,
+    special: Special,
+) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(20.),
+                    bottom: Val::Px(20.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                special.label(),
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 16.,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(200.), Val::Px(12.)),
+                        ..default()
+                    },
+                    background_color: Color::DARK_GRAY.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: Color::CYAN.into(),
+                            ..default()
+                        },
+                        EnergyFill { player },
+                    ));
+                });
+        });
+}
+
+fn activate_special(
+    mut query: Query<(Entity, &mut Energy, &mut InputBuffer, &Special)>,
+    mut activations: EventWriter<SpecialActivated>,
+    (keyboard_input, bindings, arena): (Res<Input<KeyCode>>, Res<KeyBindings>, Res<Arena>),
+) {
+    for (player, mut energy, mut buffer, &special) in &mut query {
+        // a dash needs a direction to go in
+        let aimless = special == Special::Dash
+            && !keyboard_input.any_pressed(bindings.steering(Side(0), &arena));
+
+        if energy.value >= MAX_ENERGY && !aimless && buffer.take(Action::Special, SPECIAL_BUFFER) {
+            energy.value = 0.;
+            activations.send(SpecialActivated { player, special });
+        }
+    }
+}
+
+fn time_slow(
+    mut activations: EventReader<SpecialActivated>,
+    mut slow_motion: ResMut<SlowMotion>,
+    timer: Res<Time>,
+) {
+    if activations
+        .iter()
+        .any(|activation| activation.special == Special::TimeSlow)
+    {
+        slow_motion.remaining = TIME_SLOW_DURATION;
+    }
+
+    slow_motion.remaining = (slow_motion.remaining - timer.delta_seconds()).max(0.);
+}
+
+fn paddle_dash(
+    mut activations: EventReader<SpecialActivated>,
+    mut query: Query<(&mut Transform, &PaddleStats), With<Player>>,
+    (keyboard_input, bindings, arena): (Res<Input<KeyCode>>, Res<KeyBindings>, Res<Arena>),
+) {
+    for activation in activations.iter() {
+        if activation.special != Special::Dash {
+            continue;
+        }
+        let Ok((mut transform, stats)) = query.get_mut(activation.player) else {
+            continue;
+        };
+        let [left, _] = bindings.steering(Side(0), &arena);
+        let dir = if keyboard_input.pressed(left) {
+            -1.
+        } else {
+            1.
+        };
+        transform.translation = arena.slide(
+            0,
+            transform.translation,
+            dir * DASH_DISTANCE,
+            stats.size.x / 2.,
+        );
+    }
+}
+
+// curves every ball currently flying away from the goal, i.e. the player's last return
+fn curve_shot(
+    mut commands: Commands,
+    mut activations: EventReader<SpecialActivated>,
+    query: Query<(Entity, &Speed), With<Ball>>,
+    arena: Res<Arena>,
+) {
+    let away = arena.edge(arena.goals[0]).normal().extend(0.);
+
+    for activation in activations.iter() {
+        if activation.special != Special::CurveShot {
+            continue;
+        }
+
+        for (entity, speed) in &query {
+            if speed.dir.dot(away) <= 0. {
+                continue;
+            }
+
+            // bend back against the ball's sideways drift, like a banana shot
+            let across = away.cross(Vec3::Z);
+            let side = across * -speed.dir.dot(across).signum();
+            commands.entity(entity).insert(Curve {
+                accel: side * CURVE_STRENGTH,
+                remaining: CURVE_DURATION,
+            });
+        }
+    }
+}
+
+fn curve_balls(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Speed, &mut Curve)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut speed, mut curve) in &mut query {
+        let delta = timer.delta_seconds().min(curve.remaining);
+        let magnitude = speed.dir.length();
+
+        // steer without changing how fast the ball goes
+        speed.dir = (speed.dir + curve.accel * delta).normalize_or_zero() * magnitude;
+        curve.remaining -= delta;
+
+        if curve.remaining <= 0. {
+            commands.entity(entity).remove::<Curve>();
+        }
+    }
+}
+
+fn update_energy_bars(
+    mut query_fills: Query<(&EnergyFill, &mut Style, &mut BackgroundColor)>,
+    query_energy: Query<&Energy>,
+) {
+    for (fill, mut style, mut color) in &mut query_fills {
+        let Ok(energy) = query_energy.get(fill.player) else {
+            continue;
+        };
+
+        style.size.width = Val::Percent(energy.value / MAX_ENERGY * 100.);
+        *color = if energy.value >= MAX_ENERGY {
+            Color::GOLD.into()
+        } else {
+            Color::CYAN.into()
+        };
+    }
+}
@@ -0,0 +1,287 @@
+//! Spectator view for streaming a match. F8 frees the camera (drag with the
+//! right mouse button or push the right stick to pan, scroll or pull the
+//! triggers to zoom) and shows an overlay with the rally count, possession and
+//! a graph of recent ball speed, over a chart of the shots that ended the last
+//! few points. Holding Backspace (or the gamepad's Select) there clears the
+//! stats. Turning it off puts the camera back.
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+};
+
+use crate::{
+    engine::{ScheduleSetup, Stage},
+    hold::{spawn_hold_button, HoldConfirmed},
+    hotkey::Hotkeys,
+    shot_chart::spawn_shot_chart,
+    stats::{MatchStats, SPEED_SAMPLES},
+    toast::Toast,
+    DEFAULT_SPEED,
+};
+
+pub const SPECTATOR_KEY: KeyCode = KeyCode::F8;
+
+// stick pan in pixels per second at 1x zoom
+const PAN_SPEED: f32 = 600.;
+// zoom change per scroll line
+const ZOOM_STEP: f32 = 0.1;
+// zoom change per second of a fully pulled trigger
+const TRIGGER_ZOOM_SPEED: f32 = 1.;
+const ZOOM_RANGE: (f32, f32) = (0.25, 4.);
+const STICK_DEAD_ZONE: f32 = 0.15;
+const GRAPH_HEIGHT: f32 = 60.;
+const GRAPH_BAR_WIDTH: f32 = 3.;
+// speeds at or above this fill the graph
+const GRAPH_MAX_SPEED: f32 = 20. * DEFAULT_SPEED;
+const RESET_INPUT: (KeyCode, GamepadButtonType) = (KeyCode::Back, GamepadButtonType::Select);
+const RESET_HOLD: f32 = 1.5;
+
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Spectator>()
+            .add_systems_at(Stage::Startup, spawn_overlay)
+            .add_system(toggle_spectator)
+            .add_system(free_camera.after(toggle_spectator))
+            .add_system(update_overlay.after(toggle_spectator))
+            .add_system(reset_stats);
+    }
+}
+
+#[derive(Resource, Default)]
+struct Spectator {
+    /// Where the camera was when the view was turned on.
+    saved_camera: Option<(Transform, f32)>,
+}
+
+#[derive(Component)]
+struct SpectatorOverlay;
+
+#[derive(Component)]
+struct SpectatorText;
+
+#[derive(Component)]
+struct SpeedBar(usize);
+
+#[derive(Component)]
+struct ResetStats;
+
+fn spawn_overlay(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.),
+                        bottom: Val::Px(10.),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            SpectatorOverlay,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 16.,
+                        color: Color::WHITE,
+                    },
+                ),
+                SpectatorText,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(
+                            Val::Px(GRAPH_BAR_WIDTH * SPEED_SAMPLES as f32),
+                            Val::Px(GRAPH_HEIGHT),
+                        ),
+                        align_items: AlignItems::FlexEnd,
+                        margin: UiRect::top(Val::Px(6.)),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|graph| {
+                    for i in 0..SPEED_SAMPLES {
+                        graph.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(GRAPH_BAR_WIDTH), Val::Px(0.)),
+                                    ..default()
+                                },
+                                background_color: Color::LIME_GREEN.into(),
+                                ..default()
+                            },
+                            SpeedBar(i),
+                        ));
+                    }
+                });
+
+            spawn_shot_chart(parent, &mut images);
+
+            spawn_hold_button(
+                parent,
+                &asset_server,
+                "hold Backspace / Select to reset stats",
+                RESET_INPUT,
+                RESET_HOLD,
+            )
+            .insert(ResetStats);
+        });
+}
+
+fn toggle_spectator(
+    mut spectator: ResMut<Spectator>,
+    mut query_camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mut query_overlay: Query<&mut Visibility, With<SpectatorOverlay>>,
+    hotkeys: Hotkeys,
+) {
+    if !hotkeys.just_pressed(SPECTATOR_KEY) {
+        return;
+    }
+
+    let Ok((mut transform, mut projection)) = query_camera.get_single_mut() else {
+        return;
+    };
+    spectator.saved_camera = match spectator.saved_camera.take() {
+        Some((saved, scale)) => {
+            *transform = saved;
+            projection.scale = scale;
+            None
+        }
+        None => Some((*transform, projection.scale)),
+    };
+
+    for mut visibility in &mut query_overlay {
+        *visibility = if spectator.saved_camera.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn free_camera(
+    mut query: Query<(&mut Transform, &mut OrthographicProjection), With<Camera2d>>,
+    mut motion: EventReader<MouseMotion>,
+    mut wheel: EventReader<MouseWheel>,
+    spectator: Res<Spectator>,
+    mouse_input: Res<Input<MouseButton>>,
+    (gamepads, axes, buttons, timer): (
+        Res<Gamepads>,
+        Res<Axis<GamepadAxis>>,
+        Res<Axis<GamepadButton>>,
+        Res<Time>,
+    ),
+) {
+    let drag: Vec2 = motion.iter().map(|event| event.delta).sum();
+    let scroll: f32 = wheel.iter().map(|event| event.y).sum();
+    if spectator.saved_camera.is_none() {
+        return;
+    }
+
+    let mut pan = Vec2::ZERO;
+    let mut zoom = -scroll * ZOOM_STEP;
+    if mouse_input.pressed(MouseButton::Right) {
+        // screen y grows downward
+        pan -= Vec2::new(drag.x, -drag.y);
+    }
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            let value = axes.get(GamepadAxis::new(gamepad, axis_type)).unwrap_or(0.);
+            if value.abs() > STICK_DEAD_ZONE {
+                value
+            } else {
+                0.
+            }
+        };
+        let trigger = |button_type| {
+            buttons
+                .get(GamepadButton::new(gamepad, button_type))
+                .unwrap_or(0.)
+        };
+
+        let stick = Vec2::new(
+            axis(GamepadAxisType::RightStickX),
+            axis(GamepadAxisType::RightStickY),
+        );
+        pan += stick * PAN_SPEED * timer.delta_seconds();
+        zoom += (trigger(GamepadButtonType::LeftTrigger2)
+            - trigger(GamepadButtonType::RightTrigger2))
+            * TRIGGER_ZOOM_SPEED
+            * timer.delta_seconds();
+    }
+
+    for (mut transform, mut projection) in &mut query {
+        // pan in world units so it tracks the cursor at any zoom
+        transform.translation += (pan * projection.scale).extend(0.);
+        projection.scale = (projection.scale * (1. + zoom)).clamp(ZOOM_RANGE.0, ZOOM_RANGE.1);
+    }
+}
+
+fn update_overlay(
+    mut query_text: Query<&mut Text, With<SpectatorText>>,
+    mut query_bars: Query<(&mut Style, &SpeedBar)>,
+    spectator: Res<Spectator>,
+    stats: Res<MatchStats>,
+) {
+    if spectator.saved_camera.is_none() {
+        return;
+    }
+
+    let possession = stats.possession() * 100.;
+    for mut text in &mut query_text {
+        text.sections[0].value = format!(
+            "SPECTATOR (F8)\nrally {}  longest {}  misses {}\npossession {:.0}% near / {:.0}% far  (last 10 s {:.0}%)\nball speed {:.0} px/s",
+            stats.rally,
+            stats.longest_rally,
+            stats.misses,
+            possession,
+            100. - possession,
+            stats.momentum() * 100.,
+            stats.speed_history.back().copied().unwrap_or(0.),
+        );
+    }
+
+    // newest sample on the right
+    let offset = SPEED_SAMPLES - stats.speed_history.len();
+    for (mut style, bar) in &mut query_bars {
+        let speed = bar
+            .0
+            .checked_sub(offset)
+            .and_then(|i| stats.speed_history.get(i))
+            .copied()
+            .unwrap_or(0.);
+        style.size.height = Val::Px((speed / GRAPH_MAX_SPEED).min(1.) * GRAPH_HEIGHT);
+    }
+}
+
+fn reset_stats(
+    mut stats: ResMut<MatchStats>,
+    mut confirmed: EventReader<HoldConfirmed>,
+    mut toasts: EventWriter<Toast>,
+    query: Query<(), With<ResetStats>>,
+) {
+    if confirmed.iter().any(|event| query.contains(event.0)) {
+        *stats = MatchStats::default();
+        toasts.send(Toast("Stats reset".into()));
+    }
+}
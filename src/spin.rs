@@ -0,0 +1,166 @@
+//! Spin off a moving paddle. A paddle sliding along its goal as it returns
+//! the ball brushes it sideways, and the ball curves the way the paddle was
+//! going for the next [`SPIN_DURATION`] seconds, hardest straight off the
+//! paddle and easing off to nothing.
+
+use bevy::prelude::*;
+
+use crate::{
+    engine::{ScheduleSetup, Stage},
+    event_log::GameplayEvent,
+    paddle::{Paddle, PADDLE_SPEED},
+    serve::MatchPhase,
+    time_scale::{scaled, TimeScale},
+    AppState, Ball, Speed,
+};
+
+pub const SPIN_DURATION: f32 = 1.;
+// sideways pull per pixel per second of paddle speed, so a full-speed
+// paddle pulls about as hard as a curve shot
+const SPIN_PER_SPEED: f32 = 1. / 50.;
+// slower than this and the paddle is only being held still by hand
+const MIN_SPIN_SPEED: f32 = 20.;
+// a restored snapshot moves paddles in one frame; that's no swing
+const MAX_SPIN_SPEED: f32 = PADDLE_SPEED * 2.;
+
+pub struct SpinPlugin;
+
+impl Plugin for SpinPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(
+            AppState::Playing,
+            (impart_spin, apply_spin)
+                .chain()
+                .distributive_run_if(in_state(MatchPhase::Rally)),
+        )
+        .add_systems_at(
+            Stage::PostUpdate,
+            track_paddles.run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// The curve a ball picked up off a moving paddle.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Spin {
+    accel: Vec3,
+    remaining: f32,
+}
+
+impl Spin {
+    /// The spin a paddle moving at `velocity` puts on the ball, if it's
+    /// moving enough to put any on.
+    fn off_paddle(velocity: Vec2) -> Option<Self> {
+        let speed = velocity.length();
+        (speed >= MIN_SPIN_SPEED).then(|| Spin {
+            accel: (velocity.clamp_length_max(MAX_SPIN_SPEED) * SPIN_PER_SPEED).extend(0.),
+            remaining: SPIN_DURATION,
+        })
+    }
+
+    /// `dir` after `delta` more seconds of this spin, at the same speed.
+    fn turn(&mut self, dir: Vec3, delta: f32) -> Vec3 {
+        let delta = delta.min(self.remaining);
+        let fade = self.remaining / SPIN_DURATION;
+        self.remaining -= delta;
+        (dir + self.accel * fade * delta).normalize_or_zero() * dir.length()
+    }
+}
+
+/// How fast a paddle moved over the last frame.
+#[derive(Component)]
+struct PaddleVelocity {
+    last: Vec3,
+    velocity: Vec2,
+}
+
+// after everything that moves paddles, so the next frame's bounces see this one's movement
+fn track_paddles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Transform, Option<&mut PaddleVelocity>), With<Paddle>>,
+    timer: Res<Time>,
+) {
+    let delta = timer.delta_seconds();
+    for (entity, transform, tracked) in &mut query {
+        let Some(mut tracked) = tracked else {
+            commands.entity(entity).insert(PaddleVelocity {
+                last: transform.translation,
+                velocity: Vec2::ZERO,
+            });
+            continue;
+        };
+        if delta > 0. {
+            tracked.velocity = (transform.translation - tracked.last).truncate() / delta;
+        }
+        tracked.last = transform.translation;
+    }
+}
+
+// hits only say where the ball and paddle were, so they go to the closest ones
+fn impart_spin(
+    mut commands: Commands,
+    mut events: EventReader<GameplayEvent>,
+    query_ball: Query<(Entity, &Transform), With<Ball>>,
+    query_paddle: Query<(&Transform, &PaddleVelocity), With<Paddle>>,
+) {
+    for event in events.iter() {
+        let GameplayEvent::PaddleHit { ball, paddle, .. } = *event else {
+            continue;
+        };
+        let Some((entity, _)) = query_ball.iter().min_by(|a, b| {
+            let distance = |transform: &Transform| transform.translation.distance(ball);
+            distance(a.1).total_cmp(&distance(b.1))
+        }) else {
+            continue;
+        };
+        let Some((_, tracked)) = query_paddle.iter().min_by(|a, b| {
+            let distance = |transform: &Transform| transform.translation.distance(paddle);
+            distance(a.0).total_cmp(&distance(b.0))
+        }) else {
+            continue;
+        };
+
+        // a still paddle takes the last spin off, like a fresh return should
+        match Spin::off_paddle(tracked.velocity) {
+            Some(spin) => commands.entity(entity).insert(spin),
+            None => commands.entity(entity).remove::<Spin>(),
+        };
+    }
+}
+
+fn apply_spin(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Speed, &mut Spin, Option<&TimeScale>)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut speed, mut spin, time_scale) in &mut query {
+        speed.dir = spin.turn(speed.dir, scaled(timer.delta_seconds(), time_scale));
+        if spin.remaining <= 0. {
+            commands.entity(entity).remove::<Spin>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_moving_paddle_curves_the_ball_its_way() {
+        assert_eq!(Spin::off_paddle(Vec2::new(5., 0.)), None);
+
+        let mut spin = Spin::off_paddle(Vec2::new(PADDLE_SPEED, 0.)).unwrap();
+        let mut dir = Vec3::new(0., 8., 0.);
+        let mut turns = Vec::new();
+        for _ in 0..60 {
+            let turned = spin.turn(dir, SPIN_DURATION / 60.);
+            turns.push(dir.angle_between(turned));
+            dir = turned;
+        }
+        assert!(dir.x > 0.);
+        assert!((dir.length() - 8.).abs() < 1e-4);
+        assert!(spin.remaining < 1e-4);
+        // hardest off the paddle, next to nothing by the end
+        assert!(turns[59] < turns[0] / 10.);
+    }
+}
@@ -0,0 +1,398 @@
+//! Live match statistics sampled from play: the current and longest rally,
+//! misses, how long the ball has spent in each half overall and lately
+//! (possession and momentum), and a short history of its speed. A possession
+//! bar near the top of the HUD shows the overall split with a marker for the
+//! momentum. Ball positions and paddle contacts also go into a [`Heatmap`],
+//! the last few points' final shots are kept bounce by bounce, and a goal
+//! ending a record rally gets a toast.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    arena::Arena, engine::ScheduleSetup, event_log::GameplayEvent, pause::starting_match,
+    toast::Toast, AppState, Ball, Speed, Tunables,
+};
+
+pub const SPEED_SAMPLES: usize = 60;
+// seconds between speed and momentum samples
+const SAMPLE_INTERVAL: f32 = 0.1;
+// samples making up the momentum window, ten seconds' worth
+const MOMENTUM_SAMPLES: usize = 100;
+const BAR_SIZE: Vec2 = Vec2::new(200., 8.);
+/// Heatmap cells along each side.
+pub const HEATMAP_CELLS: usize = 64;
+/// Half the width of the square the heatmap covers, centered on the origin.
+pub const HEATMAP_EXTENT: f32 = 400.;
+/// Final shots kept in [`MatchStats::shots`].
+pub const SHOT_HISTORY: usize = 12;
+// shorter rallies aren't worth a "longest rally" toast
+const RECORD_TOAST_RALLY: u32 = 5;
+const NEAR_COLOR: Color = Color::rgb(0.2, 0.6, 1.);
+const FAR_COLOR: Color = Color::DARK_GRAY;
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MatchStats>()
+            .add_systems_on_enter(
+                AppState::Playing,
+                spawn_possession_bar.run_if(starting_match),
+            )
+            .add_systems_during(AppState::Playing, sample_stats)
+            .add_systems_during(AppState::Playing, update_possession_bar.after(sample_stats));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct MatchStats {
+    /// Paddle returns since the last goal.
+    pub rally: u32,
+    pub longest_rally: u32,
+    /// Balls the player let through their goal.
+    pub misses: u32,
+    /// Ball-seconds spent in the player's half and in the far half.
+    pub half_time: [f32; 2],
+    /// Fastest ball's speed in pixels per second, oldest first.
+    pub speed_history: VecDeque<f32>,
+    /// Whether each recent sample had a ball in the player's half, oldest first.
+    recent_halves: VecDeque<bool>,
+    pub heatmap: Heatmap,
+    /// The shot that ended each recent point, oldest first: where the ball
+    /// left the paddle, each bounce after, and where it crossed the goal line.
+    pub shots: VecDeque<Vec<Vec2>>,
+    /// Bounces of the shot in flight.
+    shot: Vec<Vec2>,
+    since_sample: f32,
+}
+
+/// Counts per cell of a grid over the arena, row by row from the bottom left.
+pub struct Heatmap {
+    /// Sampled ball positions.
+    pub balls: Vec<u32>,
+    /// Where the ball met a paddle.
+    pub contacts: Vec<u32>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            balls: vec![0; HEATMAP_CELLS * HEATMAP_CELLS],
+            contacts: vec![0; HEATMAP_CELLS * HEATMAP_CELLS],
+        }
+    }
+}
+
+impl Heatmap {
+    /// The cell index containing `point`, if it's on the grid.
+    pub fn cell(point: Vec2) -> Option<usize> {
+        let scaled = (point + HEATMAP_EXTENT) / (2. * HEATMAP_EXTENT) * HEATMAP_CELLS as f32;
+        let in_grid = 0. ..HEATMAP_CELLS as f32;
+        (in_grid.contains(&scaled.x) && in_grid.contains(&scaled.y))
+            .then(|| scaled.y as usize * HEATMAP_CELLS + scaled.x as usize)
+    }
+
+    fn add(cells: &mut [u32], point: Vec2) {
+        if let Some(cell) = Self::cell(point) {
+            cells[cell] += 1;
+        }
+    }
+}
+
+impl MatchStats {
+    /// Share of time the ball has spent in the player's half, even before
+    /// anything is sampled.
+    pub fn possession(&self) -> f32 {
+        let total = self.half_time[0] + self.half_time[1];
+        if total > 0. {
+            self.half_time[0] / total
+        } else {
+            0.5
+        }
+    }
+
+    /// Like [`possession`](Self::possession), over the last ten seconds only.
+    pub fn momentum(&self) -> f32 {
+        if self.recent_halves.is_empty() {
+            return 0.5;
+        }
+        let near = self.recent_halves.iter().filter(|&&near| near).count();
+        near as f32 / self.recent_halves.len() as f32
+    }
+
+    fn hear(&mut self, event: &GameplayEvent) {
+        match event {
+            GameplayEvent::PaddleHit { ball, .. } => {
+                Heatmap::add(&mut self.heatmap.contacts, ball.truncate());
+                self.rally += 1;
+                self.longest_rally = self.longest_rally.max(self.rally);
+                self.shot.clear();
+                self.shot.push(ball.truncate());
+            }
+            GameplayEvent::WallHit { ball, .. } => self.shot.push(ball.truncate()),
+            GameplayEvent::Goal { ball, goal, .. } => {
+                self.rally = 0;
+                let mut shot = std::mem::take(&mut self.shot);
+                // goals at the other player's end aren't the player's misses
+                if *goal != 0 {
+                    return;
+                }
+
+                self.misses += 1;
+                shot.push(ball.truncate());
+                if self.shots.len() == SHOT_HISTORY {
+                    self.shots.pop_front();
+                }
+                self.shots.push_back(shot);
+            }
+            _ => {}
+        }
+    }
+
+    fn push_speed(&mut self, speed: f32) {
+        if self.speed_history.len() == SPEED_SAMPLES {
+            self.speed_history.pop_front();
+        }
+        self.speed_history.push_back(speed);
+    }
+
+    fn push_half(&mut self, near: bool) {
+        if self.recent_halves.len() == MOMENTUM_SAMPLES {
+            self.recent_halves.pop_front();
+        }
+        self.recent_halves.push_back(near);
+    }
+}
+
+#[derive(Component)]
+struct PossessionFill;
+
+#[derive(Component)]
+struct MomentumMarker;
+
+// sits under the scoreboard
+fn spawn_possession_bar(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(70.),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(100.)),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "POSSESSION",
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 12.,
+                    color: Color::WHITE,
+                },
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_SIZE.x), Val::Px(BAR_SIZE.y)),
+                        ..default()
+                    },
+                    background_color: FAR_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Percent(50.), Val::Percent(100.)),
+                                ..default()
+                            },
+                            background_color: NEAR_COLOR.into(),
+                            ..default()
+                        },
+                        PossessionFill,
+                    ));
+                    bar.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                position: UiRect {
+                                    left: Val::Percent(50.),
+                                    top: Val::Px(-2.),
+                                    ..default()
+                                },
+                                size: Size::new(Val::Px(2.), Val::Px(BAR_SIZE.y + 4.)),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                        MomentumMarker,
+                    ));
+                });
+        });
+}
+
+fn update_possession_bar(
+    mut query_fill: Query<&mut Style, (With<PossessionFill>, Without<MomentumMarker>)>,
+    mut query_marker: Query<&mut Style, With<MomentumMarker>>,
+    stats: Res<MatchStats>,
+) {
+    for mut style in &mut query_fill {
+        style.size.width = Val::Percent(stats.possession() * 100.);
+    }
+    for mut style in &mut query_marker {
+        style.position.left = Val::Percent(stats.momentum() * 100.);
+    }
+}
+
+fn sample_stats(
+    mut stats: ResMut<MatchStats>,
+    mut events: EventReader<GameplayEvent>,
+    mut toasts: EventWriter<Toast>,
+    query: Query<(&Transform, &Speed), With<Ball>>,
+    arena: Res<Arena>,
+    tunables: Res<Tunables>,
+    timer: Res<Time>,
+) {
+    for event in events.iter() {
+        let record = stats.rally >= RECORD_TOAST_RALLY && stats.rally == stats.longest_rally;
+        if record && matches!(event, GameplayEvent::Goal { .. }) {
+            toasts.send(Toast(format!("Longest rally yet: {}", stats.rally)));
+        }
+        stats.hear(event);
+    }
+
+    let delta = timer.delta_seconds();
+    for (transform, _) in &query {
+        let half = usize::from(!arena.in_own_half(transform.translation.truncate()));
+        stats.half_time[half] += delta;
+    }
+
+    stats.since_sample += delta;
+    if stats.since_sample >= SAMPLE_INTERVAL {
+        stats.since_sample -= SAMPLE_INTERVAL;
+        // the base speed, not the one-frame boost right after a bounce
+        let fastest = query
+            .iter()
+            .map(|(_, speed)| speed.dir.length() * tunables.speed)
+            .fold(0., f32::max);
+        stats.push_speed(fastest);
+
+        let near = query
+            .iter()
+            .any(|(transform, _)| arena.in_own_half(transform.translation.truncate()));
+        stats.push_half(near);
+
+        for (transform, _) in &query {
+            Heatmap::add(&mut stats.heatmap.balls, transform.translation.truncate());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rallies_track_the_longest_and_reset_on_goals() {
+        let mut stats = MatchStats::default();
+        let hit = GameplayEvent::PaddleHit {
+            ball: Vec3::ZERO,
+            paddle: Vec3::ZERO,
+            dir: Vec3::Y,
+        };
+        for _ in 0..3 {
+            stats.hear(&hit);
+        }
+        stats.hear(&GameplayEvent::Goal {
+            ball: Vec3::ZERO,
+            goal: 0,
+            score: (1, 0),
+        });
+        stats.hear(&hit);
+
+        assert_eq!((stats.rally, stats.longest_rally), (1, 3));
+        assert_eq!(stats.misses, 1);
+    }
+
+    #[test]
+    fn shots_run_from_the_last_return_to_the_goal() {
+        let mut stats = MatchStats::default();
+        let at = |x, y| Vec3::new(x, y, 0.);
+        stats.hear(&GameplayEvent::WallHit {
+            ball: at(1., 1.),
+            normal: Vec3::X,
+        });
+        stats.hear(&GameplayEvent::PaddleHit {
+            ball: at(0., -280.),
+            paddle: at(0., -290.),
+            dir: Vec3::Y,
+        });
+        stats.hear(&GameplayEvent::WallHit {
+            ball: at(290., 0.),
+            normal: Vec3::NEG_X,
+        });
+        stats.hear(&GameplayEvent::Goal {
+            ball: at(100., -290.),
+            goal: 0,
+            score: (1, 0),
+        });
+
+        assert_eq!(
+            stats.shots,
+            [vec![
+                Vec2::new(0., -280.),
+                Vec2::new(290., 0.),
+                Vec2::new(100., -290.)
+            ]]
+        );
+    }
+
+    #[test]
+    fn heatmap_cells_cover_the_grid() {
+        assert_eq!(Heatmap::cell(Vec2::splat(-HEATMAP_EXTENT)), Some(0));
+        assert_eq!(
+            Heatmap::cell(Vec2::splat(HEATMAP_EXTENT - 1.)),
+            Some(HEATMAP_CELLS * HEATMAP_CELLS - 1)
+        );
+        assert_eq!(Heatmap::cell(Vec2::new(0., HEATMAP_EXTENT + 1.)), None);
+    }
+
+    #[test]
+    fn momentum_follows_recent_samples_only() {
+        let mut stats = MatchStats::default();
+        for _ in 0..MOMENTUM_SAMPLES {
+            stats.push_half(true);
+        }
+        for _ in 0..MOMENTUM_SAMPLES / 4 {
+            stats.push_half(false);
+        }
+        assert_eq!(stats.momentum(), 0.75);
+    }
+
+    #[test]
+    fn speed_history_keeps_the_latest_samples() {
+        let mut stats = MatchStats::default();
+        for i in 0..SPEED_SAMPLES + 5 {
+            stats.push_speed(i as f32);
+        }
+        assert_eq!(stats.speed_history.len(), SPEED_SAMPLES);
+        assert_eq!(stats.speed_history.front(), Some(&5.));
+    }
+
+    #[test]
+    fn own_half_is_the_goal_side() {
+        let arena = Arena::default();
+        assert!(arena.in_own_half(Vec2::new(0., -100.)));
+        assert!(!arena.in_own_half(Vec2::new(0., 100.)));
+    }
+}
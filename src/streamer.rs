@@ -0,0 +1,141 @@
+//! Streamer mode: HUD elements pulled in from the window edges by a safe
+//! margin, tool hotkeys guarded behind Ctrl+Shift, and optionally a second
+//! window with nothing but the score on a transparent background, to capture
+//! as an overlay.
+
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{camera::RenderTarget, view::RenderLayers},
+    window::WindowRef,
+};
+
+use crate::{
+    engine::{ScheduleSetup, Stage},
+    hotkey::HotkeyGuard,
+    scoreboard::score_line,
+    GameState,
+};
+
+// keeps the score window's camera and text out of the main view
+const SCORE_LAYER: u8 = 1;
+
+/// Streamer mode options.
+#[derive(Resource, Clone, Copy)]
+pub struct StreamerSettings {
+    /// Extra inset for HUD elements from the window edges, in logical pixels.
+    pub safe_margin: f32,
+    /// Opens a second window showing only the score.
+    pub score_window: bool,
+}
+
+impl Default for StreamerSettings {
+    fn default() -> Self {
+        Self {
+            safe_margin: 40.,
+            score_window: false,
+        }
+    }
+}
+
+pub struct StreamerPlugin {
+    pub settings: StreamerSettings,
+}
+
+impl Plugin for StreamerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings)
+            .insert_resource(HotkeyGuard { chorded: true })
+            .add_system(inset_hud);
+
+        if self.settings.score_window {
+            app.add_systems_at(Stage::Startup, open_score_window)
+                .add_system(update_score_window);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ScoreWindowText;
+
+// HUD roots are absolutely placed against the window edges; pushes each
+// placed side in by the margin, once, as the node appears
+fn inset_hud(
+    mut query: Query<&mut Style, (Added<Node>, Without<Parent>)>,
+    settings: Res<StreamerSettings>,
+) {
+    for mut style in &mut query {
+        if style.position_type != PositionType::Absolute {
+            continue;
+        }
+
+        let position = &mut style.position;
+        for side in [
+            &mut position.left,
+            &mut position.right,
+            &mut position.top,
+            &mut position.bottom,
+        ] {
+            if let Val::Px(px) = side {
+                *px += settings.safe_margin;
+            }
+        }
+    }
+}
+
+fn open_score_window(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let window = commands
+        .spawn(Window {
+            title: "pong-rs score".to_owned(),
+            resolution: (320., 120.).into(),
+            transparent: true,
+            ..default()
+        })
+        .id();
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Window(WindowRef::Entity(window)),
+                ..default()
+            },
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+            },
+            ..default()
+        },
+        // the HUD belongs to the main window only
+        UiCameraConfig { show_ui: false },
+        RenderLayers::layer(SCORE_LAYER),
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 64.,
+                    color: Color::WHITE,
+                },
+            )
+            .with_alignment(TextAlignment::Center),
+            ..default()
+        },
+        RenderLayers::layer(SCORE_LAYER),
+        ScoreWindowText,
+    ));
+}
+
+fn update_score_window(
+    mut query: Query<&mut Text, With<ScoreWindowText>>,
+    game_state: Res<GameState>,
+) {
+    if !game_state.is_changed() {
+        return;
+    }
+
+    for mut text in &mut query {
+        text.sections[0].value = score_line(game_state.score);
+    }
+}
@@ -0,0 +1,177 @@
+//! Survival, the endless single-player mode a mutator turns on: there's no
+//! point limit, the first goal ends the match, and the score is how long the
+//! player lasted. The base ball speed steps up on a [`DifficultyCurve`] as the
+//! clock runs, and the clock sits under the scoreboard.
+
+use bevy::prelude::*;
+
+use crate::{
+    engine::ScheduleSetup, mutator::start_mutators, pause::starting_match, serve::MatchPhase,
+    AppState, GameState, Tunables,
+};
+
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DifficultyCurve>()
+            .init_resource::<Survival>()
+            .add_systems_on_enter(
+                AppState::Playing,
+                start_survival.after(start_mutators).run_if(starting_match),
+            )
+            .add_systems_during(
+                AppState::Playing,
+                ramp_speed.run_if(in_state(MatchPhase::Rally)),
+            )
+            .add_systems_during(AppState::Playing, end_survival)
+            .add_systems_on_enter(AppState::GameOver, settle_speed);
+    }
+}
+
+/// How quickly survival gets harder.
+#[derive(Resource, Clone, Copy)]
+pub struct DifficultyCurve {
+    /// Seconds between speed-ups.
+    pub every: f32,
+    /// Share of the starting speed each speed-up adds.
+    pub step: f32,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            every: 10.,
+            step: 0.1,
+        }
+    }
+}
+
+impl DifficultyCurve {
+    /// The starting speed's multiplier after `elapsed` seconds.
+    pub fn factor(&self, elapsed: f32) -> f32 {
+        if self.every <= 0. {
+            return 1.;
+        }
+        1. + self.step * (elapsed / self.every).floor()
+    }
+}
+
+/// The survival clock for the match under way.
+#[derive(Resource, Default, Clone)]
+pub struct Survival {
+    /// Seconds survived so far.
+    pub elapsed: f32,
+    /// [`Tunables::speed`] as the match started, before any speed-ups.
+    base_speed: f32,
+}
+
+/// `seconds` as the clock shows them, like "1:05.3".
+pub fn survival_time(seconds: f32) -> String {
+    let tenths = (seconds * 10.) as u32;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+#[derive(Component)]
+struct SurvivalHud;
+
+#[derive(Component)]
+struct SurvivalClock;
+
+fn start_survival(
+    mut commands: Commands,
+    mut survival: ResMut<Survival>,
+    query: Query<Entity, With<SurvivalHud>>,
+    tunables: Res<Tunables>,
+    asset_server: Res<AssetServer>,
+) {
+    *survival = Survival {
+        elapsed: 0.,
+        base_speed: tunables.speed,
+    };
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !tunables.survival {
+        return;
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                // under the scoreboard
+                position: UiRect {
+                    top: Val::Px(70.),
+                    ..default()
+                },
+                size: Size::width(Val::Percent(100.)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(SurvivalHud)
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    survival_time(0.),
+                    TextStyle {
+                        font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                        font_size: 28.,
+                        color: Color::WHITE,
+                    },
+                ),
+                SurvivalClock,
+            ));
+        });
+}
+
+fn ramp_speed(
+    mut survival: ResMut<Survival>,
+    mut tunables: ResMut<Tunables>,
+    mut query: Query<&mut Text, With<SurvivalClock>>,
+    curve: Res<DifficultyCurve>,
+    timer: Res<Time>,
+) {
+    if !tunables.survival {
+        return;
+    }
+    survival.elapsed += timer.delta_seconds();
+    tunables.speed = survival.base_speed * curve.factor(survival.elapsed);
+    for mut text in &mut query {
+        text.sections[0].value = survival_time(survival.elapsed);
+    }
+}
+
+// any goal at all, since the far side in single play is only walls
+fn end_survival(
+    game_state: Res<GameState>,
+    tunables: Res<Tunables>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if tunables.survival && game_state.is_changed() && game_state.score != (0, 0) {
+        next_state.set(AppState::GameOver);
+    }
+}
+
+// so the next match doesn't start at this one's final pace
+fn settle_speed(survival: Res<Survival>, mut tunables: ResMut<Tunables>) {
+    if tunables.survival {
+        tunables.speed = survival.base_speed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speeds_up_in_steps() {
+        let curve = DifficultyCurve::default();
+        assert_eq!(curve.factor(9.9), 1.);
+        assert!((curve.factor(10.) - 1.1).abs() < 1e-6);
+        assert!((curve.factor(35.) - 1.3).abs() < 1e-6);
+        assert_eq!(survival_time(65.37), "1:05.3");
+    }
+}
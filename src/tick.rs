@@ -0,0 +1,123 @@
+//! The physics tick. Balls move, bounce and score in [`Stage::FixedUpdate`]
+//! at a steady rate, [`DEFAULT_TICK_RATE`] unless the game is set up with
+//! another, so a rally plays out the same at 30 frames a second as at 240.
+//! Frames fall between ticks, so each ball is drawn partway from where the
+//! last tick found it to where it left it, by how far the clock has got
+//! toward the next one; its transform goes back to the ticked position
+//! before anything else looks at it.
+
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::engine::{ScheduleSetup, Stage};
+use crate::Ball;
+
+/// Physics ticks a second when none is given.
+pub const DEFAULT_TICK_RATE: f32 = 120.;
+// farther than this in one tick is a teleport, a serve or a rewind, which
+// is drawn where it landed rather than swept across the field
+const TELEPORT_DISTANCE: f32 = 64.;
+
+pub struct TickPlugin {
+    /// Ticks a second.
+    pub rate: f32,
+}
+
+impl Plugin for TickPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(FixedTime::new_from_secs(1. / self.rate))
+            .add_systems_at(Stage::First, restore_ticked.after(bevy::time::TimeSystem))
+            .add_systems_at(Stage::FixedUpdate, remember_positions)
+            .add_systems_at(
+                Stage::PostUpdate,
+                interpolate_balls.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+/// The seconds one tick covers.
+pub fn tick_seconds(fixed_time: &FixedTime) -> f32 {
+    fixed_time.period.as_secs_f32()
+}
+
+/// A ball's positions either side of the last tick, and where between
+/// them it was drawn.
+#[derive(Component, Clone, Copy)]
+pub struct Interpolated {
+    previous: Vec3,
+    ticked: Vec3,
+    shown: Vec3,
+}
+
+impl Interpolated {
+    fn at(translation: Vec3) -> Self {
+        Self {
+            previous: translation,
+            ticked: translation,
+            shown: translation,
+        }
+    }
+
+    /// Where to draw the ball `alpha` of the way to the next tick.
+    fn blend(&self, alpha: f32) -> Vec3 {
+        if self.previous.distance(self.ticked) > TELEPORT_DISTANCE {
+            return self.ticked;
+        }
+        self.previous.lerp(self.ticked, alpha.clamp(0., 1.))
+    }
+}
+
+// a transform that isn't where it was drawn has been moved on purpose since,
+// by a restore or a test setting up a scene, and starts over from there
+fn restore_ticked(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, Option<&mut Interpolated>), With<Ball>>,
+) {
+    for (entity, mut transform, interpolated) in &mut query {
+        match interpolated {
+            Some(interpolated) if transform.translation == interpolated.shown => {
+                transform.translation = interpolated.ticked;
+            }
+            Some(mut interpolated) => *interpolated = Interpolated::at(transform.translation),
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(Interpolated::at(transform.translation));
+            }
+        }
+    }
+}
+
+/// Notes where each ball starts the tick, ahead of moving it.
+pub fn remember_positions(mut query: Query<(&Transform, &mut Interpolated), With<Ball>>) {
+    for (transform, mut interpolated) in &mut query {
+        interpolated.previous = transform.translation;
+    }
+}
+
+fn interpolate_balls(
+    mut query: Query<(&mut Transform, &mut Interpolated), With<Ball>>,
+    fixed_time: Res<FixedTime>,
+) {
+    let alpha = fixed_time.accumulated().as_secs_f32() / tick_seconds(&fixed_time);
+    for (mut transform, mut interpolated) in &mut query {
+        interpolated.ticked = transform.translation;
+        interpolated.shown = interpolated.blend(alpha);
+        transform.translation = interpolated.shown;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_between_ticks_but_not_across_teleports() {
+        let mut interpolated = Interpolated::at(Vec3::ZERO);
+        interpolated.ticked = Vec3::new(10., 0., 0.);
+        assert_eq!(interpolated.blend(0.25), Vec3::new(2.5, 0., 0.));
+        assert_eq!(interpolated.blend(3.), Vec3::new(10., 0., 0.));
+
+        interpolated.ticked = Vec3::new(0., 300., 0.);
+        assert_eq!(interpolated.blend(0.5), Vec3::new(0., 300., 0.));
+    }
+}
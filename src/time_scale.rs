@@ -0,0 +1,72 @@
+//! Per-entity time. A [`TimeScale`] runs one entity's clock faster or slower
+//! than the match's for a while, so a slowed ball or a hasted paddle doesn't
+//! need global time bent for everything else. Ball flight and spin, paddle
+//! steering, charging, tweens, callouts and flashes all pass their frame's
+//! seconds through [`scaled`] first.
+
+use bevy::prelude::*;
+
+use crate::engine::ScheduleSetup;
+use crate::AppState;
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems_during(AppState::Playing, tick_time_scales);
+    }
+}
+
+/// Runs its entity's time at `factor` for a number of seconds.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct TimeScale {
+    pub factor: f32,
+    remaining: f32,
+}
+
+impl TimeScale {
+    pub fn new(factor: f32, seconds: f32) -> Self {
+        Self {
+            factor,
+            remaining: seconds,
+        }
+    }
+
+    /// Counts `delta` seconds off the window; whether it's over.
+    fn tick(&mut self, delta: f32) -> bool {
+        self.remaining -= delta;
+        self.remaining <= 0.
+    }
+}
+
+/// `delta` seconds as they pass for an entity with `time_scale`, if it has one.
+pub fn scaled(delta: f32, time_scale: Option<&TimeScale>) -> f32 {
+    delta * time_scale.map_or(1., |time_scale| time_scale.factor)
+}
+
+fn tick_time_scales(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TimeScale)>,
+    timer: Res<Time>,
+) {
+    for (entity, mut time_scale) in &mut query {
+        // match time, so a slowed entity doesn't stretch its own window
+        if time_scale.tick(timer.delta_seconds()) {
+            commands.entity(entity).remove::<TimeScale>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_until_the_window_runs_out() {
+        let mut haste = TimeScale::new(2., 1.);
+        assert_eq!(scaled(0.5, Some(&haste)), 1.);
+        assert_eq!(scaled(0.5, None), 0.5);
+        assert!(!haste.tick(0.6));
+        assert!(haste.tick(0.6));
+    }
+}
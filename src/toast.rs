@@ -0,0 +1,148 @@
+//! Corner notifications. Any system can send a [`Toast`]; the messages stack
+//! in the top-right corner, newest at the bottom, and fade out after a few
+//! seconds. Past [`MAX_TOASTS`] the oldest goes early.
+
+use bevy::prelude::*;
+
+use crate::engine::{ScheduleSetup, Stage};
+
+const MAX_TOASTS: usize = 4;
+const TOAST_DURATION: f32 = 3.;
+const TOAST_FADE: f32 = 0.5;
+const TOAST_BACKGROUND: Color = Color::rgba(0., 0., 0., 0.7);
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Toast>()
+            .add_systems_at(Stage::Startup, spawn_toast_stack)
+            .add_system(show_toasts)
+            .add_system(fade_toasts.after(show_toasts));
+    }
+}
+
+pub struct Toast(pub String);
+
+#[derive(Component)]
+struct ToastStack;
+
+#[derive(Component)]
+struct ToastEntry {
+    age: f32,
+}
+
+/// Opacity for a toast `age` seconds old.
+fn toast_alpha(age: f32) -> f32 {
+    ((TOAST_DURATION - age) / TOAST_FADE).clamp(0., 1.)
+}
+
+fn spawn_toast_stack(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.),
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            z_index: ZIndex::Global(20),
+            ..default()
+        },
+        ToastStack,
+    ));
+}
+
+fn show_toasts(
+    mut commands: Commands,
+    mut toasts: EventReader<Toast>,
+    query_stack: Query<Entity, With<ToastStack>>,
+    query_entries: Query<(Entity, &ToastEntry)>,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok(stack) = query_stack.get_single() else {
+        return;
+    };
+    let new: Vec<_> = toasts.iter().collect();
+    if new.is_empty() {
+        return;
+    }
+
+    // oldest first
+    let mut shown: Vec<_> = query_entries.iter().collect();
+    shown.sort_by(|a, b| b.1.age.total_cmp(&a.1.age));
+    let excess = (shown.len() + new.len()).saturating_sub(MAX_TOASTS);
+    for (entity, _) in shown.into_iter().take(excess) {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.entity(stack).with_children(|parent| {
+        for toast in new.iter().skip(new.len().saturating_sub(MAX_TOASTS)) {
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(6.)),
+                            margin: UiRect::bottom(Val::Px(4.)),
+                            ..default()
+                        },
+                        background_color: TOAST_BACKGROUND.into(),
+                        ..default()
+                    },
+                    ToastEntry { age: 0. },
+                ))
+                .with_children(|entry| {
+                    entry.spawn(TextBundle::from_section(
+                        toast.0.clone(),
+                        TextStyle {
+                            font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                            font_size: 16.,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+        }
+    });
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ToastEntry, &mut BackgroundColor, &Children)>,
+    mut query_text: Query<&mut Text>,
+    timer: Res<Time>,
+) {
+    for (entity, mut entry, mut background, children) in &mut query {
+        // real time, so toasts clear while the game is paused
+        entry.age += timer.raw_delta_seconds();
+        if entry.age >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let alpha = toast_alpha(entry.age);
+        background.0.set_a(TOAST_BACKGROUND.a() * alpha);
+        for &child in children {
+            if let Ok(mut text) = query_text.get_mut(child) {
+                text.sections[0].style.color.set_a(alpha);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toasts_fade_out_at_the_end() {
+        assert_eq!(toast_alpha(0.), 1.);
+        assert_eq!(toast_alpha(TOAST_DURATION - TOAST_FADE), 1.);
+        assert_eq!(toast_alpha(TOAST_DURATION - TOAST_FADE / 2.), 0.5);
+        assert_eq!(toast_alpha(TOAST_DURATION), 0.);
+    }
+}
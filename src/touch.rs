@@ -0,0 +1,214 @@
+//! Touchscreens, for phones and mobile browsers. A finger dragged on the
+//! field steers the paddle of the goal it's nearest (see [`Steering`]), and
+//! a tap, a touch let go about where it landed, serves at once while the
+//! player's serve counts down. The first touch puts a pause button in the
+//! top right corner for the rest of the run. Menu items take taps as well,
+//! each padded out to a finger's size: a tap focuses and activates one, or
+//! on a row that steps a value, steps it down or up by the half tapped.
+//!
+//! [`Steering`]: crate::gamepad::Steering
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{
+    bindings::KeyBindings,
+    despawn_screen,
+    engine::ScheduleSetup,
+    focus::{Focus, FocusEvent, Focusable},
+    paddle::Steered,
+    serve::{MatchPhase, Serve, Serving},
+    AppState,
+};
+
+const PAUSE_BUTTON_SIZE: f32 = 64.;
+const PAUSE_BUTTON_MARGIN: f32 = 12.;
+// how far a finger can drift and still count as tapping
+const TAP_SLOP: f32 = 12.;
+// around each menu item, so it's big enough to hit
+const TARGET_PADDING: f32 = 10.;
+
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TouchSeen>()
+            .add_systems((notice_touches, tappable_items, tap_items))
+            .add_systems_during(AppState::Playing, (show_pause_button, press_pause_button))
+            .add_systems_during(
+                AppState::Playing,
+                serve_on_tap.run_if(in_state(MatchPhase::Serve)),
+            )
+            .add_systems_on_exit(AppState::Playing, despawn_screen::<PauseButton>);
+    }
+}
+
+/// Whether the screen has been touched this run.
+#[derive(Resource, Default)]
+struct TouchSeen(bool);
+
+#[derive(Component)]
+struct PauseButton;
+
+/// Whether `position`, in window pixels from the top left, is on the pause
+/// button, so a touch there doesn't steer or serve too. The button's in the
+/// top right corner, or the top left with the HUD `mirrored`.
+pub fn on_pause_button(window: &Window, position: Vec2, mirrored: bool) -> bool {
+    let corner = PAUSE_BUTTON_SIZE + PAUSE_BUTTON_MARGIN;
+    let across = if mirrored {
+        position.x <= corner
+    } else {
+        position.x >= window.width() - corner
+    };
+    across && position.y <= corner
+}
+
+/// Whether a touch that went down at `start` and came up at `end` was a tap.
+fn is_tap(start: Vec2, end: Vec2) -> bool {
+    start.distance(end) <= TAP_SLOP
+}
+
+fn notice_touches(mut seen: ResMut<TouchSeen>, touches: Res<Touches>) {
+    if !seen.0 && touches.any_just_pressed() {
+        seen.0 = true;
+    }
+}
+
+fn show_pause_button(
+    mut commands: Commands,
+    query: Query<(), With<PauseButton>>,
+    seen: Res<TouchSeen>,
+    asset_server: Res<AssetServer>,
+) {
+    if !seen.0 || !query.is_empty() {
+        return;
+    }
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(PAUSE_BUTTON_MARGIN),
+                        right: Val::Px(PAUSE_BUTTON_MARGIN),
+                        ..default()
+                    },
+                    size: Size::all(Val::Px(PAUSE_BUTTON_SIZE)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(1., 1., 1., 0.15).into(),
+                ..default()
+            },
+            PauseButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "II",
+                TextStyle {
+                    font: asset_server.load("fonts/DejaVuSans-Bold.ttf"),
+                    font_size: 32.,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+fn press_pause_button(
+    query: Query<&Interaction, (Changed<Interaction>, With<PauseButton>)>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    if query
+        .iter()
+        .any(|interaction| *interaction == Interaction::Clicked)
+    {
+        next_state.set(AppState::Paused);
+    }
+}
+
+// only the player's own serve, the bot's keeps its countdown
+fn serve_on_tap(
+    mut serve: ResMut<Serve>,
+    query: Query<(), (With<Serving>, With<Steered>)>,
+    query_window: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    bindings: Res<KeyBindings>,
+) {
+    let Ok(window) = query_window.get_single() else {
+        return;
+    };
+    let mirrored = bindings.player(0).handedness.mirror_hud;
+    let tapped = touches.iter_just_released().any(|touch| {
+        is_tap(touch.start_position(), touch.position())
+            && !on_pause_button(window, touch.start_position(), mirrored)
+    });
+    if tapped && !query.is_empty() {
+        serve.release();
+    }
+}
+
+fn tappable_items(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Style), Added<Focusable>>,
+) {
+    for (entity, mut style) in &mut query {
+        style.padding = UiRect::all(Val::Px(TARGET_PADDING));
+        commands.entity(entity).insert(Interaction::default());
+    }
+}
+
+// mouse clicks come through as taps too
+fn tap_items(
+    mut focus: ResMut<Focus>,
+    mut events: EventWriter<FocusEvent>,
+    query: Query<(Entity, &Interaction, &Focusable, &GlobalTransform), Changed<Interaction>>,
+    query_window: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+) {
+    for (entity, interaction, focusable, transform) in &query {
+        if *interaction != Interaction::Clicked {
+            continue;
+        }
+        focus.0 = Some(entity);
+        if !focusable.adjusts {
+            events.send(FocusEvent::Activated(entity));
+            continue;
+        }
+        let pointer = touches.first_pressed_position().or_else(|| {
+            query_window
+                .get_single()
+                .ok()
+                .and_then(|window| window.cursor_position())
+        });
+        if let Some(pointer) = pointer {
+            let step = if pointer.x < transform.translation().x {
+                -1
+            } else {
+                1
+            };
+            events.send(FocusEvent::Adjusted(entity, step));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn taps_stay_put_and_off_the_pause_button() {
+        assert!(is_tap(Vec2::new(100., 100.), Vec2::new(105., 108.)));
+        assert!(!is_tap(Vec2::new(100., 100.), Vec2::new(100., 140.)));
+
+        let window = Window {
+            resolution: (800., 600.).into(),
+            ..default()
+        };
+        assert!(on_pause_button(&window, Vec2::new(780., 20.), false));
+        assert!(!on_pause_button(&window, Vec2::new(780., 200.), false));
+        assert!(!on_pause_button(&window, Vec2::new(400., 20.), false));
+        // the mirrored HUD has it in the other corner
+        assert!(on_pause_button(&window, Vec2::new(20., 20.), true));
+        assert!(!on_pause_button(&window, Vec2::new(780., 20.), true));
+    }
+}
@@ -0,0 +1,509 @@
+//! Tournaments for club nights, two entrants at a time on a two-player
+//! arena. A round robin, with `--tournament <names>`, has every entrant play
+//! every other once and keeps standings (wins, then point difference); a
+//! single-elimination bracket, entered from the title menu, sends each
+//! match's winner on to the next round until one is left. Either sits beside
+//! the game-over screen, whose Play Again becomes the next match. A match
+//! quit before anyone won it is played again. After the last one the final
+//! standings are written to a JSON file under [`TOURNAMENT_DIR`].
+
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    despawn_screen,
+    engine::ScheduleSetup,
+    paddle::{Player, Side},
+    scoreboard::{match_winner, points},
+    scorecard::today,
+    toast::Toast,
+    AppState, GameState, Tunables,
+};
+
+pub const TOURNAMENT_DIR: &str = "tournaments";
+const BRACKET_COLUMN_WIDTH: f32 = 120.;
+const BRACKET_LINE_HEIGHT: f32 = 24.;
+
+pub struct TournamentPlugin;
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(name_players.run_if(resource_exists::<Tournament>()))
+            .add_systems_on_enter(
+                AppState::GameOver,
+                (record_result, spawn_standings.after(record_result))
+                    .distributive_run_if(resource_exists::<Tournament>()),
+            )
+            .add_systems_on_exit(AppState::GameOver, despawn_screen::<StandingsTable>);
+    }
+}
+
+/// A finished match, entrants by index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fixture {
+    /// On the first goal, then the far one.
+    pub entrants: [usize; 2],
+    /// Points over every game, in the same order.
+    pub points: [u32; 2],
+    pub winner: usize,
+}
+
+/// How a tournament's matches are drawn up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Format {
+    /// Who plays whom, in order; the first entrant of each pair takes the
+    /// first goal.
+    RoundRobin(Vec<[usize; 2]>),
+    /// The first round's seats, paired off in order; `None` is a bye.
+    Bracket(Vec<Option<usize>>),
+}
+
+/// The tournament under way.
+#[derive(Resource, Debug)]
+pub struct Tournament {
+    pub entrants: Vec<String>,
+    pub format: Format,
+    pub results: Vec<Fixture>,
+}
+
+/// One entrant's line of the standings.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Standing {
+    pub name: String,
+    pub played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub points_for: u32,
+    pub points_against: u32,
+}
+
+impl Standing {
+    pub fn difference(&self) -> i64 {
+        i64::from(self.points_for) - i64::from(self.points_against)
+    }
+}
+
+#[derive(Serialize)]
+struct StandingsFile<'a> {
+    date: String,
+    standings: Vec<StandingLine<'a>>,
+}
+
+#[derive(Serialize)]
+struct StandingLine<'a> {
+    #[serde(flatten)]
+    standing: &'a Standing,
+    point_difference: i64,
+}
+
+impl Tournament {
+    /// Every pairing of `entrants` once, round by round (the circle method,
+    /// with a bye each round for an odd count), so nobody plays twice in a
+    /// row where it can be helped.
+    pub fn round_robin(entrants: Vec<String>) -> Self {
+        let mut seats: Vec<Option<usize>> = (0..entrants.len()).map(Some).collect();
+        if seats.len() % 2 == 1 {
+            seats.push(None);
+        }
+        let mut schedule = Vec::new();
+        for round in 0..seats.len().saturating_sub(1) {
+            let half = seats.len() / 2;
+            for i in 0..half {
+                if let (Some(a), Some(b)) = (seats[i], seats[seats.len() - 1 - i]) {
+                    // alternate ends, so the fixed seat isn't always first
+                    schedule.push(if (round + i) % 2 == 0 { [a, b] } else { [b, a] });
+                }
+            }
+            seats[1..].rotate_right(1);
+        }
+        Self {
+            entrants,
+            format: Format::RoundRobin(schedule),
+            results: Vec::new(),
+        }
+    }
+
+    /// A single-elimination bracket, seeded in the order `entrants` come:
+    /// the first seed meets the last and so on, with the byes a short field
+    /// leaves going to the top seeds.
+    pub fn bracket(entrants: Vec<String>) -> Self {
+        let mut order = vec![0];
+        while order.len() < entrants.len() {
+            let seats = order.len() * 2;
+            order = order
+                .into_iter()
+                .flat_map(|seed| [seed, seats - 1 - seed])
+                .collect();
+        }
+        Self {
+            format: Format::Bracket(
+                order
+                    .into_iter()
+                    .map(|seed| (seed < entrants.len()).then_some(seed))
+                    .collect(),
+            ),
+            entrants,
+            results: Vec::new(),
+        }
+    }
+
+    /// The match to play now, if any are left.
+    pub fn next_fixture(&self) -> Option<[usize; 2]> {
+        match &self.format {
+            Format::RoundRobin(schedule) => schedule.get(self.results.len()).copied(),
+            Format::Bracket(_) => self
+                .rounds()
+                .iter()
+                .flat_map(|round| round.chunks(2))
+                .find_map(|pair| match *pair {
+                    [Some(a), Some(b)] if self.winner_of([a, b]).is_none() => Some([a, b]),
+                    _ => None,
+                }),
+        }
+    }
+
+    /// A bracket's rounds so far, the first round first and the champion's
+    /// single seat last. After the first round `None` is a seat waiting on
+    /// a match; a round robin has none.
+    pub fn rounds(&self) -> Vec<Vec<Option<usize>>> {
+        let Format::Bracket(seats) = &self.format else {
+            return Vec::new();
+        };
+        let mut rounds = vec![seats.clone()];
+        while let Some(round) = rounds.last().filter(|round| round.len() > 1) {
+            let first = rounds.len() == 1;
+            let next = round
+                .chunks(2)
+                .map(|pair| match (pair[0], pair[1]) {
+                    (Some(a), Some(b)) => self.winner_of([a, b]),
+                    (Some(a), None) | (None, Some(a)) if first => Some(a),
+                    _ => None,
+                })
+                .collect();
+            rounds.push(next);
+        }
+        rounds
+    }
+
+    /// Whoever has won the whole bracket.
+    pub fn champion(&self) -> Option<usize> {
+        self.rounds().last()?.first().copied().flatten()
+    }
+
+    fn winner_of(&self, entrants: [usize; 2]) -> Option<usize> {
+        self.results
+            .iter()
+            .find(|result| result.entrants == entrants)
+            .map(|result| result.entrants[result.winner])
+    }
+
+    /// Everyone's results so far, best first: most wins, then the best point
+    /// difference, then the most points scored.
+    pub fn standings(&self) -> Vec<Standing> {
+        let mut table: Vec<Standing> = self
+            .entrants
+            .iter()
+            .map(|name| Standing {
+                name: name.clone(),
+                played: 0,
+                wins: 0,
+                losses: 0,
+                points_for: 0,
+                points_against: 0,
+            })
+            .collect();
+        for result in &self.results {
+            for (seat, &entrant) in result.entrants.iter().enumerate() {
+                let line = &mut table[entrant];
+                line.played += 1;
+                if result.winner == seat {
+                    line.wins += 1;
+                } else {
+                    line.losses += 1;
+                }
+                line.points_for += result.points[seat];
+                line.points_against += result.points[1 - seat];
+            }
+        }
+        table.sort_by(|a, b| {
+            b.wins
+                .cmp(&a.wins)
+                .then(b.difference().cmp(&a.difference()))
+                .then(b.points_for.cmp(&a.points_for))
+        });
+        table
+    }
+
+    fn export(&self) -> std::io::Result<PathBuf> {
+        let standings = self.standings();
+        let file = StandingsFile {
+            date: today(),
+            standings: standings
+                .iter()
+                .map(|standing| StandingLine {
+                    standing,
+                    point_difference: standing.difference(),
+                })
+                .collect(),
+        };
+        fs::create_dir_all(TOURNAMENT_DIR)?;
+        let path = PathBuf::from(TOURNAMENT_DIR).join(format!("standings-{}.json", file.date));
+        fs::write(&path, serde_json::to_string_pretty(&file)?)?;
+        Ok(path)
+    }
+}
+
+#[derive(Component)]
+struct StandingsTable;
+
+// the arena's first two sides are the fixture's; anyone else keeps their name
+fn name_players(mut query: Query<(&mut Player, &Side)>, tournament: Res<Tournament>) {
+    let Some(fixture) = tournament.next_fixture() else {
+        return;
+    };
+    for (mut player, side) in &mut query {
+        let Some(&entrant) = fixture.get(side.0) else {
+            continue;
+        };
+        if player.name != tournament.entrants[entrant] {
+            player.name.clone_from(&tournament.entrants[entrant]);
+        }
+    }
+}
+
+pub fn record_result(
+    mut tournament: ResMut<Tournament>,
+    mut toasts: EventWriter<Toast>,
+    game_state: Res<GameState>,
+    tunables: Res<Tunables>,
+) {
+    let (Some(entrants), Some(winner)) = (
+        tournament.next_fixture(),
+        match_winner(&game_state.games, tunables.best_of),
+    ) else {
+        return;
+    };
+    let mut total = [0; 2];
+    for &score in &game_state.games {
+        let [first, far] = points(score);
+        total[0] += first;
+        total[1] += far;
+    }
+    tournament.results.push(Fixture {
+        entrants,
+        points: total,
+        winner,
+    });
+
+    if tournament.next_fixture().is_none() {
+        match tournament.export() {
+            Ok(path) => toasts.send(Toast(format!("Saved {}", path.display()))),
+            Err(err) => {
+                error!("couldn't write the standings: {err}");
+                toasts.send(Toast("Couldn't save the standings".to_owned()));
+            }
+        }
+    }
+}
+
+fn spawn_standings(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    tournament: Res<Tournament>,
+) {
+    let font = asset_server.load("fonts/DejaVuSans-Bold.ttf");
+    let text = |value: String, font_size: f32| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font: font.clone(),
+                font_size,
+                color: Color::WHITE,
+            },
+        )
+    };
+    let bracket = matches!(tournament.format, Format::Bracket(_));
+    let title = match (bracket, tournament.next_fixture(), tournament.champion()) {
+        (true, _, Some(champion)) => {
+            format!("CHAMPION: {}", tournament.entrants[champion].to_uppercase())
+        }
+        (true, ..) => "BRACKET".to_owned(),
+        (false, Some(_), _) => "STANDINGS".to_owned(),
+        (false, None, _) => "FINAL STANDINGS".to_owned(),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(20.),
+                        top: Val::Px(20.),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                // over the game-over screen's shade
+                z_index: ZIndex::Global(11),
+                ..default()
+            },
+            StandingsTable,
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(title, 24.));
+            if bracket {
+                spawn_rounds(parent, &tournament, &text);
+            } else {
+                for (place, standing) in tournament.standings().iter().enumerate() {
+                    parent.spawn(text(
+                        format!(
+                            "{}. {}   W{} L{}   {:+}",
+                            place + 1,
+                            standing.name,
+                            standing.wins,
+                            standing.losses,
+                            standing.difference()
+                        ),
+                        18.,
+                    ));
+                }
+            }
+            if let Some([first, far]) = tournament.next_fixture() {
+                parent.spawn(text(
+                    format!(
+                        "NEXT: {} vs {}",
+                        tournament.entrants[first], tournament.entrants[far]
+                    ),
+                    18.,
+                ));
+            }
+        });
+}
+
+// a column a round, spread out so each pair sits level with the seat its
+// winner goes on to
+fn spawn_rounds(
+    parent: &mut ChildBuilder,
+    tournament: &Tournament,
+    text: &impl Fn(String, f32) -> TextBundle,
+) {
+    let rounds = tournament.rounds();
+    let height = rounds[0].len() as f32 * BRACKET_LINE_HEIGHT;
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::vertical(Val::Px(8.)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (round, seats) in rounds.iter().enumerate() {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(BRACKET_COLUMN_WIDTH), Val::Px(height)),
+                            flex_direction: FlexDirection::Column,
+                            justify_content: JustifyContent::SpaceAround,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for seat in seats {
+                            let name = match seat {
+                                Some(entrant) => tournament.entrants[*entrant].clone(),
+                                None if round == 0 => "(bye)".to_owned(),
+                                None => "-".to_owned(),
+                            };
+                            parent.spawn(text(name, 16.));
+                        }
+                    });
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn everyone_plays_everyone_once() {
+        let names = ["Ana", "Ben", "Cho", "Dee", "Eli"].map(str::to_owned);
+        let mut tournament = Tournament::round_robin(names.to_vec());
+        let Format::RoundRobin(schedule) = &tournament.format else {
+            panic!("not a round robin");
+        };
+        assert_eq!(schedule.len(), 10);
+        for a in 0..5 {
+            for b in (a + 1)..5 {
+                let meetings = schedule
+                    .iter()
+                    .filter(|pair| pair.contains(&a) && pair.contains(&b))
+                    .count();
+                assert_eq!(meetings, 1);
+            }
+        }
+
+        // Ana and Cho both win once; Cho by more
+        tournament.results = vec![
+            Fixture {
+                entrants: [0, 1],
+                points: [11, 9],
+                winner: 0,
+            },
+            Fixture {
+                entrants: [2, 3],
+                points: [11, 2],
+                winner: 0,
+            },
+        ];
+        let standings = tournament.standings();
+        assert_eq!(standings[0].name, "Cho");
+        assert_eq!(standings[1].name, "Ana");
+        assert_eq!(standings[1].difference(), 2);
+        assert_eq!(standings.last().unwrap().name, "Dee");
+    }
+
+    #[test]
+    fn bracket_sends_winners_on() {
+        let names = ["Ana", "Ben", "Cho", "Dee", "Eli"].map(str::to_owned);
+        let mut tournament = Tournament::bracket(names.to_vec());
+        // the top three seeds get byes
+        assert_eq!(
+            tournament.format,
+            Format::Bracket(vec![
+                Some(0),
+                None,
+                Some(3),
+                Some(4),
+                Some(1),
+                None,
+                Some(2),
+                None
+            ])
+        );
+
+        let play = |tournament: &mut Tournament, winner| {
+            let entrants = tournament.next_fixture().unwrap();
+            tournament.results.push(Fixture {
+                entrants,
+                points: [11, 5],
+                winner,
+            });
+            entrants
+        };
+        assert_eq!(play(&mut tournament, 1), [3, 4]);
+        assert_eq!(play(&mut tournament, 0), [0, 4]);
+        assert_eq!(play(&mut tournament, 1), [1, 2]);
+        assert_eq!(tournament.champion(), None);
+        assert_eq!(play(&mut tournament, 0), [0, 2]);
+        assert_eq!(tournament.next_fixture(), None);
+        assert_eq!(tournament.champion(), Some(0));
+        assert_eq!(tournament.rounds().len(), 4);
+    }
+}
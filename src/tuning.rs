@@ -0,0 +1,94 @@
+//! Live tuning panel, built with the `dev` feature. An egui side panel edits
+//! [`Tunables`], the survival curve and the audio options in place and has
+//! buttons to respawn the ball and reset the score.
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts, EguiPlugin};
+
+use crate::{
+    announcer::AnnouncerSettings, arena::Arena, physics::serve_dir, spawn_ball,
+    survival::DifficultyCurve, Ball, BallAssets, GameRng, GameState, Tunables,
+};
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugin(EguiPlugin);
+        }
+        app.add_system(tuning_panel);
+    }
+}
+
+fn tuning_panel(
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    mut tunables: ResMut<Tunables>,
+    (mut curve, mut announcer): (ResMut<DifficultyCurve>, ResMut<AnnouncerSettings>),
+    mut game_state: ResMut<GameState>,
+    query_ball: Query<Entity, With<Ball>>,
+    (ball_assets, arena, mut rng): (Option<Res<BallAssets>>, Res<Arena>, ResMut<GameRng>),
+) {
+    let mut respawn = false;
+
+    egui::SidePanel::right("tuning").show(contexts.ctx_mut(), |ui| {
+        ui.heading("Tuning");
+        ui.add(egui::Slider::new(&mut tunables.speed, Tunables::SPEED_RANGE).text("speed"));
+        ui.add(egui::Slider::new(&mut tunables.ramp, 1.0..=4.0).text("bounce ramp"));
+        ui.add(
+            egui::Slider::new(&mut tunables.paddle_scale, Tunables::PADDLE_SCALE_RANGE)
+                .text("paddle width"),
+        );
+        ui.add(
+            egui::Slider::new(&mut tunables.paddle_height, Tunables::PADDLE_HEIGHT_RANGE)
+                .text("paddle height"),
+        );
+        ui.add(
+            egui::Slider::new(&mut tunables.ball_radius, Tunables::BALL_RADIUS_RANGE)
+                .text("ball radius"),
+        );
+        ui.add(
+            egui::Slider::new(&mut tunables.ball_scale, Tunables::BALL_SCALE_RANGE)
+                .text("ball size"),
+        );
+        ui.add(egui::Slider::new(&mut tunables.gravity, Tunables::GRAVITY_RANGE).text("gravity"));
+        ui.add(egui::Slider::new(&mut tunables.idle_timeout, 0.0..=120.0).text("idle pause (s)"));
+        ui.add(egui::Slider::new(&mut tunables.rescue_after, 0.0..=30.0).text("ball rescue (s)"));
+        ui.add(
+            egui::Slider::new(&mut tunables.point_limit, Tunables::POINT_LIMIT_RANGE)
+                .text("first to"),
+        );
+        if ui.button("Defaults").clicked() {
+            *tunables = Tunables::default();
+        }
+
+        ui.separator();
+        ui.heading("Survival");
+        ui.add(egui::Slider::new(&mut curve.every, 1.0..=60.0).text("speed-up every (s)"));
+        ui.add(egui::Slider::new(&mut curve.step, 0.0..=0.5).text("speed-up step"));
+
+        ui.separator();
+        ui.heading("Audio");
+        ui.checkbox(&mut announcer.enabled, "announcer");
+        ui.add(egui::Slider::new(&mut announcer.volume, 0.0..=1.0).text("announcer volume"));
+
+        ui.separator();
+        respawn = ui.button("Respawn ball").clicked();
+        if ui.button("Reset score").clicked() {
+            game_state.score = (0, 0);
+        }
+    });
+
+    if let (true, Some(ball_assets)) = (respawn, ball_assets) {
+        for entity in &query_ball {
+            commands.entity(entity).despawn();
+        }
+        spawn_ball(
+            &mut commands,
+            &ball_assets,
+            arena.ball_spawn(),
+            serve_dir(&mut rng.0, arena.edge(arena.goals[0]).normal()),
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! Small UI tweens. A [`Tween`] eases one property of its entity (a node's
+//! left offset or its text's alpha) from one value to another after an
+//! optional delay, on real time so menus animate the same while play is
+//! paused. Finished tweens stay at their end value and drop off.
+
+use bevy::prelude::*;
+
+use crate::time_scale::{scaled, TimeScale};
+
+pub struct TweenPlugin;
+
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(animate_tweens);
+    }
+}
+
+/// The property a tween drives.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TweenLens {
+    /// `Style::position.left`, in pixels.
+    Left,
+    /// The alpha of every section of the entity's `Text`.
+    TextAlpha,
+}
+
+#[derive(Component, Clone, Debug)]
+pub struct Tween {
+    lens: TweenLens,
+    from: f32,
+    to: f32,
+    delay: f32,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Tween {
+    pub fn new(lens: TweenLens, from: f32, to: f32, duration: f32) -> Self {
+        Self {
+            lens,
+            from,
+            to,
+            delay: 0.,
+            duration,
+            elapsed: 0.,
+        }
+    }
+
+    /// This tween held at its start value for `delay` seconds first.
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Advances by `delta` seconds; returns the current value.
+    fn tick(&mut self, delta: f32) -> f32 {
+        self.elapsed += delta;
+        let t = ((self.elapsed - self.delay) / self.duration.max(f32::EPSILON)).clamp(0., 1.);
+        self.from + (self.to - self.from) * ease_out_cubic(t)
+    }
+
+    fn finished(&self) -> bool {
+        self.elapsed >= self.delay + self.duration
+    }
+}
+
+/// Fast at first, settling gently, for things arriving on screen.
+fn ease_out_cubic(t: f32) -> f32 {
+    1. - (1. - t).powi(3)
+}
+
+fn animate_tweens(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut Tween,
+        Option<&mut Style>,
+        Option<&mut Text>,
+        Option<&TimeScale>,
+    )>,
+    timer: Res<Time>,
+) {
+    for (entity, mut tween, style, text, time_scale) in &mut query {
+        let value = tween.tick(scaled(timer.raw_delta_seconds(), time_scale));
+        match (tween.lens, style, text) {
+            (TweenLens::Left, Some(mut style), _) => style.position.left = Val::Px(value),
+            (TweenLens::TextAlpha, _, Some(mut text)) => {
+                for section in &mut text.sections {
+                    section.style.color.set_a(value);
+                }
+            }
+            _ => {}
+        }
+        if tween.finished() {
+            commands.entity(entity).remove::<Tween>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn holds_through_the_delay_then_eases_to_the_end() {
+        let mut tween = Tween::new(TweenLens::Left, -100., 0., 1.).with_delay(0.5);
+        assert_eq!(tween.tick(0.25), -100.);
+        let halfway = tween.tick(0.75);
+        assert!(halfway > -50. && halfway < 0., "{halfway}");
+        assert_eq!(tween.tick(1.), 0.);
+        assert!(tween.finished());
+    }
+}
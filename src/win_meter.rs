@@ -0,0 +1,201 @@
+//! The win meter, an option for spectators and stream overlays: a bar under
+//! the score, filled from the left with the first goal's player's chance of
+//! taking the game. The chance treats each point left as a coin flip leaned
+//! by momentum, the share of the last [`MOMENTUM_POINTS`] points each side
+//! won, and races both sides to the point limit; an endless game weighs the
+//! score differential instead. It moves after each point.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    engine::ScheduleSetup, pause::starting_match, scoreboard::points, AppState, GameState, Tunables,
+};
+
+pub const MOMENTUM_POINTS: usize = 5;
+/// How far a run of points leans the next one, per point of the recent
+/// difference.
+const MOMENTUM_LEAN: f64 = 0.04;
+/// How far each point ahead leans an endless game.
+const LEAD_WEIGHT: f64 = 0.3;
+pub const METER_SIZE: Vec2 = Vec2::new(160., 6.);
+const FIRST_COLOR: Color = Color::WHITE;
+const FAR_COLOR: Color = Color::DARK_GRAY;
+
+pub struct WinMeterPlugin;
+
+impl Plugin for WinMeterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WinOdds>()
+            .add_systems_on_enter(AppState::Playing, reset_odds.run_if(starting_match))
+            .add_systems_during(
+                AppState::Playing,
+                follow_points.run_if(resource_changed::<GameState>()),
+            )
+            .add_systems_during(AppState::Playing, show_meter);
+    }
+}
+
+/// The game's run of points and the first side's chance of taking it.
+#[derive(Resource)]
+struct WinOdds {
+    /// Who won each of the latest points, oldest first.
+    recent: VecDeque<usize>,
+    /// Points as of the last one counted.
+    seen: [u32; 2],
+    chance: f32,
+}
+
+impl Default for WinOdds {
+    fn default() -> Self {
+        Self {
+            recent: VecDeque::new(),
+            seen: [0; 2],
+            chance: 0.5,
+        }
+    }
+}
+
+impl WinOdds {
+    /// Counts the points scored since the last call. A score that went
+    /// down, a new game or a rewind, starts the run over.
+    fn follow(&mut self, now: [u32; 2]) {
+        if now[0] < self.seen[0] || now[1] < self.seen[1] {
+            self.recent.clear();
+        } else {
+            for (side, (&seen, &now)) in self.seen.iter().zip(&now).enumerate() {
+                self.recent.extend((seen..now).map(|_| side));
+            }
+            while self.recent.len() > MOMENTUM_POINTS {
+                self.recent.pop_front();
+            }
+        }
+        self.seen = now;
+    }
+
+    /// The first side's lead in the latest points, from -1 to 1.
+    fn momentum(&self) -> f64 {
+        let first = self.recent.iter().filter(|&&side| side == 0).count() as f64;
+        (2. * first - self.recent.len() as f64) / MOMENTUM_POINTS as f64
+    }
+}
+
+/// The first side's chance of taking a game at `points` (first side first)
+/// to `limit`, 0 being endless, when it's ahead by `momentum` (-1 to 1) over
+/// the latest points.
+fn win_chance(points: [u32; 2], limit: u32, momentum: f64) -> f64 {
+    if limit == 0 {
+        let lead = f64::from(points[0]) - f64::from(points[1]);
+        return 1. / (1. + (-(lead * LEAD_WEIGHT + momentum)).exp());
+    }
+    let p = 0.5 + momentum * MOMENTUM_LEAN * MOMENTUM_POINTS as f64;
+    let [need, far_need] = points.map(|points| limit.saturating_sub(points) as usize);
+    // odds[i][j]: the first side's chance needing i points to the far side's j
+    let mut odds = vec![vec![0.; far_need + 1]; need + 1];
+    for i in 0..=need {
+        for j in 0..=far_need {
+            odds[i][j] = match (i, j) {
+                (0, _) => 1.,
+                (_, 0) => 0.,
+                _ => p * odds[i - 1][j] + (1. - p) * odds[i][j - 1],
+            };
+        }
+    }
+    odds[need][far_need]
+}
+
+#[derive(Component)]
+struct WinMeter;
+
+#[derive(Component)]
+struct WinMeterFill;
+
+/// The meter's bar, for the scoreboard to place under the score; it stays
+/// out of the layout while [`Tunables::win_meter`] is off.
+pub fn spawn_meter(parent: &mut ChildBuilder) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Px(METER_SIZE.x), Val::Px(METER_SIZE.y)),
+                    margin: UiRect::vertical(Val::Px(METER_SIZE.y / 2.)),
+                    display: Display::None,
+                    ..default()
+                },
+                background_color: FAR_COLOR.into(),
+                ..default()
+            },
+            WinMeter,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(50.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    background_color: FIRST_COLOR.into(),
+                    ..default()
+                },
+                WinMeterFill,
+            ));
+        });
+}
+
+fn reset_odds(mut odds: ResMut<WinOdds>) {
+    *odds = WinOdds::default();
+}
+
+fn follow_points(mut odds: ResMut<WinOdds>, game_state: Res<GameState>, tunables: Res<Tunables>) {
+    let now = points(game_state.score);
+    odds.follow(now);
+    odds.chance = win_chance(now, tunables.point_limit, odds.momentum()) as f32;
+}
+
+fn show_meter(
+    mut query_meter: Query<&mut Style, (With<WinMeter>, Without<WinMeterFill>)>,
+    mut query_fill: Query<&mut Style, With<WinMeterFill>>,
+    odds: Res<WinOdds>,
+    tunables: Res<Tunables>,
+) {
+    let display = if tunables.win_meter {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    for mut style in &mut query_meter {
+        if style.display != display {
+            style.display = display;
+        }
+    }
+    if odds.is_changed() {
+        for mut style in &mut query_fill {
+            style.size.width = Val::Percent(odds.chance * 100.);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leads_and_runs_move_the_odds() {
+        assert_eq!(win_chance([0, 0], 11, 0.), 0.5);
+        assert!(win_chance([8, 3], 11, 0.) > 0.9);
+        assert_eq!(win_chance([11, 3], 11, 0.), 1.);
+        assert_eq!(win_chance([3, 11], 11, 0.), 0.);
+        assert!(win_chance([5, 5], 11, 1.) > 0.6);
+        assert!(win_chance([2, 0], 0, 0.) > 0.6);
+
+        let mut odds = WinOdds::default();
+        odds.follow([3, 0]);
+        odds.follow([3, 3]);
+        assert_eq!(odds.recent, [0, 0, 1, 1, 1]);
+        assert!(odds.momentum() < 0.);
+        // the next game starts the run over
+        odds.follow([0, 0]);
+        assert_eq!(odds.momentum(), 0.);
+    }
+}
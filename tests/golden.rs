@@ -0,0 +1,86 @@
+//! Renders fixed scenes offscreen and compares them with the reference images
+//! in `tests/golden/`. Needs a GPU adapter; on headless machines run it on a
+//! software rasterizer:
+//!
+//!     WGPU_BACKEND=gl cargo test --features golden --test golden
+//!
+//! Set `UPDATE_GOLDEN=1` to rewrite the references after an intended visual
+//! change. Mismatching renders are written to `target/golden/` for review.
+
+use std::{env, fs, path::PathBuf};
+
+use image::RgbaImage;
+use pong_rs::golden::{render, Frame, GoldenScene};
+
+// per-channel difference that still counts as a match, for rasterizer noise
+// along antialiased edges
+const CHANNEL_TOLERANCE: u8 = 8;
+// share of pixels allowed to differ by more than that
+const MAX_MISMATCH: f64 = 0.005;
+
+fn reference_path(scene: GoldenScene) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", scene.name()))
+}
+
+fn to_image(frame: Frame) -> RgbaImage {
+    RgbaImage::from_raw(frame.width, frame.height, frame.rgba).expect("frame size mismatch")
+}
+
+fn mismatch(actual: &RgbaImage, expected: &RgbaImage) -> f64 {
+    let differing = actual
+        .pixels()
+        .zip(expected.pixels())
+        .filter(|(a, e)| {
+            a.0.iter()
+                .zip(e.0.iter())
+                .any(|(a, e)| a.abs_diff(*e) > CHANNEL_TOLERANCE)
+        })
+        .count();
+    differing as f64 / actual.pixels().len() as f64
+}
+
+// one test for every scene, since each render needs its own app and the
+// render device doesn't like being set up from several test threads at once
+#[test]
+fn scenes_match_references() {
+    let update = env::var_os("UPDATE_GOLDEN").is_some();
+    let mut failures = Vec::new();
+
+    for scene in GoldenScene::ALL {
+        let actual = to_image(render(scene));
+        let path = reference_path(scene);
+
+        if update {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            actual.save(&path).unwrap();
+            continue;
+        }
+
+        let expected = image::open(&path)
+            .unwrap_or_else(|err| panic!("missing reference {}: {err}", path.display()))
+            .to_rgba8();
+        let differing = if actual.dimensions() == expected.dimensions() {
+            mismatch(&actual, &expected)
+        } else {
+            1.
+        };
+
+        if differing > MAX_MISMATCH {
+            let out = PathBuf::from(env!("CARGO_TARGET_TMPDIR"))
+                .join("../golden")
+                .join(format!("{}.png", scene.name()));
+            fs::create_dir_all(out.parent().unwrap()).unwrap();
+            actual.save(&out).unwrap();
+            failures.push(format!(
+                "{}: {:.2}% of pixels differ, render saved to {}",
+                scene.name(),
+                differing * 100.,
+                out.display()
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
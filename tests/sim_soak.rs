@@ -0,0 +1,125 @@
+//! Soak tests for the headless simulation: long seeded runs checked after every
+//! step. `cargo test --release -- --ignored` runs the multi-million step soak.
+
+use bevy::prelude::*;
+use pong_rs::{
+    arena::Arena,
+    physics::MAX_SWEEP_BOUNCES,
+    sim::{simulate_steps, SimConfig, Simulation},
+    DEFAULT_SPEED,
+};
+
+// fastest serve `physics::serve_dir` can produce; bounces only ever redirect it
+// and splits slow it down
+const MAX_DIR: f32 = 10. * std::f32::consts::SQRT_2;
+// every bounce in a frame doubles the multiplier
+const MAX_MULTIPLIER: f32 = DEFAULT_SPEED * (1 << MAX_SWEEP_BOUNCES) as f32;
+const EPSILON: f32 = 1e-3;
+
+fn configs() -> Vec<(&'static str, SimConfig)> {
+    ["square", "hex", "triangle"]
+        .into_iter()
+        .flat_map(|name| {
+            let arena = Arena::from_name(name).unwrap();
+            [1, 4].map(|balls| {
+                (
+                    name,
+                    SimConfig {
+                        arena: arena.clone(),
+                        balls,
+                        ..default()
+                    },
+                )
+            })
+        })
+        .collect()
+}
+
+fn check_invariants(sim: &Simulation, label: &str) {
+    let config = sim.config();
+    // a ball can overshoot a wall by a frame's travel before it's turned around
+    let max_step = MAX_DIR * MAX_MULTIPLIER * config.dt;
+
+    assert!(
+        sim.balls().len() <= config.max_balls.max(config.balls),
+        "{label}: {} balls in play",
+        sim.balls().len()
+    );
+    assert!(
+        sim.paddle().is_finite(),
+        "{label}: paddle at {}",
+        sim.paddle()
+    );
+
+    for ball in sim.balls() {
+        let (pos, speed) = (ball.translation, ball.speed);
+        assert!(
+            pos.is_finite() && speed.dir.is_finite() && speed.speed_multiplier.is_finite(),
+            "{label}: non-finite ball {ball:?}"
+        );
+        assert!(
+            speed.dir.length() <= MAX_DIR + EPSILON,
+            "{label}: ball too fast {ball:?}"
+        );
+        assert!(
+            speed.speed_multiplier <= MAX_MULTIPLIER,
+            "{label}: multiplier too high {ball:?}"
+        );
+
+        for wall in config.arena.walls() {
+            assert!(
+                wall.signed_distance(pos.truncate()) > -max_step,
+                "{label}: ball escaped through a wall {ball:?}"
+            );
+        }
+        for goal in config.arena.goal_lines() {
+            assert!(
+                goal.signed_distance(pos.truncate()) > 0.,
+                "{label}: ball left behind the goal line {ball:?}"
+            );
+        }
+    }
+}
+
+fn soak(seeds: u64, steps: u64) {
+    for (name, config) in configs() {
+        for seed in 0..seeds {
+            let label = format!("{name} x{} seed {seed}", config.balls);
+            let mut sim = Simulation::new(config.clone(), seed);
+            for _ in 0..steps {
+                sim.step();
+                check_invariants(&sim, &label);
+            }
+        }
+    }
+}
+
+#[test]
+fn short_runs_hold_invariants() {
+    soak(8, 5_000);
+}
+
+#[test]
+fn same_seed_replays_identically() {
+    let config = SimConfig {
+        balls: 3,
+        ..default()
+    };
+    let a = simulate_steps(config.clone(), 42, 10_000);
+    let b = simulate_steps(config, 42, 10_000);
+
+    assert!(a.score() > 0, "nothing reached the goal");
+    assert_eq!(a.score(), b.score());
+    assert_eq!(a.paddle(), b.paddle());
+    assert_eq!(a.balls().len(), b.balls().len());
+    for (a, b) in a.balls().iter().zip(b.balls()) {
+        assert_eq!(a.translation, b.translation);
+        assert_eq!(a.speed.dir, b.speed.dir);
+    }
+}
+
+#[test]
+#[ignore = "multi-million step soak, run with --release -- --ignored"]
+fn long_soak_holds_invariants() {
+    soak(64, 200_000);
+}